@@ -0,0 +1,20 @@
+/*!
+This is a contract that implements hash-time-locked atomic swaps (HTLCs) of native and cw20
+tokens. It can be used for trustless trades with counterparties on other chains, even without
+IBC, as long as both chains support sha256 hash locks.
+
+A swap is created with a sha256 hash, a recipient and an expiration. Anyone who knows the
+preimage of the hash can release the tokens to the recipient before the swap expires. Revealing
+the preimage on one chain lets the counterparty release the matching swap on the other chain.
+Once expired, the swap can only be refunded to its creator.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-atomic-swap/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
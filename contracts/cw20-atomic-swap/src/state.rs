@@ -0,0 +1,41 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, BlockInfo, Order, StdResult, Storage};
+use cw20::Balance;
+use cw_storage_plus::{Bound, Map};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct AtomicSwap {
+    /// This is the sha-256 hash of the preimage
+    pub hash: Binary,
+    pub recipient: Addr,
+    pub source: Addr,
+    pub expires: Expiration,
+    /// Balance in native tokens, or cw20 token
+    pub balance: Balance,
+}
+
+impl AtomicSwap {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_expired(block)
+    }
+}
+
+pub const SWAPS: Map<&str, AtomicSwap> = Map::new("atomic_swap");
+
+/// This returns the list of ids for all active swaps, starting after `start_after`
+pub fn all_swap_ids(
+    storage: &dyn Storage,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    SWAPS
+        .keys(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
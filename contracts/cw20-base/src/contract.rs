@@ -2,26 +2,30 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::Order::Ascending;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128,
 };
 
 use cw2::set_contract_version;
 use cw20::{
     BalanceResponse, Cw20Coin, Cw20ReceiveMsg, DownloadLogoResponse, EmbeddedLogo, Logo, LogoInfo,
-    MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+    MarketingInfoResponse, MetadataResponse, MinterResponse, TokenInfoResponse,
 };
 use cw_utils::ensure_from_older_version;
+use sha2::{Digest, Sha256};
 
 use crate::allowances::{
     execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
     execute_transfer_from, query_allowance,
 };
-use crate::enumerable::{query_all_accounts, query_owner_allowances, query_spender_allowances};
+use crate::enumerable::{
+    query_all_accounts, query_all_metadata, query_owner_allowances, query_spender_allowances,
+};
 use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::state::{
     MinterData, TokenInfo, ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, LOGO, MARKETING_INFO,
-    TOKEN_INFO,
+    METADATA, TOKEN_INFO,
 };
 
 // version info for migration info
@@ -29,6 +33,12 @@ const CONTRACT_NAME: &str = "crates.io:cw20-base";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const LOGO_SIZE_CAP: usize = 5 * 1024;
+/// Maximum width and height of an embedded PNG logo
+const LOGO_MAX_DIMENSION: u32 = 1024;
+
+const METADATA_KEY_CAP: usize = 64;
+const METADATA_VALUE_CAP: usize = 1024;
+const METADATA_ENTRIES_CAP: usize = 32;
 
 /// Checks if data starts with XML preamble
 fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
@@ -53,6 +63,272 @@ fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
     // comments presence inside of preable, but it is probably not worth it.
 }
 
+/// Skips the markup which is allowed around the root element: whitespace, comments and
+/// processing instructions. Document type declarations are rejected, as the entities they
+/// declare are expanded by XML parsers and could hide unsafe content.
+fn skip_xml_prolog(mut data: &[u8]) -> Result<&[u8], ContractError> {
+    loop {
+        data = trim_start(data);
+        let terminator: &[u8] = if data.starts_with(b"<!--") {
+            b"-->"
+        } else if data.starts_with(b"<?") {
+            b"?>"
+        } else if is_declaration(data.strip_prefix(b"<").unwrap_or_default()) {
+            return Err(ContractError::UnsafeSvg {});
+        } else if data.starts_with(b"<!") {
+            return Err(ContractError::InvalidSvg {});
+        } else {
+            return Ok(data);
+        };
+        data = skip_past(data, terminator).ok_or(ContractError::InvalidSvg {})?;
+    }
+}
+
+/// Returns the remainder of `data` after the first occurrence of `pattern`
+fn skip_past<'a>(data: &'a [u8], pattern: &[u8]) -> Option<&'a [u8]> {
+    data.windows(pattern.len())
+        .position(|w| w == pattern)
+        .map(|pos| &data[pos + pattern.len()..])
+}
+
+fn trim_start(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+/// Checks if `data` (just past a `<`) starts a `<!DOCTYPE` or `<!ENTITY` declaration
+fn is_declaration(data: &[u8]) -> bool {
+    [b"!DOCTYPE".as_slice(), b"!ENTITY"].iter().any(|keyword| {
+        data.len() >= keyword.len() && data[..keyword.len()].eq_ignore_ascii_case(keyword)
+    })
+}
+
+fn is_name_end(c: u8) -> bool {
+    c.is_ascii_whitespace() || c == b'/' || c == b'>' || c == b'='
+}
+
+/// Elements which run scripts or embed other documents, matched by their local name
+const UNSAFE_ELEMENTS: [&[u8]; 5] = [
+    b"script",
+    b"foreignObject",
+    b"iframe",
+    b"embed",
+    b"object",
+];
+
+/// Decodes the character references of an attribute value (eg. `&#106;` or `&colon;`), so they
+/// cannot hide a `javascript:` url. Fails on references which cannot be decoded, as a parser
+/// could still expand them to anything.
+fn decode_references(value: &[u8]) -> Result<Vec<u8>, ContractError> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut rest = value;
+    while let Some((&c, tail)) = rest.split_first() {
+        rest = tail;
+        if c != b'&' {
+            decoded.push(c);
+            continue;
+        }
+        let reference = match rest.strip_prefix(b"#") {
+            // browsers also accept numeric references without the trailing `;`
+            Some(number) => {
+                let (radix, digits) = match number.first() {
+                    Some(b'x') | Some(b'X') => (16, &number[1..]),
+                    _ => (10, number),
+                };
+                let len = digits
+                    .iter()
+                    .position(|c| !(*c as char).is_digit(radix))
+                    .unwrap_or(digits.len());
+                let skipped = number.len() - digits.len() + len;
+                std::str::from_utf8(&digits[..len])
+                    .ok()
+                    .and_then(|digits| u32::from_str_radix(digits, radix).ok())
+                    .and_then(char::from_u32)
+                    .map(|c| {
+                        let end = skipped + 1 + usize::from(rest.get(skipped + 1) == Some(&b';'));
+                        (c, end)
+                    })
+            }
+            None => {
+                let len = rest.iter().position(|c| *c == b';').unwrap_or(rest.len());
+                let c = match &rest[..len] {
+                    b"colon" => Some(':'),
+                    b"Tab" => Some('\t'),
+                    b"NewLine" => Some('\n'),
+                    b"amp" => Some('&'),
+                    b"lt" => Some('<'),
+                    b"gt" => Some('>'),
+                    b"quot" => Some('"'),
+                    b"apos" => Some('\''),
+                    _ => None,
+                };
+                c.filter(|_| len < rest.len()).map(|c| (c, len + 1))
+            }
+        };
+        match reference {
+            Some((c, len)) => {
+                let mut buf = [0; 4];
+                decoded.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                rest = &rest[len..];
+            }
+            None => return Err(ContractError::InvalidSvg {}),
+        }
+    }
+    Ok(decoded)
+}
+
+/// Browsers ignore whitespace and control characters in the scheme of a url, so they are
+/// removed before matching it
+fn is_javascript_url(value: &[u8]) -> Result<bool, ContractError> {
+    let url: Vec<u8> = decode_references(value)?
+        .into_iter()
+        .filter(|c| *c > b' ' && *c != 0x7f)
+        .collect();
+    Ok(url.len() >= 11 && url[..11].eq_ignore_ascii_case(b"javascript:"))
+}
+
+/// Parses a single start tag (`data` points just past the `<`), returning its name, whether
+/// it is self-closing and the remainder of the document. Fails if the tag contains an event
+/// handler attribute or an attribute value referring to a `javascript:` url.
+fn parse_start_tag(data: &[u8]) -> Result<(&[u8], bool, &[u8]), ContractError> {
+    let name_len = data
+        .iter()
+        .position(|c| is_name_end(*c))
+        .unwrap_or(data.len());
+    let (name, mut rest) = data.split_at(name_len);
+    if name.is_empty() {
+        return Err(ContractError::InvalidSvg {});
+    }
+
+    loop {
+        rest = trim_start(rest);
+        match rest.first() {
+            None => return Err(ContractError::InvalidSvg {}),
+            Some(b'>') => return Ok((name, false, &rest[1..])),
+            Some(b'/') if rest.get(1) == Some(&b'>') => return Ok((name, true, &rest[2..])),
+            _ => (),
+        }
+
+        let attr_len = rest
+            .iter()
+            .position(|c| is_name_end(*c))
+            .unwrap_or(rest.len());
+        let attr = &rest[..attr_len];
+        if attr.is_empty() {
+            return Err(ContractError::InvalidSvg {});
+        }
+        // Event handlers (`onload`, `onclick`, ...) are able to run arbitrary scripts
+        if attr.len() > 2 && attr[..2].eq_ignore_ascii_case(b"on") {
+            return Err(ContractError::UnsafeSvg {});
+        }
+        rest = trim_start(&rest[attr_len..]);
+
+        if let Some(after_eq) = rest.strip_prefix(b"=") {
+            let after_eq = trim_start(after_eq);
+            let (value, remainder) = match after_eq.first() {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let value = &after_eq[1..];
+                    let value_len = value
+                        .iter()
+                        .position(|c| *c == quote)
+                        .ok_or(ContractError::InvalidSvg {})?;
+                    (&value[..value_len], &value[value_len + 1..])
+                }
+                _ => {
+                    let value_len = after_eq
+                        .iter()
+                        .position(|c| c.is_ascii_whitespace() || *c == b'>')
+                        .unwrap_or(after_eq.len());
+                    after_eq.split_at(value_len)
+                }
+            };
+            if is_javascript_url(value)? {
+                return Err(ContractError::UnsafeSvg {});
+            }
+            rest = remainder;
+        }
+    }
+}
+
+/// Checks that the document after the XML preamble consists of a single `<svg>` root element
+/// with properly nested elements, and that no element inside of it can execute scripts or embed
+/// other documents.
+fn verify_svg_content(data: &[u8]) -> Result<(), ContractError> {
+    // Skip the preamble itself, it is validated by `verify_xml_preamble`
+    let data = skip_past(data, b"?>").ok_or(ContractError::InvalidXmlPreamble {})?;
+    let mut rest = skip_xml_prolog(data)?;
+
+    // names of the currently open elements, the first one being the root
+    let mut open: Vec<&[u8]> = vec![];
+    let mut roots = 0;
+    loop {
+        let next = rest.iter().position(|c| *c == b'<');
+        // only whitespace is allowed outside of the root element
+        let text = &rest[..next.unwrap_or(rest.len())];
+        if open.is_empty() && !trim_start(text).is_empty() {
+            return Err(ContractError::InvalidSvg {});
+        }
+        let pos = match next {
+            Some(pos) => pos,
+            None => break,
+        };
+
+        rest = &rest[pos + 1..];
+        if rest.starts_with(b"!--") {
+            rest = skip_past(rest, b"-->").ok_or(ContractError::InvalidSvg {})?;
+        } else if rest.starts_with(b"![CDATA[") && !open.is_empty() {
+            rest = skip_past(rest, b"]]>").ok_or(ContractError::InvalidSvg {})?;
+        } else if is_declaration(rest) {
+            return Err(ContractError::UnsafeSvg {});
+        } else if rest.starts_with(b"?") {
+            rest = skip_past(rest, b"?>").ok_or(ContractError::InvalidSvg {})?;
+        } else if rest.starts_with(b"!") {
+            return Err(ContractError::InvalidSvg {});
+        } else if let Some(end_tag) = rest.strip_prefix(b"/") {
+            let name_len = end_tag
+                .iter()
+                .position(|c| is_name_end(*c))
+                .unwrap_or(end_tag.len());
+            let (name, after_name) = end_tag.split_at(name_len);
+            if open.pop() != Some(name) {
+                return Err(ContractError::InvalidSvg {});
+            }
+            rest = trim_start(after_name)
+                .strip_prefix(b">")
+                .ok_or(ContractError::InvalidSvg {})?;
+        } else {
+            let (name, self_closing, remainder) = parse_start_tag(rest)?;
+            if open.is_empty() {
+                roots += 1;
+                if roots > 1 || name != b"svg" {
+                    return Err(ContractError::InvalidSvg {});
+                }
+            }
+            // Also catches namespaced elements like `<svg:script>`
+            let local_name = name.rsplit(|c| *c == b':').next().unwrap_or(name);
+            if UNSAFE_ELEMENTS
+                .iter()
+                .any(|unsafe_name| local_name.eq_ignore_ascii_case(unsafe_name))
+            {
+                return Err(ContractError::UnsafeSvg {});
+            }
+            if !self_closing {
+                open.push(name);
+            }
+            rest = remainder;
+        }
+    }
+
+    if roots == 1 && open.is_empty() {
+        Ok(())
+    } else {
+        Err(ContractError::InvalidSvg {})
+    }
+}
+
 /// Validates XML logo
 fn verify_xml_logo(logo: &[u8]) -> Result<(), ContractError> {
     verify_xml_preamble(logo)?;
@@ -60,7 +336,7 @@ fn verify_xml_logo(logo: &[u8]) -> Result<(), ContractError> {
     if logo.len() > LOGO_SIZE_CAP {
         Err(ContractError::LogoTooBig {})
     } else {
-        Ok(())
+        verify_svg_content(logo)
     }
 }
 
@@ -73,10 +349,51 @@ fn verify_png_logo(logo: &[u8]) -> Result<(), ContractError> {
     // 0x1a - dos control character, stop displaying rest of the file
     // 0x0a - unix style line ending
     const HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    // The first chunk must be IHDR, with 13 bytes of data (as big endian u32 length),
+    // starting with the width and height of the image as big endian u32 values
+    const IHDR: [u8; 8] = [0, 0, 0, 13, b'I', b'H', b'D', b'R'];
+    if logo.len() > LOGO_SIZE_CAP {
+        return Err(ContractError::LogoTooBig {});
+    } else if !logo.starts_with(&HEADER) {
+        return Err(ContractError::InvalidPngHeader {});
+    }
+
+    let ihdr = &logo[HEADER.len()..];
+    if !ihdr.starts_with(&IHDR) || ihdr.len() < IHDR.len() + 8 {
+        return Err(ContractError::InvalidPngHeader {});
+    }
+    let dimension = |offset: usize| {
+        let bytes = &ihdr[IHDR.len() + offset..IHDR.len() + offset + 4];
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    let (width, height) = (dimension(0), dimension(4));
+    if width == 0 || height == 0 || width > LOGO_MAX_DIMENSION || height > LOGO_MAX_DIMENSION {
+        Err(ContractError::InvalidPngDimensions {})
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates webp logo
+fn verify_webp_logo(logo: &[u8]) -> Result<(), ContractError> {
+    // WebP is a RIFF container: "RIFF", little endian u32 file size, then "WEBP"
+    if logo.len() > LOGO_SIZE_CAP {
+        Err(ContractError::LogoTooBig {})
+    } else if logo.len() < 12 || &logo[..4] != b"RIFF" || &logo[8..12] != b"WEBP" {
+        Err(ContractError::InvalidWebpHeader {})
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates jpeg logo
+fn verify_jpeg_logo(logo: &[u8]) -> Result<(), ContractError> {
+    // JPEG files start with the SOI marker (0xffd8), directly followed by another marker
+    const HEADER: [u8; 3] = [0xff, 0xd8, 0xff];
     if logo.len() > LOGO_SIZE_CAP {
         Err(ContractError::LogoTooBig {})
     } else if !logo.starts_with(&HEADER) {
-        Err(ContractError::InvalidPngHeader {})
+        Err(ContractError::InvalidJpegHeader {})
     } else {
         Ok(())
     }
@@ -87,10 +404,25 @@ fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
     match logo {
         Logo::Embedded(EmbeddedLogo::Svg(logo)) => verify_xml_logo(logo),
         Logo::Embedded(EmbeddedLogo::Png(logo)) => verify_png_logo(logo),
+        Logo::Embedded(EmbeddedLogo::Webp(logo)) => verify_webp_logo(logo),
+        Logo::Embedded(EmbeddedLogo::Jpeg(logo)) => verify_jpeg_logo(logo),
         Logo::Url(_) => Ok(()), // Any reasonable url validation would be regex based, probably not worth it
     }
 }
 
+/// Returns the hex-encoded sha256 hash of the embedded logo data, `None` for url logos
+fn logo_hash(logo: &Logo) -> Option<String> {
+    let data = match logo {
+        Logo::Embedded(EmbeddedLogo::Svg(data))
+        | Logo::Embedded(EmbeddedLogo::Png(data))
+        | Logo::Embedded(EmbeddedLogo::Webp(data))
+        | Logo::Embedded(EmbeddedLogo::Jpeg(data)) => data,
+        Logo::Url(_) => return None,
+    };
+    let hash = Sha256::digest(data.as_slice());
+    Some(hash.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
@@ -129,16 +461,17 @@ pub fn instantiate(
     TOKEN_INFO.save(deps.storage, &data)?;
 
     if let Some(marketing) = msg.marketing {
-        let logo = if let Some(logo) = marketing.logo {
+        let (logo, logo_hash) = if let Some(logo) = marketing.logo {
             verify_logo(&logo)?;
             LOGO.save(deps.storage, &logo)?;
 
+            let hash = logo_hash(&logo);
             match logo {
-                Logo::Url(url) => Some(LogoInfo::Url(url)),
-                Logo::Embedded(_) => Some(LogoInfo::Embedded),
+                Logo::Url(url) => (Some(LogoInfo::Url(url)), hash),
+                Logo::Embedded(_) => (Some(LogoInfo::Embedded), hash),
             }
         } else {
-            None
+            (None, None)
         };
 
         let data = MarketingInfoResponse {
@@ -149,6 +482,7 @@ pub fn instantiate(
                 .map(|addr| deps.api.addr_validate(&addr))
                 .transpose()?,
             logo,
+            logo_hash,
         };
         MARKETING_INFO.save(deps.storage, &data)?;
     }
@@ -230,6 +564,8 @@ pub fn execute(
             marketing,
         } => execute_update_marketing(deps, env, info, project, description, marketing),
         ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
+        ExecuteMsg::SetMetadata { key, value } => execute_set_metadata(deps, env, info, key, value),
+        ExecuteMsg::RemoveMetadata { key } => execute_remove_metadata(deps, env, info, key),
         ExecuteMsg::UpdateMinter { new_minter } => {
             execute_update_minter(deps, env, info, new_minter)
         }
@@ -508,6 +844,7 @@ pub fn execute_upload_logo(
 
     LOGO.save(deps.storage, &logo)?;
 
+    marketing_info.logo_hash = logo_hash(&logo);
     let logo_info = match logo {
         Logo::Url(url) => LogoInfo::Url(url),
         Logo::Embedded(_) => LogoInfo::Embedded,
@@ -520,6 +857,89 @@ pub fn execute_upload_logo(
     Ok(res)
 }
 
+/// Fails unless `sender` is the current marketing role
+fn ensure_marketing(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let marketing_info = MARKETING_INFO
+        .may_load(deps.storage)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    if marketing_info
+        .marketing
+        .as_ref()
+        .ok_or(ContractError::Unauthorized {})?
+        != sender
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn verify_metadata_key(key: &str) -> Result<(), ContractError> {
+    if key.is_empty() || key.len() > METADATA_KEY_CAP {
+        Err(ContractError::InvalidMetadataKey {
+            max: METADATA_KEY_CAP,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+pub fn execute_set_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    key: String,
+    value: String,
+) -> Result<Response, ContractError> {
+    ensure_marketing(deps.as_ref(), &info.sender)?;
+
+    verify_metadata_key(&key)?;
+    if value.is_empty() || value.len() > METADATA_VALUE_CAP {
+        return Err(ContractError::InvalidMetadataValue {
+            max: METADATA_VALUE_CAP,
+        });
+    }
+
+    // only adding a new key can exceed the cap, overwriting is always fine
+    if !METADATA.has(deps.storage, &key) {
+        let entries = METADATA
+            .keys_raw(deps.storage, None, None, Ascending)
+            .take(METADATA_ENTRIES_CAP)
+            .count();
+        if entries >= METADATA_ENTRIES_CAP {
+            return Err(ContractError::TooManyMetadataEntries {
+                max: METADATA_ENTRIES_CAP,
+            });
+        }
+    }
+
+    METADATA.save(deps.storage, &key, &value)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_metadata")
+        .add_attribute("key", key);
+    Ok(res)
+}
+
+pub fn execute_remove_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    key: String,
+) -> Result<Response, ContractError> {
+    ensure_marketing(deps.as_ref(), &info.sender)?;
+
+    if !METADATA.has(deps.storage, &key) {
+        return Err(StdError::not_found("metadata").into());
+    }
+    METADATA.remove(deps.storage, &key);
+
+    let res = Response::new()
+        .add_attribute("action", "remove_metadata")
+        .add_attribute("key", key);
+    Ok(res)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -549,6 +969,10 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
         QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::Metadata { key } => to_binary(&query_metadata(deps, key)?),
+        QueryMsg::AllMetadata { start_after, limit } => {
+            to_binary(&query_all_metadata(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -598,10 +1022,23 @@ pub fn query_download_logo(deps: Deps) -> StdResult<DownloadLogoResponse> {
             mime_type: "image/png".to_owned(),
             data: logo,
         }),
+        Logo::Embedded(EmbeddedLogo::Webp(logo)) => Ok(DownloadLogoResponse {
+            mime_type: "image/webp".to_owned(),
+            data: logo,
+        }),
+        Logo::Embedded(EmbeddedLogo::Jpeg(logo)) => Ok(DownloadLogoResponse {
+            mime_type: "image/jpeg".to_owned(),
+            data: logo,
+        }),
         Logo::Url(_) => Err(StdError::not_found("logo")),
     }
 }
 
+pub fn query_metadata(deps: Deps, key: String) -> StdResult<MetadataResponse> {
+    let value = METADATA.load(deps.storage, &key)?;
+    Ok(MetadataResponse { value })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let original_version =
@@ -616,6 +1053,16 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             ALLOWANCES_SPENDER.save(deps.storage, (&spender, &owner), &allowance)?;
         }
     }
+
+    // Logos uploaded before the hash was introduced are missing it
+    if let Some(mut marketing_info) = MARKETING_INFO.may_load(deps.storage)? {
+        if marketing_info.logo_hash.is_none() {
+            if let Some(logo) = LOGO.may_load(deps.storage)? {
+                marketing_info.logo_hash = logo_hash(&logo);
+                MARKETING_INFO.save(deps.storage, &marketing_info)?;
+            }
+        }
+    }
     Ok(Response::default())
 }
 
@@ -695,7 +1142,14 @@ mod tests {
         meta
     }
 
-    const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    // PNG signature followed by the IHDR chunk of a 1x1 image
+    const PNG_HEADER: [u8; 33] = [
+        0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, b'I', b'H', b'D',
+        b'R', 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+        0x15, 0xc4, 0x89,
+    ];
+    const PNG_HEADER_HASH: &str =
+        "4ffd8bb30991e3a6f28d1d03f1aedcd02ccf8e0cc16bb9e969e7bc2bda1ddf03";
 
     mod instantiate {
         use super::*;
@@ -843,6 +1297,7 @@ mod tests {
                         description: Some("Description".to_owned()),
                         marketing: Some(Addr::unchecked("marketing")),
                         logo: Some(LogoInfo::Url("url".to_owned())),
+                        logo_hash: None,
                     }
                 );
 
@@ -1509,6 +1964,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("marketing")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1562,6 +2018,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1615,6 +2072,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1668,6 +2126,7 @@ mod tests {
                     description: Some("Better description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1721,6 +2180,7 @@ mod tests {
                     description: None,
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1774,6 +2234,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("marketing")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1831,6 +2292,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1884,6 +2346,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: None,
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1933,6 +2396,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("new_url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -1982,6 +2446,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Embedded),
+                    logo_hash: Some(PNG_HEADER_HASH.to_owned()),
                 }
             );
 
@@ -2033,6 +2498,10 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Embedded),
+                    logo_hash: Some(
+                        "f049b14b8d6718e585a68fd4cf43141b2208b45af06ba46edfc23b12369a7042"
+                            .to_owned()
+                    ),
                 }
             );
 
@@ -2084,6 +2553,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -2141,6 +2611,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -2191,6 +2662,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -2242,6 +2714,7 @@ mod tests {
                     description: Some("Description".to_owned()),
                     marketing: Some(Addr::unchecked("creator")),
                     logo: Some(LogoInfo::Url("url".to_owned())),
+                    logo_hash: None,
                 }
             );
 
@@ -2252,5 +2725,412 @@ mod tests {
                 err
             );
         }
+
+        fn instantiate_with_url_logo(deps: DepsMut) {
+            let instantiate_msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 9,
+                initial_balances: vec![],
+                mint: None,
+                marketing: Some(InstantiateMarketingInfo {
+                    project: Some("Project".to_owned()),
+                    description: Some("Description".to_owned()),
+                    marketing: Some("creator".to_owned()),
+                    logo: Some(Logo::Url("url".to_owned())),
+                }),
+            };
+            instantiate(deps, mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+        }
+
+        fn upload_logo(deps: DepsMut, logo: EmbeddedLogo) -> Result<Response, ContractError> {
+            execute(
+                deps,
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::UploadLogo(Logo::Embedded(logo)),
+            )
+        }
+
+        #[test]
+        fn update_logo_webp_and_jpeg() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let webp = b"RIFF\x1a\x00\x00\x00WEBPVP8L\x0d\x00\x00\x00".to_vec();
+            upload_logo(deps.as_mut(), EmbeddedLogo::Webp(webp.clone().into())).unwrap();
+            assert_eq!(
+                query_download_logo(deps.as_ref()).unwrap(),
+                DownloadLogoResponse {
+                    mime_type: "image/webp".to_owned(),
+                    data: webp.into(),
+                }
+            );
+            let webp_hash = query_marketing_info(deps.as_ref()).unwrap().logo_hash;
+            assert!(webp_hash.is_some());
+
+            let jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+            upload_logo(deps.as_mut(), EmbeddedLogo::Jpeg(jpeg.clone().into())).unwrap();
+            assert_eq!(
+                query_download_logo(deps.as_ref()).unwrap(),
+                DownloadLogoResponse {
+                    mime_type: "image/jpeg".to_owned(),
+                    data: jpeg.into(),
+                }
+            );
+            let info = query_marketing_info(deps.as_ref()).unwrap();
+            assert_eq!(info.logo, Some(LogoInfo::Embedded));
+            assert!(info.logo_hash.is_some());
+            assert_ne!(info.logo_hash, webp_hash);
+
+            // switching back to url clears the hash
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::UploadLogo(Logo::Url("url".to_owned())),
+            )
+            .unwrap();
+            assert_eq!(query_marketing_info(deps.as_ref()).unwrap().logo_hash, None);
+        }
+
+        #[test]
+        fn update_logo_webp_and_jpeg_invalid() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let err =
+                upload_logo(deps.as_mut(), EmbeddedLogo::Webp(b"RIFF1234WEBX".into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidWebpHeader {});
+
+            let err = upload_logo(deps.as_mut(), EmbeddedLogo::Webp(b"RIFF".into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidWebpHeader {});
+
+            let err =
+                upload_logo(deps.as_mut(), EmbeddedLogo::Jpeg(PNG_HEADER.into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidJpegHeader {});
+
+            let jpeg = [&[0xff, 0xd8, 0xff][..], &[1; 6000][..]].concat();
+            let err = upload_logo(deps.as_mut(), EmbeddedLogo::Jpeg(jpeg.into())).unwrap_err();
+            assert_eq!(err, ContractError::LogoTooBig {});
+
+            assert_eq!(
+                query_marketing_info(deps.as_ref()).unwrap().logo,
+                Some(LogoInfo::Url("url".to_owned()))
+            );
+        }
+
+        #[test]
+        fn update_logo_png_invalid_ihdr() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            // signature only, no IHDR chunk
+            let err =
+                upload_logo(deps.as_mut(), EmbeddedLogo::Png(PNG_HEADER[..8].into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidPngHeader {});
+
+            // zero width
+            let mut img = PNG_HEADER;
+            img[19] = 0;
+            let err = upload_logo(deps.as_mut(), EmbeddedLogo::Png(img.into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidPngDimensions {});
+
+            // height over the limit
+            let mut img = PNG_HEADER;
+            img[20..24].copy_from_slice(&(LOGO_MAX_DIMENSION + 1).to_be_bytes());
+            let err = upload_logo(deps.as_mut(), EmbeddedLogo::Png(img.into())).unwrap_err();
+            assert_eq!(err, ContractError::InvalidPngDimensions {});
+
+            // maximal dimensions are fine
+            let mut img = PNG_HEADER;
+            img[16..20].copy_from_slice(&LOGO_MAX_DIMENSION.to_be_bytes());
+            img[20..24].copy_from_slice(&LOGO_MAX_DIMENSION.to_be_bytes());
+            upload_logo(deps.as_mut(), EmbeddedLogo::Png(img.into())).unwrap();
+        }
+
+        #[test]
+        fn update_logo_svg_valid_documents() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let valid = [
+                "<?xml version=\"1.0\"?><svg/>",
+                "<?xml version=\"1.0\"?>\n<!-- logo -->\n<?xml-stylesheet href=\"a.css\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width='10'>\n</svg>\n",
+                "<?xml version=\"1.0\"?><svg><g id=\"a\"><circle r=5 /></g><text>a > b</text></svg>",
+                "<?xml version=\"1.0\"?><svg><style><![CDATA[ .on { fill: red } ]]></style></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"https://example.com\" opacity=\"1\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><text>&amp; &#106;avascript:</text><a href=\"#javascript:\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"/&#9;\" title=\"&lt;&amp;&gt;\"/></svg>",
+            ];
+            for img in valid {
+                upload_logo(deps.as_mut(), EmbeddedLogo::Svg(img.as_bytes().into()))
+                    .unwrap_or_else(|err| panic!("{} rejected: {}", img, err));
+            }
+        }
+
+        #[test]
+        fn update_logo_svg_invalid_root() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let invalid = [
+                "<?xml version=\"1.0\"?>",
+                "<?xml version=\"1.0\"?><html></html>",
+                "<?xml version=\"1.0\"?><svg>",
+                "<?xml version=\"1.0\"?><svg></svg><svg></svg>trailing",
+                "<?xml version=\"1.0\"?><svg/><svg/>",
+                "<?xml version=\"1.0\"?><svg></svg><svg></svg>",
+                "<?xml version=\"1.0\"?><svg></svg><!-- --><svg/>",
+                "<?xml version=\"1.0\"?><svg><g></svg></g>",
+                "<?xml version=\"1.0\"?><svg></g></svg>",
+                "<?xml version=\"1.0\"?><svg></svg>text",
+                "<?xml version=\"1.0\"?><![CDATA[<svg/>]]><svg/>",
+                "<?xml version=\"1.0\"?><svg width=\"10></svg>",
+                "<?xml version=\"1.0\"?><!-- unterminated <svg></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"&unknown;\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"a&b\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"&#xFFFFFFFF;\"/></svg>",
+            ];
+            for img in invalid {
+                let err = upload_logo(deps.as_mut(), EmbeddedLogo::Svg(img.as_bytes().into()))
+                    .unwrap_err();
+                assert_eq!(err, ContractError::InvalidSvg {}, "{}", img);
+            }
+        }
+
+        #[test]
+        fn update_logo_svg_unsafe() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let unsafe_svgs = [
+                "<?xml version=\"1.0\"?><svg><script>alert(1)</script></svg>",
+                "<?xml version=\"1.0\"?><svg><SCRIPT>alert(1)</SCRIPT></svg>",
+                "<?xml version=\"1.0\"?><svg><svg:script>alert(1)</svg:script></svg>",
+                "<?xml version=\"1.0\"?><svg onload=\"alert(1)\"></svg>",
+                "<?xml version=\"1.0\"?><svg><g><rect OnClick='alert(1)'/></g></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\" JavaScript:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a xlink:href=javascript:alert(1)>x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"&#106;avascript:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"&#x6A;avascript&colon;alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"&#0000106avascript:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"java\tscript:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"java&#10;script:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><a href=\"\u{1}javascript:alert(1)\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><foreignObject><div/></foreignObject></svg>",
+                "<?xml version=\"1.0\"?><svg><svg:foreignobject/></svg>",
+                "<?xml version=\"1.0\"?><svg><iframe src=\"https://example.com\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><embed src=\"https://example.com\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><object data=\"evil.html\"/></svg>",
+                "<?xml version=\"1.0\"?><svg><svg:OBJECT data=\"evil.html\"></svg:OBJECT></svg>",
+                "<?xml version=\"1.0\"?><!DOCTYPE svg><svg/>",
+                "<?xml version=\"1.0\"?><!doctype svg><svg/>",
+                "<?xml version=\"1.0\"?><!DOCTYPE svg [<!ENTITY a \"> <!--\"><!ENTITY x \"javascript:alert(1)\">]><!-- --><svg><a href=\"&x;\">x</a></svg>",
+                "<?xml version=\"1.0\"?><svg><!ENTITY x \"javascript:alert(1)\"><a/></svg>",
+            ];
+            for img in unsafe_svgs {
+                let err = upload_logo(deps.as_mut(), EmbeddedLogo::Svg(img.as_bytes().into()))
+                    .unwrap_err();
+                assert_eq!(err, ContractError::UnsafeSvg {}, "{}", img);
+            }
+
+            assert_eq!(
+                query_marketing_info(deps.as_ref()).unwrap().logo,
+                Some(LogoInfo::Url("url".to_owned()))
+            );
+        }
+
+        #[test]
+        fn set_and_remove_metadata() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+            let info = mock_info("creator", &[]);
+
+            let err = query_metadata(deps.as_ref(), "twitter".to_owned()).unwrap_err();
+            assert!(matches!(err, StdError::NotFound { .. }));
+
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::SetMetadata {
+                    key: "twitter".to_owned(),
+                    value: "@cash".to_owned(),
+                },
+            )
+            .unwrap();
+            assert_eq!(res.messages, vec![]);
+            assert_eq!(
+                query_metadata(deps.as_ref(), "twitter".to_owned()).unwrap(),
+                MetadataResponse {
+                    value: "@cash".to_owned()
+                }
+            );
+
+            // overwrite
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::SetMetadata {
+                    key: "twitter".to_owned(),
+                    value: "@cash_token".to_owned(),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                query_metadata(deps.as_ref(), "twitter".to_owned())
+                    .unwrap()
+                    .value,
+                "@cash_token"
+            );
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::RemoveMetadata {
+                    key: "twitter".to_owned(),
+                },
+            )
+            .unwrap();
+            let err = query_metadata(deps.as_ref(), "twitter".to_owned()).unwrap_err();
+            assert!(matches!(err, StdError::NotFound { .. }));
+
+            // removing missing entry fails
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::RemoveMetadata {
+                    key: "twitter".to_owned(),
+                },
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+        }
+
+        #[test]
+        fn metadata_unauthorised() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::SetMetadata {
+                    key: "isin".to_owned(),
+                    value: "US0000000000".to_owned(),
+                },
+            )
+            .unwrap();
+
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("random", &[]),
+                ExecuteMsg::SetMetadata {
+                    key: "isin".to_owned(),
+                    value: "XX0000000000".to_owned(),
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("random", &[]),
+                ExecuteMsg::RemoveMetadata {
+                    key: "isin".to_owned(),
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            assert_eq!(
+                query_metadata(deps.as_ref(), "isin".to_owned())
+                    .unwrap()
+                    .value,
+                "US0000000000"
+            );
+
+            // no marketing info at all
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "creator", Uint128::new(100));
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::SetMetadata {
+                    key: "isin".to_owned(),
+                    value: "US0000000000".to_owned(),
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        #[test]
+        fn metadata_limits() {
+            let mut deps = mock_dependencies();
+            instantiate_with_url_logo(deps.as_mut());
+
+            let mut set = |key: String, value: String| {
+                execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info("creator", &[]),
+                    ExecuteMsg::SetMetadata { key, value },
+                )
+            };
+
+            let err = set("".to_owned(), "value".to_owned()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidMetadataKey {
+                    max: METADATA_KEY_CAP
+                }
+            );
+            let err = set("k".repeat(METADATA_KEY_CAP + 1), "value".to_owned()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidMetadataKey {
+                    max: METADATA_KEY_CAP
+                }
+            );
+            let err = set("key".to_owned(), "".to_owned()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidMetadataValue {
+                    max: METADATA_VALUE_CAP
+                }
+            );
+            let err = set("key".to_owned(), "v".repeat(METADATA_VALUE_CAP + 1)).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidMetadataValue {
+                    max: METADATA_VALUE_CAP
+                }
+            );
+
+            // maximal sizes are fine
+            set("k".repeat(METADATA_KEY_CAP), "v".repeat(METADATA_VALUE_CAP)).unwrap();
+
+            for i in 1..METADATA_ENTRIES_CAP {
+                set(format!("key{:02}", i), "value".to_owned()).unwrap();
+            }
+            let err = set("one_too_many".to_owned(), "value".to_owned()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::TooManyMetadataEntries {
+                    max: METADATA_ENTRIES_CAP
+                }
+            );
+            // existing entries can still be updated
+            set("key01".to_owned(), "updated".to_owned()).unwrap();
+        }
     }
 }
@@ -1,10 +1,10 @@
 use cosmwasm_std::{Deps, Order, StdResult};
 use cw20::{
-    AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse, AllowanceInfo,
-    SpenderAllowanceInfo,
+    AllAccountsResponse, AllAllowancesResponse, AllMetadataResponse, AllSpenderAllowancesResponse,
+    AllowanceInfo, MetadataInfo, SpenderAllowanceInfo,
 };
 
-use crate::state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES};
+use crate::state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, METADATA};
 use cw_storage_plus::Bound;
 
 // settings for pagination
@@ -78,6 +78,23 @@ pub fn query_all_accounts(
     Ok(AllAccountsResponse { accounts })
 }
 
+pub fn query_all_metadata(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllMetadataResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let metadata = METADATA
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(key, value)| MetadataInfo { key, value }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllMetadataResponse { metadata })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,7 +104,7 @@ mod tests {
     use cw20::{Cw20Coin, Expiration, TokenInfoResponse};
 
     use crate::contract::{execute, instantiate, query, query_token_info};
-    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::msg::{ExecuteMsg, InstantiateMarketingInfo, InstantiateMsg, QueryMsg};
 
     // this will set up the instantiation for other tests
     fn do_instantiate(mut deps: DepsMut, addr: &str, amount: Uint128) -> TokenInfoResponse {
@@ -316,4 +333,83 @@ mod tests {
                 .unwrap();
         assert_eq!(accounts.accounts, expected_order[3..].to_vec());
     }
+
+    #[test]
+    fn query_all_metadata_works() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![],
+            mint: None,
+            marketing: Some(InstantiateMarketingInfo {
+                project: None,
+                description: None,
+                marketing: Some("marketing".to_string()),
+                logo: None,
+            }),
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+
+        // nothing to start
+        let metadata = query_all_metadata(deps.as_ref(), None, None).unwrap();
+        assert_eq!(metadata.metadata, vec![]);
+
+        // inserted out of order
+        let info = mock_info("marketing", &[]);
+        for key in ["twitter", "audit", "isin"] {
+            let msg = ExecuteMsg::SetMetadata {
+                key: key.to_string(),
+                value: format!("{}-value", key),
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        // returned in key order
+        let metadata = query_all_metadata(deps.as_ref(), None, Some(2)).unwrap();
+        assert_eq!(
+            metadata.metadata,
+            vec![
+                MetadataInfo {
+                    key: "audit".to_string(),
+                    value: "audit-value".to_string()
+                },
+                MetadataInfo {
+                    key: "isin".to_string(),
+                    value: "isin-value".to_string()
+                },
+            ]
+        );
+
+        // next page
+        let metadata =
+            query_all_metadata(deps.as_ref(), Some("isin".to_string()), Some(2)).unwrap();
+        assert_eq!(
+            metadata.metadata,
+            vec![MetadataInfo {
+                key: "twitter".to_string(),
+                value: "twitter-value".to_string()
+            }]
+        );
+
+        // also works through the query entry point
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AllMetadata {
+                start_after: Some("twitter".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+        let metadata: AllMetadataResponse = from_binary(&res).unwrap();
+        assert_eq!(metadata.metadata, vec![]);
+    }
 }
@@ -33,6 +33,30 @@ pub enum ContractError {
     #[error("Invalid png header")]
     InvalidPngHeader {},
 
+    #[error("Invalid png dimensions")]
+    InvalidPngDimensions {},
+
+    #[error("Invalid webp header")]
+    InvalidWebpHeader {},
+
+    #[error("Invalid jpeg header")]
+    InvalidJpegHeader {},
+
+    #[error("SVG must have a single svg root element")]
+    InvalidSvg {},
+
+    #[error("SVG must not contain scripts, embedded documents or event handlers")]
+    UnsafeSvg {},
+
+    #[error("Metadata key must be between 1 and {max} bytes")]
+    InvalidMetadataKey { max: usize },
+
+    #[error("Metadata value must be between 1 and {max} bytes")]
+    InvalidMetadataValue { max: usize },
+
+    #[error("Cannot store more than {max} metadata entries")]
+    TooManyMetadataEntries { max: usize },
+
     #[error("Invalid expiration value")]
     InvalidExpiration {},
 
@@ -120,6 +120,17 @@ pub enum QueryMsg {
     /// contract.
     #[returns(cw20::DownloadLogoResponse)]
     DownloadLogo {},
+    /// Only with "marketing" extension
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    #[returns(cw20::MetadataResponse)]
+    Metadata { key: String },
+    /// Only with "marketing" extension
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    #[returns(cw20::AllMetadataResponse)]
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
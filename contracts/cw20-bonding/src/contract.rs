@@ -0,0 +1,666 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, to_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128,
+};
+
+use cw2::set_contract_version;
+use cw20::MinterResponse;
+use cw20_base::allowances::{
+    execute_decrease_allowance, execute_increase_allowance, execute_send_from,
+    execute_transfer_from, query_allowance,
+};
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_remove_metadata, execute_send, execute_set_metadata,
+    execute_transfer, execute_update_marketing, execute_upload_logo, query_balance,
+    query_download_logo, query_marketing_info, query_metadata, query_token_info,
+};
+use cw20_base::enumerable::{
+    query_all_accounts, query_all_metadata, query_owner_allowances, query_spender_allowances,
+};
+use cw_utils::{must_pay, nonpayable};
+
+use crate::curves::{Curve, DecimalPlaces};
+use crate::error::ContractError;
+use crate::msg::{CurveInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg, SpotPriceResponse};
+use crate::state::{CurveState, CURVE_STATE, CURVE_TYPE};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-bonding";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.reserve_denom.is_empty() {
+        return Err(ContractError::EmptyDenom {});
+    }
+    let factor = msg
+        .curve_type
+        .factor()
+        .map_err(|e| ContractError::InvalidCurve {
+            reason: e.to_string(),
+        })?;
+    if factor.is_zero() {
+        return Err(ContractError::InvalidCurve {
+            reason: "value and slope must be positive".to_string(),
+        });
+    }
+
+    // the contract is the only minter, minting only when receiving reserve tokens
+    let base_msg = cw20_base::msg::InstantiateMsg {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: env.contract.address.to_string(),
+            cap: None,
+        }),
+        marketing: msg.marketing,
+    };
+    cw20_base::contract::instantiate(deps.branch(), env, info, base_msg)?;
+
+    // overwrite the version set by cw20-base
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let places = DecimalPlaces::new(msg.decimals, msg.reserve_decimals);
+    let supply = CurveState::new(msg.reserve_denom, places);
+    CURVE_STATE.save(deps.storage, &supply)?;
+    CURVE_TYPE.save(deps.storage, &msg.curve_type)?;
+
+    Ok(Response::default())
+}
+
+/// Builds the curve configured on instantiation
+fn load_curve(storage: &dyn Storage) -> StdResult<Box<dyn Curve>> {
+    let curve_type = CURVE_TYPE.load(storage)?;
+    let state = CURVE_STATE.load(storage)?;
+    curve_type.to_curve(state.decimals)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let curve = load_curve(deps.storage)?;
+    do_execute(deps, env, info, msg, curve.as_ref())
+}
+
+/// We pull out logic here, so we can import this from another contract and set a different Curve.
+/// This contract only supports the curves of `CurveType`, but other contracts can plug in
+/// any implementation of `Curve`.
+pub fn do_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+    curve: &dyn Curve,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Buy {} => execute_buy(deps, env, info, curve),
+        ExecuteMsg::Sell { amount } => execute_sell(deps, env, info, curve, amount),
+
+        // these all come from cw20-base to implement the cw20 standard
+        ExecuteMsg::Transfer { recipient, amount } => {
+            Ok(execute_transfer(deps, env, info, recipient, amount)?)
+        }
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send(deps, env, info, contract, amount, msg)?),
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => Ok(execute_transfer_from(
+            deps, env, info, owner, recipient, amount,
+        )?),
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send_from(
+            deps, env, info, owner, contract, amount, msg,
+        )?),
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => Ok(execute_update_marketing(
+            deps,
+            env,
+            info,
+            project,
+            description,
+            marketing,
+        )?),
+        ExecuteMsg::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
+        ExecuteMsg::SetMetadata { key, value } => {
+            Ok(execute_set_metadata(deps, env, info, key, value)?)
+        }
+        ExecuteMsg::RemoveMetadata { key } => Ok(execute_remove_metadata(deps, env, info, key)?),
+    }
+}
+
+pub fn execute_buy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    curve: &dyn Curve,
+) -> Result<Response, ContractError> {
+    let mut state = CURVE_STATE.load(deps.storage)?;
+
+    let payment = must_pay(&info, &state.reserve_denom)?;
+
+    // calculate how many tokens can be purchased with this and mint them
+    state.reserve = state.reserve.checked_add(payment).map_err(StdError::from)?;
+    let new_supply = curve.supply(state.reserve)?;
+    if new_supply <= state.supply {
+        return Err(ContractError::NothingToMint {});
+    }
+    let minted = new_supply - state.supply;
+    state.supply = new_supply;
+    CURVE_STATE.save(deps.storage, &state)?;
+
+    // call into cw20-base to mint the token, call as self as no one else is allowed
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    execute_mint(deps, env, sub_info, info.sender.to_string(), minted)?;
+
+    let res = Response::new()
+        .add_attribute("action", "buy")
+        .add_attribute("from", info.sender)
+        .add_attribute("reserve", payment)
+        .add_attribute("supply", minted);
+    Ok(res)
+}
+
+pub fn execute_sell(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    curve: &dyn Curve,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+
+    // burn from the caller, this ensures there are tokens to cover this
+    execute_burn(deps.branch(), env, info.clone(), amount)?;
+
+    // calculate how many reserve tokens are released by burning them
+    let mut state = CURVE_STATE.load(deps.storage)?;
+    state.supply = state.supply.checked_sub(amount).map_err(StdError::from)?;
+    let new_reserve = curve.reserve(state.supply)?;
+    let released = state
+        .reserve
+        .checked_sub(new_reserve)
+        .map_err(StdError::from)?;
+    if released.is_zero() {
+        return Err(ContractError::NothingToRelease {});
+    }
+    state.reserve = new_reserve;
+    CURVE_STATE.save(deps.storage, &state)?;
+
+    // now send the tokens to the sender
+    let msg = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: coins(released.u128(), state.reserve_denom),
+    };
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute("action", "sell")
+        .add_attribute("from", info.sender)
+        .add_attribute("supply", amount)
+        .add_attribute("reserve", released);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let curve = load_curve(deps.storage)?;
+    do_query(deps, env, msg, curve.as_ref())
+}
+
+/// We pull out logic here, so we can import this from another contract and set a different Curve.
+pub fn do_query(deps: Deps, _env: Env, msg: QueryMsg, curve: &dyn Curve) -> StdResult<Binary> {
+    match msg {
+        // custom queries
+        QueryMsg::CurveInfo {} => to_binary(&query_curve_info(deps, curve)?),
+        QueryMsg::SpotPrice { supply } => to_binary(&query_spot_price(deps, curve, supply)?),
+        // inherited from cw20-base
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::Metadata { key } => to_binary(&query_metadata(deps, key)?),
+        QueryMsg::AllMetadata { start_after, limit } => {
+            to_binary(&query_all_metadata(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_curve_info(deps: Deps, curve: &dyn Curve) -> StdResult<CurveInfoResponse> {
+    let CurveState {
+        reserve,
+        supply,
+        reserve_denom,
+        ..
+    } = CURVE_STATE.load(deps.storage)?;
+
+    // This is based on the underlying token, eg. 1 TOKEN = 1.5 ATOM
+    let spot_price = curve.spot_price(supply)?;
+
+    Ok(CurveInfoResponse {
+        reserve,
+        supply,
+        spot_price,
+        reserve_denom,
+        curve_type: CURVE_TYPE.load(deps.storage)?,
+    })
+}
+
+pub fn query_spot_price(
+    deps: Deps,
+    curve: &dyn Curve,
+    supply: Option<Uint128>,
+) -> StdResult<SpotPriceResponse> {
+    let supply = match supply {
+        Some(supply) => supply,
+        None => CURVE_STATE.load(deps.storage)?.supply,
+    };
+    Ok(SpotPriceResponse {
+        supply,
+        spot_price: curve.spot_price(supply)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::decimal;
+    use crate::msg::CurveType;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, Addr, Decimal256, Empty, SubMsg};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_utils::PaymentError;
+
+    const DENOM: &str = "satoshi";
+    const CREATOR: &str = "creator";
+    const INVESTOR: &str = "investor";
+    const BUYER: &str = "buyer";
+
+    fn default_instantiate(
+        decimals: u8,
+        reserve_decimals: u8,
+        curve_type: CurveType,
+    ) -> InstantiateMsg {
+        InstantiateMsg {
+            name: "Bonded".to_string(),
+            symbol: "EPOXY".to_string(),
+            decimals,
+            reserve_denom: DENOM.to_string(),
+            reserve_decimals,
+            curve_type,
+            marketing: None,
+        }
+    }
+
+    fn get_balance<U: Into<String>>(deps: Deps, addr: U) -> Uint128 {
+        query_balance(deps, addr.into()).unwrap().balance
+    }
+
+    fn setup_test(deps: DepsMut, decimals: u8, reserve_decimals: u8, curve_type: CurveType) {
+        // this matches `linear_curve` test case from curves.rs
+        let creator = String::from(CREATOR);
+        let msg = default_instantiate(decimals, reserve_decimals, curve_type);
+        let info = mock_info(&creator, &[]);
+
+        // make sure we can instantiate with this
+        let res = instantiate(deps, mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+
+        // this matches `linear_curve` test case from curves.rs
+        let creator = String::from("creator");
+        let curve_type = CurveType::SquareRoot {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        let msg = default_instantiate(2, 8, curve_type.clone());
+        let info = mock_info(&creator, &[]);
+
+        // make sure we can instantiate with this
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // token info is proper
+        let token = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(&token.name, "Bonded");
+        assert_eq!(&token.symbol, "EPOXY");
+        assert_eq!(token.decimals, 2);
+        assert_eq!(token.total_supply, Uint128::zero());
+
+        // curve state is sensible
+        let curve = curve_type.to_curve(DecimalPlaces::new(2, 8)).unwrap();
+        let state = query_curve_info(deps.as_ref(), curve.as_ref()).unwrap();
+        assert_eq!(state.reserve, Uint128::zero());
+        assert_eq!(state.supply, Uint128::zero());
+        assert_eq!(state.reserve_denom.as_str(), DENOM);
+        assert_eq!(state.curve_type, curve_type);
+        // spot price 0 as supply is 0
+        assert_eq!(state.spot_price, Decimal256::zero());
+
+        // curve type is stored properly
+        assert_eq!(
+            curve_type.factor().unwrap(),
+            Decimal256::from_ratio(1u128, 10u128)
+        );
+
+        // no balance
+        assert_eq!(get_balance(deps.as_ref(), &creator), Uint128::zero());
+    }
+
+    #[test]
+    fn instantiate_validates_curve() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+
+        let curve_type = CurveType::Linear {
+            slope: Uint128::zero(),
+            scale: 1,
+        };
+        let msg = default_instantiate(2, 8, curve_type);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCurve { .. }));
+
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type);
+        msg.reserve_denom = String::new();
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyDenom {});
+    }
+
+    #[test]
+    fn buy_issues_tokens() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        setup_test(deps.as_mut(), 2, 8, curve_type.clone());
+
+        // succeeds with proper token (5 BTC = 10 BONDED)
+        let info = mock_info(INVESTOR, &coins(500_000_000, DENOM));
+        let buy = ExecuteMsg::Buy {};
+        execute(deps.as_mut(), mock_env(), info, buy.clone()).unwrap();
+
+        // bob got 1000 EPOXY (10.00)
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(1000));
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::zero());
+
+        // send them all to buyer
+        let info = mock_info(INVESTOR, &[]);
+        let send = ExecuteMsg::Transfer {
+            recipient: BUYER.into(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), mock_env(), info, send).unwrap();
+
+        // ensure balances updated
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::zero());
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(1000));
+
+        // second stake needs more to get next 1000 EPOXY
+        let info = mock_info(INVESTOR, &coins(1_500_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, buy).unwrap();
+
+        // ensure balances updated
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(1000));
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(1000));
+
+        // check curve info updated
+        let curve = curve_type.to_curve(DecimalPlaces::new(2, 8)).unwrap();
+        let curve_info = query_curve_info(deps.as_ref(), curve.as_ref()).unwrap();
+        assert_eq!(curve_info.reserve, Uint128::new(2_000_000_000));
+        assert_eq!(curve_info.supply, Uint128::new(2000));
+        assert_eq!(curve_info.spot_price, decimal(2u128, 0).unwrap());
+
+        // check token info updated
+        let token = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(token.decimals, 2);
+        assert_eq!(token.total_supply, Uint128::new(2000));
+
+        // spot price at any supply
+        let price =
+            query_spot_price(deps.as_ref(), curve.as_ref(), Some(Uint128::new(500))).unwrap();
+        assert_eq!(price.spot_price, decimal(5u128, 1).unwrap());
+    }
+
+    #[test]
+    fn bonding_fails_with_wrong_denom() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        setup_test(deps.as_mut(), 2, 8, curve_type);
+
+        // fails when no tokens sent
+        let info = mock_info(INVESTOR, &[]);
+        let buy = ExecuteMsg::Buy {};
+        let err = execute(deps.as_mut(), mock_env(), info, buy.clone()).unwrap_err();
+        assert_eq!(err, PaymentError::NoFunds {}.into());
+
+        // fails when wrong tokens sent
+        let info = mock_info(INVESTOR, &coins(1234567, "wei"));
+        let err = execute(deps.as_mut(), mock_env(), info, buy.clone()).unwrap_err();
+        assert_eq!(err, PaymentError::MissingDenom(DENOM.into()).into());
+
+        // fails when too many tokens sent
+        let info = mock_info(INVESTOR, &[coin(3400022, DENOM), coin(1234567, "wei")]);
+        let err = execute(deps.as_mut(), mock_env(), info, buy.clone()).unwrap_err();
+        assert_eq!(err, PaymentError::MultipleDenoms {}.into());
+
+        // fails when too little is sent to buy a single token
+        let info = mock_info(INVESTOR, &coins(1, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, buy).unwrap_err();
+        assert_eq!(err, ContractError::NothingToMint {});
+    }
+
+    #[test]
+    fn burning_sends_reserve() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        setup_test(deps.as_mut(), 2, 8, curve_type.clone());
+
+        // succeeds with proper token (20 BTC = 20 BONDED)
+        let info = mock_info(INVESTOR, &coins(2_000_000_000, DENOM));
+        let buy = ExecuteMsg::Buy {};
+        execute(deps.as_mut(), mock_env(), info, buy).unwrap();
+
+        // bob got 2000 EPOXY (20.00)
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(2000));
+
+        // cannot burn too much
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Sell {
+            amount: Uint128::new(3000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, burn).unwrap_err();
+        assert!(matches!(err, ContractError::Base(_)));
+
+        // cannot attach funds
+        let info = mock_info(INVESTOR, &coins(1, DENOM));
+        let burn = ExecuteMsg::Sell {
+            amount: Uint128::new(1000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, burn).unwrap_err();
+        assert_eq!(err, PaymentError::NonPayable {}.into());
+
+        // burn 1000 EPOXY to get back 15BTC (*10^8)
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Sell {
+            amount: Uint128::new(1000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, burn).unwrap();
+
+        // balance is lower
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(1000));
+
+        // ensure we got our money back
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            &res.messages[0],
+            &SubMsg::new(BankMsg::Send {
+                to_address: INVESTOR.into(),
+                amount: coins(1_500_000_000, DENOM),
+            })
+        );
+
+        // check curve info updated
+        let curve = curve_type.to_curve(DecimalPlaces::new(2, 8)).unwrap();
+        let curve_info = query_curve_info(deps.as_ref(), curve.as_ref()).unwrap();
+        assert_eq!(curve_info.reserve, Uint128::new(500_000_000));
+        assert_eq!(curve_info.supply, Uint128::new(1000));
+        assert_eq!(curve_info.spot_price, decimal(10u128, 1).unwrap());
+
+        // check token info updated
+        let token = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(token.decimals, 2);
+        assert_eq!(token.total_supply, Uint128::new(1000));
+    }
+
+    fn contract_bonding() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    #[test]
+    fn buy_and_sell_sqrt_curve() {
+        let investor = Addr::unchecked(INVESTOR);
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &investor, coins(1_000_000, DENOM))
+                .unwrap();
+        });
+
+        let bonding_id = app.store_code(contract_bonding());
+        // supply has 6 decimals, reserve 2, spot price is 0.35 * supply^0.5
+        let curve_type = CurveType::SquareRoot {
+            slope: Uint128::new(35),
+            scale: 2,
+        };
+        let bonding = app
+            .instantiate_contract(
+                bonding_id,
+                Addr::unchecked(CREATOR),
+                &default_instantiate(6, 2, curve_type),
+                &[],
+                "bonding",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            investor.clone(),
+            bonding.clone(),
+            &ExecuteMsg::Buy {},
+            &coins(84_058, DENOM),
+        )
+        .unwrap();
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &bonding,
+                &QueryMsg::Balance {
+                    address: INVESTOR.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(235_000_170));
+
+        // selling everything returns the whole reserve (rounded down)
+        app.execute_contract(
+            investor.clone(),
+            bonding.clone(),
+            &ExecuteMsg::Sell {
+                amount: balance.balance,
+            },
+            &[],
+        )
+        .unwrap();
+        let info: CurveInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&bonding, &QueryMsg::CurveInfo {})
+            .unwrap();
+        assert_eq!(info.supply, Uint128::zero());
+        assert_eq!(info.reserve, Uint128::zero());
+        assert_eq!(
+            app.wrap().query_balance(&investor, DENOM).unwrap(),
+            coin(1_000_000, DENOM)
+        );
+        assert_eq!(
+            app.wrap().query_balance(&bonding, DENOM).unwrap(),
+            coin(0, DENOM)
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256, Uint512};
+
+/// This defines the curves we are using.
+///
+/// Tokens are stored as `Uint128` with different decimal places for supply and reserve, which
+/// doesn't matter much for constant or linear functions, but a lot for non-linear ones.
+/// So all the math is done on whole tokens, as `Decimal256`: amounts are normalized with
+/// `DecimalPlaces` (passed to the curve constructors) on the way in and out.
+/// All results are rounded down, so that the contract never releases more than it holds.
+pub trait Curve {
+    /// Returns the spot price given the supply, in whole reserve tokens per whole supply token.
+    /// `f(x)` from the README
+    fn spot_price(&self, supply: Uint128) -> StdResult<Decimal256>;
+
+    /// Returns the total price paid up to purchase supply tokens (integral)
+    /// `F(x)` from the README
+    fn reserve(&self, supply: Uint128) -> StdResult<Uint128>;
+
+    /// Inverse of reserve. Returns how many tokens would be issued
+    /// with a total paid amount of reserve.
+    /// `F^-1(x)` from the README
+    fn supply(&self, reserve: Uint128) -> StdResult<Uint128>;
+}
+
+/// Returns `num * 10^-scale`, erroring if this cannot be represented
+pub fn decimal(num: impl Into<Uint256>, scale: u32) -> StdResult<Decimal256> {
+    Decimal256::from_atomics(num, scale).map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+fn mul(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    Ok(a.checked_mul(b)?)
+}
+
+fn div(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    a.checked_div(b)
+        .map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+/// spot price is always a constant value
+pub struct Constant {
+    pub value: Decimal256,
+    pub normalize: DecimalPlaces,
+}
+
+impl Constant {
+    pub fn new(value: Decimal256, normalize: DecimalPlaces) -> Self {
+        Self { value, normalize }
+    }
+}
+
+impl Curve for Constant {
+    fn spot_price(&self, _supply: Uint128) -> StdResult<Decimal256> {
+        // f(x) = self.value
+        Ok(self.value)
+    }
+
+    fn reserve(&self, supply: Uint128) -> StdResult<Uint128> {
+        // F(x) = supply * self.value
+        let reserve = mul(self.normalize.from_supply(supply)?, self.value)?;
+        self.normalize.to_reserve(reserve)
+    }
+
+    fn supply(&self, reserve: Uint128) -> StdResult<Uint128> {
+        // F^-1(x) = reserve / self.value
+        let supply = div(self.normalize.from_reserve(reserve)?, self.value)?;
+        self.normalize.to_supply(supply)
+    }
+}
+
+/// spot_price is slope * supply
+pub struct Linear {
+    pub slope: Decimal256,
+    pub normalize: DecimalPlaces,
+}
+
+impl Linear {
+    pub fn new(slope: Decimal256, normalize: DecimalPlaces) -> Self {
+        Self { slope, normalize }
+    }
+}
+
+impl Curve for Linear {
+    fn spot_price(&self, supply: Uint128) -> StdResult<Decimal256> {
+        // f(x) = self.slope * supply
+        mul(self.normalize.from_supply(supply)?, self.slope)
+    }
+
+    fn reserve(&self, supply: Uint128) -> StdResult<Uint128> {
+        // F(x) = self.slope * supply * supply / 2
+        let normalized = self.normalize.from_supply(supply)?;
+        let square = mul(normalized, normalized)?;
+        let reserve = mul(mul(square, self.slope)?, Decimal256::percent(50))?;
+        self.normalize.to_reserve(reserve)
+    }
+
+    fn supply(&self, reserve: Uint128) -> StdResult<Uint128> {
+        // F^-1(x) = (2 * reserve / self.slope) ^ 0.5
+        let normalized = self.normalize.from_reserve(reserve)?;
+        let square = div(mul(normalized, Decimal256::percent(200))?, self.slope)?;
+        self.normalize.to_supply(square.sqrt())
+    }
+}
+
+/// spot_price is slope * (supply)^0.5
+pub struct SquareRoot {
+    pub slope: Decimal256,
+    pub normalize: DecimalPlaces,
+}
+
+impl SquareRoot {
+    pub fn new(slope: Decimal256, normalize: DecimalPlaces) -> Self {
+        Self { slope, normalize }
+    }
+}
+
+impl Curve for SquareRoot {
+    fn spot_price(&self, supply: Uint128) -> StdResult<Decimal256> {
+        // f(x) = self.slope * supply^0.5
+        let normalized = self.normalize.from_supply(supply)?;
+        mul(normalized.sqrt(), self.slope)
+    }
+
+    fn reserve(&self, supply: Uint128) -> StdResult<Uint128> {
+        // F(x) = self.slope * supply * supply^0.5 / 1.5
+        let normalized = self.normalize.from_supply(supply)?;
+        let product = mul(mul(normalized, normalized.sqrt())?, self.slope)?;
+        let reserve = div(product, Decimal256::percent(150))?;
+        self.normalize.to_reserve(reserve)
+    }
+
+    fn supply(&self, reserve: Uint128) -> StdResult<Uint128> {
+        // F^-1(x) = (1.5 * reserve / self.slope) ^ (2/3)
+        let normalized = self.normalize.from_reserve(reserve)?;
+        let base = div(mul(normalized, Decimal256::percent(150))?, self.slope)?;
+        let supply = cube_root(mul(base, base)?)?;
+        self.normalize.to_supply(supply)
+    }
+}
+
+/// Cube root of a decimal, rounded down to the full 18 decimal places
+fn cube_root(cube: Decimal256) -> StdResult<Decimal256> {
+    // cbrt(atomics / 10^18) * 10^18 == cbrt(atomics * 10^36)
+    let scaled = Uint512::from(cube.atomics()) * Uint512::from(10u128.pow(36));
+    let root = Uint256::try_from(integer_cbrt(scaled))?;
+    Ok(Decimal256::new(root))
+}
+
+/// Integer cube root (rounded down) with Newton's method
+fn integer_cbrt(n: Uint512) -> Uint512 {
+    if n.is_zero() {
+        return n;
+    }
+
+    // start with a power of two known to be above the root
+    let mut bits = 0u32;
+    while !(n >> bits).is_zero() {
+        bits += 8;
+    }
+    // any input we pass fits in 384 bits, so this fits in a u128
+    let mut x = Uint512::from(1u128 << (bits / 3 + 1));
+
+    // from above, the approximations decrease until they reach the root
+    let three = Uint512::from(3u8);
+    loop {
+        let y = (x + x + n / (x * x)) / three;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// DecimalPlaces should be passed into curve constructors
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct DecimalPlaces {
+    /// Number of decimal places for the supply token (this is what was passed in cw20-base instantiate
+    pub supply: u32,
+    /// Number of decimal places for the reserve token (eg. 6 for uatom, 9 for nstep, 18 for wei)
+    pub reserve: u32,
+}
+
+impl DecimalPlaces {
+    pub fn new(supply: u8, reserve: u8) -> Self {
+        DecimalPlaces {
+            supply: supply as u32,
+            reserve: reserve as u32,
+        }
+    }
+
+    pub fn to_reserve(self, reserve: Decimal256) -> StdResult<Uint128> {
+        to_atomics(reserve, self.reserve)
+    }
+
+    pub fn to_supply(self, supply: Decimal256) -> StdResult<Uint128> {
+        to_atomics(supply, self.supply)
+    }
+
+    pub fn from_supply(&self, supply: Uint128) -> StdResult<Decimal256> {
+        decimal(supply, self.supply)
+    }
+
+    pub fn from_reserve(&self, reserve: Uint128) -> StdResult<Decimal256> {
+        decimal(reserve, self.reserve)
+    }
+}
+
+/// Returns `value * 10^places`, rounded down
+fn to_atomics(value: Decimal256, places: u32) -> StdResult<Uint128> {
+    let atomics = value.atomics();
+    let out = if places >= Decimal256::DECIMAL_PLACES {
+        let factor = Uint256::from(10u8).checked_pow(places - Decimal256::DECIMAL_PLACES)?;
+        atomics.checked_mul(factor)?
+    } else {
+        let factor = Uint256::from(10u8).checked_pow(Decimal256::DECIMAL_PLACES - places)?;
+        atomics / factor
+    };
+    Ok(Uint128::try_from(out)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // returns atomics * 10^-places, like dec(15, 1) == 1.5
+    fn dec(atomics: u128, places: u32) -> Decimal256 {
+        decimal(atomics, places).unwrap()
+    }
+
+    #[test]
+    fn constant_curve() {
+        // supply is nstep (9), reserve is uatom (6)
+        let normalize = DecimalPlaces::new(9, 6);
+        let curve = Constant::new(dec(15, 1), normalize);
+
+        // do some sanity checks....
+        // spot price is always 1.5 ATOM
+        assert_eq!(dec(15, 1), curve.spot_price(Uint128::new(123)).unwrap());
+
+        // if we have 30 STEP, we should have 45 ATOM
+        let reserve = curve.reserve(Uint128::new(30_000_000_000)).unwrap();
+        assert_eq!(Uint128::new(45_000_000), reserve);
+
+        // if we have 36 ATOM, we should have 24 STEP
+        let supply = curve.supply(Uint128::new(36_000_000)).unwrap();
+        assert_eq!(Uint128::new(24_000_000_000), supply);
+    }
+
+    #[test]
+    fn linear_curve() {
+        // supply is usdt (2), reserve is btc (8)
+        let normalize = DecimalPlaces::new(2, 8);
+        // slope is 0.1 (eg hits 1.0 after 10btc)
+        let curve = Linear::new(dec(1, 1), normalize);
+
+        // do some sanity checks....
+        // spot price is 0.1 with 1 USDT supply
+        assert_eq!(dec(1, 1), curve.spot_price(Uint128::new(100)).unwrap());
+        // spot price is 1.7 with 17 USDT supply
+        assert_eq!(dec(17, 1), curve.spot_price(Uint128::new(1700)).unwrap());
+        // spot price is 0.212 with 2.12 USDT supply
+        assert_eq!(dec(212, 3), curve.spot_price(Uint128::new(212)).unwrap());
+
+        // if we have 10 USDT, we should have 5 BTC
+        let reserve = curve.reserve(Uint128::new(1000)).unwrap();
+        assert_eq!(Uint128::new(500_000_000), reserve);
+        // if we have 20 USDT, we should have 20 BTC
+        let reserve = curve.reserve(Uint128::new(2000)).unwrap();
+        assert_eq!(Uint128::new(2_000_000_000), reserve);
+
+        // if we have 1.25 BTC, we should have 5 USDT
+        let supply = curve.supply(Uint128::new(125_000_000)).unwrap();
+        assert_eq!(Uint128::new(500), supply);
+        // test square root rounding
+        // if we have 1.11 BTC, we should have 4.7116875957... USDT
+        let supply = curve.supply(Uint128::new(111_000_000)).unwrap();
+        assert_eq!(Uint128::new(471), supply);
+    }
+
+    #[test]
+    fn sqrt_curve() {
+        // supply is utree (6) reserve is chf (2)
+        let normalize = DecimalPlaces::new(6, 2);
+        // slope is 0.35 (eg hits 0.35 after 1 chf, 3.5 after 100chf)
+        let curve = SquareRoot::new(dec(35, 2), normalize);
+
+        // do some sanity checks....
+        // spot price is 0.35 with 1 TREE supply
+        assert_eq!(
+            dec(35, 2),
+            curve.spot_price(Uint128::new(1_000_000)).unwrap()
+        );
+        // spot price is 3.5 with 100 TREE supply
+        assert_eq!(
+            dec(350, 2),
+            curve.spot_price(Uint128::new(100_000_000)).unwrap()
+        );
+        // spot price should be 23.478713763747791812... with 4500 TREE supply,
+        // rounded down to 18 decimal places
+        assert_eq!(
+            dec(23_478_713_763_747_791_812, 18),
+            curve.spot_price(Uint128::new(4_500_000_000)).unwrap()
+        );
+
+        // if we have 1 TREE, we should have 0.2333333333333 CHF
+        let reserve = curve.reserve(Uint128::new(1_000_000)).unwrap();
+        assert_eq!(Uint128::new(23), reserve);
+        // if we have 100 TREE, we should have 233.333333333 CHF
+        let reserve = curve.reserve(Uint128::new(100_000_000)).unwrap();
+        assert_eq!(Uint128::new(23_333), reserve);
+        // test rounding
+        // if we have 235 TREE, we should have 840.5790828021146 CHF
+        let reserve = curve.reserve(Uint128::new(235_000_000)).unwrap();
+        assert_eq!(Uint128::new(84_057), reserve);
+
+        // if we have 0.23 CHF, we should have 0.990453 TREE (round down)
+        let supply = curve.supply(Uint128::new(23)).unwrap();
+        assert_eq!(Uint128::new(990_453), supply);
+        // if we have 840.58 CHF, we should have 235.000170 TREE (round down)
+        let supply = curve.supply(Uint128::new(84058)).unwrap();
+        assert_eq!(Uint128::new(235_000_170), supply);
+    }
+
+    #[test]
+    fn cube_root_rounds_down() {
+        assert_eq!(integer_cbrt(Uint512::from(0u8)), Uint512::from(0u8));
+        assert_eq!(integer_cbrt(Uint512::from(1u8)), Uint512::from(1u8));
+        assert_eq!(integer_cbrt(Uint512::from(26u8)), Uint512::from(2u8));
+        assert_eq!(integer_cbrt(Uint512::from(27u8)), Uint512::from(3u8));
+        assert_eq!(
+            integer_cbrt(Uint512::from(u128::MAX)),
+            Uint512::from(6_981_463_658_331u128)
+        );
+        assert_eq!(cube_root(dec(8, 0)).unwrap(), dec(2, 0));
+        assert_eq!(cube_root(dec(1, 3)).unwrap(), dec(1, 1));
+    }
+}
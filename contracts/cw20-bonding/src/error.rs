@@ -0,0 +1,27 @@
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw20_base::ContractError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Reserve denom cannot be empty")]
+    EmptyDenom {},
+
+    #[error("Invalid curve: {reason}")]
+    InvalidCurve { reason: String },
+
+    #[error("Payment too small to buy any tokens")]
+    NothingToMint {},
+
+    #[error("Amount too small to release any reserve")]
+    NothingToRelease {},
+}
@@ -0,0 +1,22 @@
+/*!
+This is a cw20 token issued along a bonding curve. Anyone can buy new tokens by paying a native
+reserve denom, and sell them back at any time for their share of the reserve. The price follows
+a curve depending on the total supply, so it rises with every buy and falls with every sell.
+
+Three curves are supported out of the box (constant, linear and square root), and other
+contracts can plug in their own by implementing the `Curve` trait and calling `do_execute`.
+
+All the cw20 logic is imported from
+[cw20-base](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-base/README.md).
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-bonding/README.md).
+*/
+
+pub mod contract;
+pub mod curves;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
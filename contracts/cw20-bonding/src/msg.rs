@@ -0,0 +1,211 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Decimal256, StdResult, Uint128};
+use cw20::{Expiration, Logo};
+
+use crate::curves::{decimal, Constant, Curve, DecimalPlaces, Linear, SquareRoot};
+
+pub use cw20_base::msg::InstantiateMarketingInfo;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// name of the supply token
+    pub name: String,
+    /// symbol / ticker of the supply token
+    pub symbol: String,
+    /// number of decimal places of the supply token, needed for proper curve math.
+    /// If it is eg. BTC, where a balance of 10^8 means 1 BTC, then use 8 here.
+    pub decimals: u8,
+
+    /// this is the reserve token denom (only support native for now)
+    pub reserve_denom: String,
+    /// number of decimal places for the reserve token, needed for proper curve math.
+    /// Same format as decimals above, eg. if it is uatom, where 1 unit is 10^-6 ATOM, use 6 here
+    pub reserve_decimals: u8,
+
+    /// enum to store the curve parameters used for this contract
+    /// if you want to add a custom Curve, you should make a new contract that imports this one.
+    /// write a custom `instantiate`, and then dispatch `your::execute` -> `cw20_bonding::contract::do_execute`
+    /// with your custom curve as a parameter (and same with `query` -> `do_query`)
+    pub curve_type: CurveType,
+
+    pub marketing: Option<InstantiateMarketingInfo>,
+}
+
+#[cw_serde]
+pub enum CurveType {
+    /// Constant always returns `value * 10^-scale` as spot price
+    Constant { value: Uint128, scale: u32 },
+    /// Linear returns `slope * 10^-scale * supply` as spot price
+    Linear { slope: Uint128, scale: u32 },
+    /// SquareRoot returns `slope * 10^-scale * supply^0.5` as spot price
+    SquareRoot { slope: Uint128, scale: u32 },
+}
+
+impl CurveType {
+    /// The constant value or slope of the curve, as a decimal
+    pub fn factor(&self) -> StdResult<Decimal256> {
+        match self {
+            CurveType::Constant { value, scale } => decimal(*value, *scale),
+            CurveType::Linear { slope, scale } => decimal(*slope, *scale),
+            CurveType::SquareRoot { slope, scale } => decimal(*slope, *scale),
+        }
+    }
+
+    /// Builds the curve, normalizing amounts with the given decimal places
+    pub fn to_curve(&self, normalize: DecimalPlaces) -> StdResult<Box<dyn Curve>> {
+        let factor = self.factor()?;
+        let curve: Box<dyn Curve> = match self {
+            CurveType::Constant { .. } => Box::new(Constant::new(factor, normalize)),
+            CurveType::Linear { .. } => Box::new(Linear::new(factor, normalize)),
+            CurveType::SquareRoot { .. } => Box::new(SquareRoot::new(factor, normalize)),
+        };
+        Ok(curve)
+    }
+}
+
+/// All the cw20 messages, besides the ones that would change the total supply without
+/// touching the reserve (`Burn`, `BurnFrom`, `Mint` and `UpdateMinter`)
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Buy will attempt to purchase as many supply tokens as possible.
+    /// You must send only reserve tokens in that message
+    Buy {},
+    /// Sell burns `amount` supply tokens of the sender and sends back the reserve
+    /// they are worth on the curve
+    Sell { amount: Uint128 },
+
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// If authorized, updates marketing metadata.
+    /// Setting None/null for any of these will leave it unchanged.
+    /// Setting Some("") will clear this field on the contract storage
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, PNG, WebP or JPEG
+    /// for the token
+    UploadLogo(Logo),
+    /// If authorized, sets an arbitrary metadata entry
+    SetMetadata { key: String, value: String },
+    /// If authorized, removes the metadata entry stored under this key
+    RemoveMetadata { key: String },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the reserve and supply quantities, as well as the spot price to buy 1 token
+    #[returns(CurveInfoResponse)]
+    CurveInfo {},
+    /// Returns the spot price at the given supply (current supply if not set)
+    #[returns(SpotPriceResponse)]
+    SpotPrice { supply: Option<Uint128> },
+
+    /// Returns the current balance of the given address, 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    /// Returns how much spender can use from owner account, 0 if unset.
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns more metadata on the contract to display in the client:
+    /// - description, logo, project url, etc.
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for
+    /// this contract.
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    #[returns(cw20::MetadataResponse)]
+    Metadata { key: String },
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    #[returns(cw20::AllMetadataResponse)]
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct CurveInfoResponse {
+    /// how many reserve tokens have been received
+    pub reserve: Uint128,
+    /// how many supply tokens have been issued
+    pub supply: Uint128,
+    /// price of one whole supply token, in whole reserve tokens
+    pub spot_price: Decimal256,
+    pub reserve_denom: String,
+    pub curve_type: CurveType,
+}
+
+#[cw_serde]
+pub struct SpotPriceResponse {
+    /// the supply the price was computed for
+    pub supply: Uint128,
+    /// price of one whole supply token, in whole reserve tokens
+    pub spot_price: Decimal256,
+}
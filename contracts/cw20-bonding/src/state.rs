@@ -0,0 +1,36 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+use cw_storage_plus::Item;
+
+use crate::curves::DecimalPlaces;
+use crate::msg::CurveType;
+
+/// Tracks the reserve held by the contract and the supply issued against it
+#[cw_serde]
+pub struct CurveState {
+    /// reserve is how many native tokens the contract holds
+    pub reserve: Uint128,
+    /// supply is how many tokens this contract has issued
+    pub supply: Uint128,
+
+    /// the denom of the reserve token
+    pub reserve_denom: String,
+
+    /// how to normalize reserve and supply
+    pub decimals: DecimalPlaces,
+}
+
+impl CurveState {
+    pub fn new(reserve_denom: String, decimals: DecimalPlaces) -> Self {
+        CurveState {
+            reserve: Uint128::zero(),
+            supply: Uint128::zero(),
+            reserve_denom,
+            decimals,
+        }
+    }
+}
+
+pub const CURVE_STATE: Item<CurveState> = Item::new("curve_state");
+
+pub const CURVE_TYPE: Item<CurveType> = Item::new("curve_type");
@@ -0,0 +1,935 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, SubMsg, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Balance, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_utils::NativeBalance;
+
+use crate::error::ContractError;
+use crate::msg::{
+    is_valid_name, CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg, ListResponse, QueryMsg,
+    ReceiveMsg,
+};
+use crate::state::{all_escrow_ids, Escrow, GenericBalance, ESCROWS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-escrow";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    // no setup
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Create(msg) => {
+            execute_create(deps, env, msg, Balance::from(info.funds), &info.sender)
+        }
+        ExecuteMsg::SetRecipient { id, recipient } => {
+            execute_set_recipient(deps, env, info, id, recipient)
+        }
+        ExecuteMsg::Approve { id } => execute_approve(deps, env, info, id),
+        ExecuteMsg::TopUp { id } => execute_top_up(deps, env, id, Balance::from(info.funds)),
+        ExecuteMsg::Refund { id } => execute_refund(deps, env, info, id),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    let balance = Balance::Cw20(Cw20CoinVerified {
+        address: info.sender,
+        amount: wrapper.amount,
+    });
+    let api = deps.api;
+    match msg {
+        ReceiveMsg::Create(msg) => execute_create(
+            deps,
+            env,
+            msg,
+            balance,
+            &api.addr_validate(&wrapper.sender)?,
+        ),
+        ReceiveMsg::TopUp { id } => execute_top_up(deps, env, id, balance),
+    }
+}
+
+pub fn execute_create(
+    deps: DepsMut,
+    env: Env,
+    msg: CreateMsg,
+    balance: Balance,
+    sender: &Addr,
+) -> Result<Response, ContractError> {
+    if balance.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    if !is_valid_name(&msg.id) {
+        return Err(ContractError::InvalidId {});
+    }
+
+    let mut cw20_whitelist = msg.addr_whitelist(deps.api)?;
+
+    let escrow_balance = match balance {
+        Balance::Native(balance) => GenericBalance {
+            native: NativeBalance::default() + balance,
+            cw20: vec![],
+        },
+        Balance::Cw20(token) => {
+            // make sure the token sent is on the whitelist by default
+            if !cw20_whitelist.iter().any(|t| t == &token.address) {
+                cw20_whitelist.push(token.address.clone())
+            }
+            GenericBalance {
+                native: NativeBalance::default(),
+                cw20: vec![token],
+            }
+        }
+    };
+
+    let recipient: Option<Addr> = msg
+        .recipient
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let escrow = Escrow {
+        arbiter: deps.api.addr_validate(&msg.arbiter)?,
+        recipient,
+        source: sender.clone(),
+        title: msg.title,
+        description: msg.description,
+        expires: msg.expires.unwrap_or_default(),
+        cw20_whitelist,
+        balance: escrow_balance,
+    };
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+
+    // try to store it, fail if the id was already in use
+    ESCROWS.update(deps.storage, &msg.id, |existing| match existing {
+        None => Ok(escrow),
+        Some(_) => Err(ContractError::AlreadyInUse {}),
+    })?;
+
+    let res = Response::new().add_attributes(vec![("action", "create"), ("id", msg.id.as_str())]);
+    Ok(res)
+}
+
+pub fn execute_set_recipient(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = ESCROWS.load(deps.storage, &id)?;
+    if info.sender != escrow.arbiter {
+        return Err(ContractError::Unauthorized {});
+    }
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    escrow.recipient = Some(recipient.clone());
+    ESCROWS.save(deps.storage, &id, &escrow)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_recipient"),
+        ("id", id.as_str()),
+        ("recipient", recipient.as_str()),
+    ]))
+}
+
+pub fn execute_top_up(
+    deps: DepsMut,
+    env: Env,
+    id: String,
+    balance: Balance,
+) -> Result<Response, ContractError> {
+    if balance.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    // this fails if no escrow there
+    let mut escrow = ESCROWS.load(deps.storage, &id)?;
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+
+    if let Balance::Cw20(token) = &balance {
+        // ensure the token is on the whitelist
+        if !escrow.cw20_whitelist.iter().any(|t| t == &token.address) {
+            return Err(ContractError::NotInWhitelist {});
+        }
+    };
+
+    escrow.balance.add_tokens(balance);
+
+    // and save
+    ESCROWS.save(deps.storage, &id, &escrow)?;
+
+    let res = Response::new().add_attributes(vec![("action", "top_up"), ("id", id.as_str())]);
+    Ok(res)
+}
+
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    // this fails if no escrow there
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+
+    if info.sender != escrow.arbiter {
+        return Err(ContractError::Unauthorized {});
+    }
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+
+    let recipient = escrow.recipient.ok_or(ContractError::RecipientNotSet {})?;
+
+    // we delete the escrow
+    ESCROWS.remove(deps.storage, &id);
+
+    // send all tokens out
+    let messages: Vec<SubMsg> = send_tokens(&recipient, &escrow.balance)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve")
+        .add_attribute("id", id)
+        .add_attribute("to", recipient)
+        .add_submessages(messages))
+}
+
+pub fn execute_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    // this fails if no escrow there
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+
+    // the arbiter can send anytime OR anyone can send after expiration
+    if !escrow.is_expired(&env) && info.sender != escrow.arbiter {
+        Err(ContractError::Unauthorized {})
+    } else {
+        // we delete the escrow
+        ESCROWS.remove(deps.storage, &id);
+
+        // send all tokens out
+        let messages = send_tokens(&escrow.source, &escrow.balance)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "refund")
+            .add_attribute("id", id)
+            .add_attribute("to", escrow.source)
+            .add_submessages(messages))
+    }
+}
+
+fn send_tokens(to: &Addr, balance: &GenericBalance) -> StdResult<Vec<SubMsg>> {
+    let native_balance = &balance.native;
+    let mut msgs: Vec<SubMsg> = if native_balance.is_empty() {
+        vec![]
+    } else {
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: to.into(),
+            amount: native_balance.clone().into_vec(),
+        })]
+    };
+
+    let cw20_balance = &balance.cw20;
+    let cw20_msgs: StdResult<Vec<_>> = cw20_balance
+        .iter()
+        .map(|c| {
+            let msg = Cw20ExecuteMsg::Transfer {
+                recipient: to.into(),
+                amount: c.amount,
+            };
+            let exec = SubMsg::new(WasmMsg::Execute {
+                contract_addr: c.address.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            });
+            Ok(exec)
+        })
+        .collect();
+    msgs.append(&mut cw20_msgs?);
+    Ok(msgs)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::List { start_after, limit } => to_binary(&query_list(deps, start_after, limit)?),
+        QueryMsg::Details { id } => to_binary(&query_details(deps, id)?),
+    }
+}
+
+fn query_details(deps: Deps, id: String) -> StdResult<DetailsResponse> {
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+
+    let cw20_whitelist = escrow.human_whitelist();
+
+    // transform tokens
+    let native_balance = escrow.balance.native.into_vec();
+
+    let cw20_balance: Vec<Cw20Coin> = escrow
+        .balance
+        .cw20
+        .into_iter()
+        .map(|token| Cw20Coin {
+            address: token.address.into(),
+            amount: token.amount,
+        })
+        .collect();
+
+    let details = DetailsResponse {
+        id,
+        arbiter: escrow.arbiter.into(),
+        recipient: escrow.recipient.map(|addr| addr.into()),
+        source: escrow.source.into(),
+        title: escrow.title,
+        description: escrow.description,
+        expires: escrow.expires,
+        native_balance,
+        cw20_balance,
+        cw20_whitelist,
+    };
+    Ok(details)
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_list(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    Ok(ListResponse {
+        escrows: all_escrow_ids(deps.storage, start_after.as_deref(), limit)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, CosmosMsg, Empty, StdError, Uint128};
+    use cw20::BalanceResponse;
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_utils::Expiration;
+
+    use super::*;
+
+    fn create_msg(id: &str) -> CreateMsg {
+        CreateMsg {
+            id: id.to_string(),
+            arbiter: String::from("arbitrate"),
+            recipient: Some(String::from("recd")),
+            title: "some_title".to_string(),
+            expires: Some(Expiration::AtHeight(123456)),
+            cw20_whitelist: None,
+            description: "some_description".to_string(),
+        }
+    }
+
+    #[test]
+    fn happy_path_native() {
+        let mut deps = mock_dependencies();
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {};
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // create an escrow
+        let create = create_msg("foobar");
+        let sender = String::from("source");
+        let balance = coins(100, "tokens");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::Create(create.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // ensure the details is what we expect
+        let details = query_details(deps.as_ref(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details,
+            DetailsResponse {
+                id: "foobar".to_string(),
+                arbiter: String::from("arbitrate"),
+                recipient: Some(String::from("recd")),
+                source: String::from("source"),
+                title: "some_title".to_string(),
+                description: "some_description".to_string(),
+                expires: Expiration::AtHeight(123456),
+                native_balance: balance.clone(),
+                cw20_balance: vec![],
+                cw20_whitelist: vec![],
+            }
+        );
+
+        // approve it
+        let id = create.id.clone();
+        let info = mock_info(&create.arbiter, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(("action", "approve"), res.attributes[0]);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: create.recipient.unwrap(),
+                amount: balance,
+            }))
+        );
+
+        // second attempt fails (not found)
+        let id = create.id.clone();
+        let info = mock_info(&create.arbiter, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+    }
+
+    #[test]
+    fn happy_path_cw20() {
+        let mut deps = mock_dependencies();
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {};
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // create an escrow
+        let mut create = create_msg("foobar");
+        create.cw20_whitelist = Some(vec![String::from("other-token")]);
+        let receive = Cw20ReceiveMsg {
+            sender: String::from("source"),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::Create(create.clone())).unwrap(),
+        };
+        let token_contract = String::from("my-cw20-token");
+        let info = mock_info(&token_contract, &[]);
+        let msg = ExecuteMsg::Receive(receive.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // ensure the whitelist is what we expect
+        let details = query_details(deps.as_ref(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details,
+            DetailsResponse {
+                id: "foobar".to_string(),
+                arbiter: String::from("arbitrate"),
+                recipient: Some(String::from("recd")),
+                source: String::from("source"),
+                title: "some_title".to_string(),
+                description: "some_description".to_string(),
+                expires: Expiration::AtHeight(123456),
+                native_balance: vec![],
+                cw20_balance: vec![Cw20Coin {
+                    address: String::from("my-cw20-token"),
+                    amount: Uint128::new(100),
+                }],
+                cw20_whitelist: vec![String::from("other-token"), String::from("my-cw20-token")],
+            }
+        );
+
+        // approve it
+        let id = create.id.clone();
+        let info = mock_info(&create.arbiter, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(("action", "approve"), res.attributes[0]);
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: create.recipient.unwrap(),
+            amount: receive.amount,
+        };
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token_contract,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+
+        // second attempt fails (not found)
+        let id = create.id.clone();
+        let info = mock_info(&create.arbiter, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+    }
+
+    #[test]
+    fn set_recipient_after_creation() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let mut create = create_msg("foobar");
+        create.recipient = None;
+        let info = mock_info("source", &coins(100, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // cannot approve without a recipient
+        let id = "foobar".to_string();
+        let info = mock_info("arbitrate", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Approve { id: id.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::RecipientNotSet {});
+
+        // only the arbiter can set it
+        let msg = ExecuteMsg::SetRecipient {
+            id: id.clone(),
+            recipient: "recp".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("source", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "recp".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+    }
+
+    #[test]
+    fn create_errors() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let info = mock_info("source", &[]);
+        let msg = ExecuteMsg::Create(create_msg("foobar"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyBalance {});
+
+        let info = mock_info("source", &coins(100, "tokens"));
+        let msg = ExecuteMsg::Create(create_msg("fb"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidId {});
+
+        let mut create = create_msg("foobar");
+        create.expires = Some(Expiration::AtHeight(mock_env().block.height));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+
+        let msg = ExecuteMsg::Create(create_msg("foobar"));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyInUse {});
+    }
+
+    #[test]
+    fn add_tokens_proper() {
+        let mut tokens = GenericBalance::default();
+        tokens.add_tokens(Balance::from(vec![coin(123, "atom"), coin(789, "eth")]));
+        tokens.add_tokens(Balance::from(vec![coin(456, "atom"), coin(12, "btc")]));
+        assert_eq!(
+            tokens.native.into_vec(),
+            vec![coin(579, "atom"), coin(12, "btc"), coin(789, "eth")]
+        );
+    }
+
+    #[test]
+    fn add_cw_tokens_proper() {
+        let mut tokens = GenericBalance::default();
+        let bar_token = Addr::unchecked("bar_token");
+        let foo_token = Addr::unchecked("foo_token");
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: foo_token.clone(),
+            amount: Uint128::new(12345),
+        }));
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: bar_token.clone(),
+            amount: Uint128::new(777),
+        }));
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: foo_token.clone(),
+            amount: Uint128::new(23400),
+        }));
+        assert_eq!(
+            tokens.cw20,
+            vec![
+                Cw20CoinVerified {
+                    address: foo_token,
+                    amount: Uint128::new(35745),
+                },
+                Cw20CoinVerified {
+                    address: bar_token,
+                    amount: Uint128::new(777),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn top_up_mixed_tokens() {
+        let mut deps = mock_dependencies();
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {};
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // only accept these tokens
+        let whitelist = vec![String::from("bar_token"), String::from("foo_token")];
+
+        // create an escrow with 2 native tokens
+        let mut create = create_msg("foobar");
+        create.cw20_whitelist = Some(whitelist);
+        let sender = String::from("source");
+        let balance = vec![coin(100, "fee"), coin(200, "stake")];
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::Create(create.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // top it up with 2 more native tokens
+        let extra_native = vec![coin(250, "random"), coin(300, "stake")];
+        let info = mock_info(&sender, &extra_native);
+        let top_up = ExecuteMsg::TopUp {
+            id: create.id.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // top up with one foreign token
+        let bar_token = String::from("bar_token");
+        let base = TopUp {
+            id: create.id.clone(),
+        };
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("random"),
+            amount: Uint128::new(7890),
+            msg: to_binary(&base.to_receive()).unwrap(),
+        });
+        let info = mock_info(&bar_token, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // top with a foreign token not on the whitelist
+        // top up with one foreign token
+        let baz_token = String::from("baz_token");
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("random"),
+            amount: Uint128::new(7890),
+            msg: to_binary(&base.to_receive()).unwrap(),
+        });
+        let info = mock_info(&baz_token, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, top_up).unwrap_err();
+        assert_eq!(err, ContractError::NotInWhitelist {});
+
+        // top up with second foreign token
+        let foo_token = String::from("foo_token");
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("random"),
+            amount: Uint128::new(888),
+            msg: to_binary(&base.to_receive()).unwrap(),
+        });
+        let info = mock_info(&foo_token, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // approve it
+        let id = create.id.clone();
+        let info = mock_info(&create.arbiter, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
+        assert_eq!(("action", "approve"), res.attributes[0]);
+        assert_eq!(3, res.messages.len());
+
+        // first message releases all native coins
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: create.recipient.clone().unwrap(),
+                amount: vec![coin(100, "fee"), coin(250, "random"), coin(500, "stake")],
+            }))
+        );
+
+        // second one release bar cw20 token
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: create.recipient.clone().unwrap(),
+            amount: Uint128::new(7890),
+        };
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: bar_token,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+
+        // third one release foo cw20 token
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: create.recipient.unwrap(),
+            amount: Uint128::new(888),
+        };
+        assert_eq!(
+            res.messages[2],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: foo_token,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+    }
+
+    struct TopUp {
+        id: String,
+    }
+
+    impl TopUp {
+        fn to_receive(&self) -> ReceiveMsg {
+            ReceiveMsg::TopUp {
+                id: self.id.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn refund_after_expiration() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = create_msg("foobar");
+        let info = mock_info("source", &coins(100, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // only the arbiter before expiration
+        let id = "foobar".to_string();
+        let info = mock_info("anyone", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Refund { id: id.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // anyone afterwards, but it cannot be approved or topped up anymore
+        let mut env = mock_env();
+        env.block.height = 123456;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("arbitrate", &[]),
+            ExecuteMsg::Approve { id: id.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("source", &coins(100, "tokens")),
+            ExecuteMsg::TopUp { id: id.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::Refund { id }).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "source".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+    }
+
+    #[test]
+    fn list_escrows() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        for id in ["escrow3", "escrow1", "escrow2"] {
+            let info = mock_info("source", &coins(100, "tokens"));
+            let msg = ExecuteMsg::Create(create_msg(id));
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let list = query_list(deps.as_ref(), None, None).unwrap();
+        assert_eq!(list.escrows, vec!["escrow1", "escrow2", "escrow3"]);
+        let list = query_list(deps.as_ref(), Some("escrow1".to_string()), Some(1)).unwrap();
+        assert_eq!(list.escrows, vec!["escrow2"]);
+    }
+
+    fn contract_escrow() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn escrow_cw20_multitest() {
+        let owner = Addr::unchecked("owner");
+        let mut app = App::default();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let cash = app
+            .instantiate_contract(
+                cw20_id,
+                owner.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Cash Money".to_string(),
+                    symbol: "CASH".to_string(),
+                    decimals: 2,
+                    initial_balances: vec![Cw20Coin {
+                        address: owner.to_string(),
+                        amount: Uint128::new(5000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "CASH",
+                None,
+            )
+            .unwrap();
+
+        let escrow_id = app.store_code(contract_escrow());
+        let escrow = app
+            .instantiate_contract(
+                escrow_id,
+                owner.clone(),
+                &InstantiateMsg {},
+                &[],
+                "Escrow",
+                None,
+            )
+            .unwrap();
+
+        // send some tokens to create an escrow
+        let create = create_msg("demo");
+        app.execute_contract(
+            owner.clone(),
+            cash.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: escrow.to_string(),
+                amount: Uint128::new(1200),
+                msg: to_binary(&ReceiveMsg::Create(create.clone())).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let balance = |app: &App, address: &str| -> Uint128 {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &cash,
+                    &cw20::Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance
+        };
+        assert_eq!(balance(&app, owner.as_str()), Uint128::new(3800));
+        assert_eq!(balance(&app, escrow.as_str()), Uint128::new(1200));
+
+        // release escrow
+        app.execute_contract(
+            Addr::unchecked(create.arbiter),
+            escrow.clone(),
+            &ExecuteMsg::Approve {
+                id: create.id.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(balance(&app, escrow.as_str()), Uint128::zero());
+        assert_eq!(balance(&app, "recd"), Uint128::new(1200));
+
+        let list: ListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &escrow,
+                &QueryMsg::List {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(list.escrows.is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Only accepts tokens in the cw20_whitelist")]
+    NotInWhitelist {},
+
+    #[error("Escrow is expired")]
+    Expired {},
+
+    #[error("Send some coins to create an escrow")]
+    EmptyBalance {},
+
+    #[error("Escrow id must be 3-20 bytes of utf-8 text")]
+    InvalidId {},
+
+    #[error("Escrow id already in use")]
+    AlreadyInUse {},
+
+    #[error("Recipient is not set")]
+    RecipientNotSet {},
+}
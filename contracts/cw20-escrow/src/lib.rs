@@ -0,0 +1,23 @@
+/*!
+This is an escrow meta-contract that allows multiple users to create independent escrows.
+Each escrow has a sender, recipient, and arbiter. It also has a unique id (for future calls
+to reference it) and an optional expiration.
+
+The basic function is the sender creates an escrow with funds. The arbiter may at any time
+decide to release the funds to either the intended recipient or the original sender
+(but no one else), and if it passes optional expiration without release, anyone can refund
+the locked tokens to the original sender.
+
+It holds native tokens as well as cw20 tokens. Only cw20 tokens on the whitelist of an escrow
+can be deposited into it, so escrows cannot be polluted with worthless tokens.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-escrow/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,124 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Api, Coin, StdResult};
+use cw20::{Cw20Coin, Cw20ReceiveMsg};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct InstantiateMsg {}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Creates a new escrow with the native tokens sent along with this message
+    Create(CreateMsg),
+    /// Adds all sent native tokens to the contract
+    TopUp { id: String },
+    /// Set the recipient of the given escrow. Only the arbiter can do this.
+    SetRecipient { id: String, recipient: String },
+    /// Approve sends all tokens to the recipient.
+    /// Only the arbiter can do this
+    Approve {
+        /// id is a human-readable name for the escrow from create
+        id: String,
+    },
+    /// Refund returns all remaining tokens to the original sender.
+    /// The arbiter can do this any time, or anyone once the escrow has expired
+    Refund {
+        /// id is a human-readable name for the escrow from create
+        id: String,
+    },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Creates a new escrow with the received cw20 tokens.
+    /// The token is implicitly added to the whitelist of the escrow.
+    Create(CreateMsg),
+    /// Adds all received tokens to the contract. Only whitelisted cw20 tokens are accepted.
+    TopUp { id: String },
+}
+
+#[cw_serde]
+pub struct CreateMsg {
+    /// id is a human-readable name for the escrow to use later
+    /// 3-20 bytes of utf-8 text
+    pub id: String,
+    /// arbiter can decide to approve or refund the escrow
+    pub arbiter: String,
+    /// if approved, funds go to the recipient
+    pub recipient: Option<String>,
+    /// Title of the escrow
+    pub title: String,
+    /// Longer description of the escrow, e.g. what conditions should be met
+    pub description: String,
+    /// When the escrow expires, anyone can refund it to the sender.
+    /// If not set, the escrow never expires.
+    pub expires: Option<Expiration>,
+    /// Besides any possible tokens sent with the CreateMsg, this is a list of all cw20 token
+    /// addresses that are accepted by the escrow during a top-up. This is required to avoid a
+    /// DoS attack by topping-up with an invalid cw20 contract. See
+    /// https://github.com/CosmWasm/cosmwasm-plus/issues/19
+    pub cw20_whitelist: Option<Vec<String>>,
+}
+
+impl CreateMsg {
+    pub fn addr_whitelist(&self, api: &dyn Api) -> StdResult<Vec<Addr>> {
+        match self.cw20_whitelist.as_ref() {
+            Some(v) => v.iter().map(|h| api.addr_validate(h)).collect(),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    if bytes.len() < 3 || bytes.len() > 20 {
+        return false;
+    }
+    true
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Show all open escrows. Supports pagination.
+    #[returns(ListResponse)]
+    List {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the details of the named escrow, error if not created
+    #[returns(DetailsResponse)]
+    Details { id: String },
+}
+
+#[cw_serde]
+pub struct ListResponse {
+    /// list all registered ids
+    pub escrows: Vec<String>,
+}
+
+#[cw_serde]
+pub struct DetailsResponse {
+    /// id of this escrow
+    pub id: String,
+    /// arbiter can decide to approve or refund the escrow
+    pub arbiter: String,
+    /// if approved, funds go to the recipient
+    pub recipient: Option<String>,
+    /// if refunded, funds go to the source
+    pub source: String,
+    /// Title of the escrow
+    pub title: String,
+    /// Longer description of the escrow, e.g. what conditions should be met
+    pub description: String,
+    /// When the escrow expires, anyone can refund it to the source
+    pub expires: Expiration,
+    /// Balance in native tokens
+    pub native_balance: Vec<Coin>,
+    /// Balance in cw20 tokens
+    pub cw20_balance: Vec<Cw20Coin>,
+    /// Whitelisted cw20 tokens
+    pub cw20_whitelist: Vec<String>,
+}
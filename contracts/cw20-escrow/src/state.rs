@@ -0,0 +1,79 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Env, Order, StdResult, Storage};
+use cw20::{Balance, Cw20CoinVerified};
+use cw_storage_plus::{Bound, Map};
+use cw_utils::{Expiration, NativeBalance};
+
+#[cw_serde]
+#[derive(Default)]
+pub struct GenericBalance {
+    pub native: NativeBalance,
+    pub cw20: Vec<Cw20CoinVerified>,
+}
+
+impl GenericBalance {
+    pub fn add_tokens(&mut self, add: Balance) {
+        match add {
+            Balance::Native(balance) => {
+                self.native += balance;
+            }
+            Balance::Cw20(token) => {
+                let index = self.cw20.iter().position(|t| t.address == token.address);
+                match index {
+                    Some(idx) => self.cw20[idx].amount += token.amount,
+                    None => self.cw20.push(token),
+                }
+            }
+        };
+    }
+}
+
+#[cw_serde]
+pub struct Escrow {
+    /// arbiter can decide to approve or refund the escrow
+    pub arbiter: Addr,
+    /// if approved, funds go to the recipient, cannot approve if recipient is none
+    pub recipient: Option<Addr>,
+    /// if refunded, funds go to the source
+    pub source: Addr,
+    /// Title of the escrow, for future usage should be used for indexing
+    pub title: String,
+    /// Longer description of the escrow, e.g. what conditions should be met
+    pub description: String,
+    /// When the escrow expires, anyone can refund it to the source.
+    /// Never expires if not set on creation.
+    pub expires: Expiration,
+    /// Balance in Native and Cw20 tokens
+    pub balance: GenericBalance,
+    /// All possible contracts that we accept tokens from
+    pub cw20_whitelist: Vec<Addr>,
+}
+
+impl Escrow {
+    pub fn is_expired(&self, env: &Env) -> bool {
+        self.expires.is_expired(&env.block)
+    }
+
+    pub fn human_whitelist(&self) -> Vec<String> {
+        self.cw20_whitelist.iter().map(|a| a.to_string()).collect()
+    }
+}
+
+pub const ESCROWS: Map<&str, Escrow> = Map::new("escrow");
+
+/// This returns the list of ids for all registered escrows, starting after `start_after`
+pub fn all_escrow_ids(
+    storage: &dyn Storage,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    ESCROWS
+        .keys(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
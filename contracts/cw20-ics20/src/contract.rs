@@ -1,26 +1,37 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, IbcMsg, IbcQuery, MessageInfo, Order,
-    PortIdResponse, Response, StdError, StdResult,
+    from_binary, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, IbcMsg,
+    IbcQuery, MessageInfo, Order, PortIdResponse, Response, StdError, StdResult, SubMsg, Uint128,
+    WasmMsg,
 };
 use semver::Version;
 
 use cw2::{get_contract_version, set_contract_version};
-use cw20::{Cw20Coin, Cw20ReceiveMsg};
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_storage_plus::Bound;
 
 use crate::amount::Amount;
 use crate::error::ContractError;
-use crate::ibc::Ics20Packet;
+use crate::ibc::{
+    channel_version, contract_gas_limit, encode_packets, refund_submsg, release_batch, send_amount,
+    Ics20Packet, ICS20_VERSION_2, TRANSFER_ID,
+};
 use crate::migrations::{v1, v2};
 use crate::msg::{
-    AllowMsg, AllowedInfo, AllowedResponse, ChannelResponse, ConfigResponse, ExecuteMsg, InitMsg,
-    ListAllowedResponse, ListChannelsResponse, MigrateMsg, PortResponse, QueryMsg, TransferMsg,
+    AllowMsg, AllowedInfo, AllowedResponse, ChannelResponse, ConfigResponse, DenomTraceResponse,
+    ExecuteMsg, FeeResponse, InitMsg, ListAllowedNativeResponse, ListAllowedResponse,
+    ListChannelsResponse, ListDenomTracesResponse, ListHooksResponse, ListPausedDenomsResponse,
+    MigrateMsg, PendingRefund, PendingRefundsResponse, PortResponse, QueryMsg, RateLimitInfo,
+    RateLimitsResponse, TransferBoundsResponse, TransferMsg, TransferResponse, TransfersResponse,
 };
 use crate::state::{
-    increase_channel_balance, AllowInfo, Config, ADMIN, ALLOW_LIST, CHANNEL_INFO, CHANNEL_STATE,
-    CONFIG,
+    assert_not_paused, assert_transfer_bounds, check_rate_limit, increase_channel_balance,
+    remove_pending_refund, transfer_fees, AllowInfo, BatchedToken, Config, DenomFee, DenomTrace,
+    Fee, Flow, Quota, RateLimit, TransferBounds, TransferInfo, TransferStatus, ACCRUED_FEES, ADMIN,
+    ALLOW_LIST, CHANNEL_INFO, CHANNEL_STATE, CONFIG, DENOM_TRACES, FEES, HOOK_ALLOW_LIST,
+    NATIVE_ALLOW_LIST, PAUSED_CHANNELS, PAUSED_DENOMS, PENDING_REFUNDS, PENDING_TRANSFERS,
+    RATE_LIMITS, REFUNDS_BY_RECIPIENT, TRANSFERS, TRANSFERS_BY_SENDER, TRANSFER_BOUNDS,
 };
 use cw_utils::{maybe_addr, nonpayable, one_coin};
 
@@ -39,8 +50,14 @@ pub fn instantiate(
     let cfg = Config {
         default_timeout: msg.default_timeout,
         default_gas_limit: msg.default_gas_limit,
+        voucher_code_id: msg.voucher_code_id,
+        restrict_native: msg.native_allowlist.is_some(),
+        allow_forwarding: msg.allow_forwarding,
     };
     CONFIG.save(deps.storage, &cfg)?;
+    for denom in msg.native_allowlist.unwrap_or_default() {
+        NATIVE_ALLOW_LIST.save(deps.storage, &denom, &Empty {})?;
+    }
 
     let admin = deps.api.addr_validate(&msg.gov_contract)?;
     ADMIN.set(deps.branch(), Some(admin))?;
@@ -69,11 +86,57 @@ pub fn execute(
             let coin = one_coin(&info)?;
             execute_transfer(deps, env, msg, Amount::Native(coin), info.sender)
         }
+        ExecuteMsg::TransferBatch(msg) => {
+            execute_transfer_batch(deps, env, msg, info.funds, info.sender)
+        }
         ExecuteMsg::Allow(allow) => execute_allow(deps, env, info, allow),
+        ExecuteMsg::AllowNative { denom } => execute_allow_native(deps, info, denom),
+        ExecuteMsg::Disallow { denom } => execute_disallow(deps, info, denom),
+        ExecuteMsg::SetTransferBounds { denom, min, max } => {
+            execute_set_transfer_bounds(deps, info, denom, min, max)
+        }
         ExecuteMsg::UpdateAdmin { admin } => {
             let admin = deps.api.addr_validate(&admin)?;
             Ok(ADMIN.execute_update_admin(deps, info, Some(admin))?)
         }
+        ExecuteMsg::AllowHook { contract } => execute_allow_hook(deps, info, contract),
+        ExecuteMsg::RemoveHook { contract } => execute_remove_hook(deps, info, contract),
+        ExecuteMsg::SetRateLimit {
+            channel,
+            denom,
+            window,
+            max_inflow,
+            max_outflow,
+        } => execute_set_rate_limit(
+            deps,
+            env,
+            info,
+            channel,
+            denom,
+            window,
+            max_inflow,
+            max_outflow,
+        ),
+        ExecuteMsg::RemoveRateLimit { channel, denom } => {
+            execute_remove_rate_limit(deps, info, channel, denom)
+        }
+        ExecuteMsg::PauseChannel { channel } => execute_pause_channel(deps, info, channel, true),
+        ExecuteMsg::ResumeChannel { channel } => execute_pause_channel(deps, info, channel, false),
+        ExecuteMsg::PauseDenom { denom } => execute_pause_denom(deps, info, denom, true),
+        ExecuteMsg::ResumeDenom { denom } => execute_pause_denom(deps, info, denom, false),
+        ExecuteMsg::ClaimRefund {} => execute_claim_refund(deps, info),
+        ExecuteMsg::RetryRefund { id } => execute_retry_refund(deps, info, id),
+        ExecuteMsg::SetFee {
+            denom,
+            fee,
+            relayer_tip,
+        } => execute_set_fee(deps, info, denom, fee, relayer_tip),
+        ExecuteMsg::ReleaseBatch { packet, relayer } => {
+            release_batch(deps, env, info, packet, relayer)
+        }
+        ExecuteMsg::WithdrawFees { denom, recipient } => {
+            execute_withdraw_fees(deps, info, denom, recipient)
+        }
     }
 }
 
@@ -95,7 +158,7 @@ pub fn execute_receive(
 }
 
 pub fn execute_transfer(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     msg: TransferMsg,
     amount: Amount,
@@ -108,6 +171,103 @@ pub fn execute_transfer(
     if !CHANNEL_INFO.has(deps.storage, &msg.channel) {
         return Err(ContractError::NoSuchChannel { id: msg.channel });
     }
+    assert_transfer_bounds(deps.storage, &amount.denom(), amount.amount())?;
+
+    // vouchers sent back over the channel they came from are burnt and redeemed on the other side
+    if let Amount::Cw20(coin) = &amount {
+        let addr = deps.api.addr_validate(&coin.address)?;
+        if let Some(trace) = DENOM_TRACES.may_load(deps.storage, &addr)? {
+            if trace.channel == msg.channel {
+                assert_not_paused(deps.storage, &msg.channel, &trace.path)?;
+                return return_voucher(deps, env, msg, addr, trace, coin.amount, sender);
+            }
+        }
+    }
+
+    let escrowed = escrow_transfer(deps.branch(), &env, &msg, amount, &sender)?;
+    let packet = escrowed.packet;
+    let send = send_packets(
+        deps,
+        &env,
+        &msg,
+        &sender,
+        vec![(packet.clone(), escrowed.relayer_tip)],
+    )?;
+
+    // send response
+    let res = Response::new()
+        .add_submessages(send)
+        .add_attribute("action", "transfer")
+        .add_attribute("sender", &packet.sender)
+        .add_attribute("receiver", &packet.receiver)
+        .add_attribute("denom", &packet.denom)
+        .add_attribute("amount", packet.amount.to_string())
+        .add_attribute("fee", escrowed.fee.to_string())
+        .add_attribute("relayer_tip", escrowed.relayer_tip.to_string());
+    Ok(res)
+}
+
+/// Sends every native coin of the funds over the channel, in one packet on ics20-2 channels
+pub fn execute_transfer_batch(
+    mut deps: DepsMut,
+    env: Env,
+    msg: TransferMsg,
+    funds: Vec<Coin>,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    if funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+    // ensure the requested channel is registered
+    if !CHANNEL_INFO.has(deps.storage, &msg.channel) {
+        return Err(ContractError::NoSuchChannel { id: msg.channel });
+    }
+
+    let mut tokens = vec![];
+    let (mut amounts, mut fees, mut tips) = (vec![], vec![], vec![]);
+    for coin in funds {
+        let amount = Amount::Native(coin);
+        if amount.is_empty() {
+            return Err(ContractError::NoFunds {});
+        }
+        assert_transfer_bounds(deps.storage, &amount.denom(), amount.amount())?;
+
+        let escrowed = escrow_transfer(deps.branch(), &env, &msg, amount, &sender)?;
+        let denom = &escrowed.packet.denom;
+        amounts.push(format!("{}{}", escrowed.packet.amount, denom));
+        fees.push(format!("{}{}", escrowed.fee, denom));
+        tips.push(format!("{}{}", escrowed.relayer_tip, denom));
+        tokens.push((escrowed.packet, escrowed.relayer_tip));
+    }
+    let send = send_packets(deps, &env, &msg, &sender, tokens)?;
+
+    let res = Response::new()
+        .add_attribute("action", "transfer_batch")
+        .add_attribute("sender", sender)
+        .add_attribute("receiver", msg.remote_address)
+        .add_attribute("packets", send.len().to_string())
+        .add_attribute("amount", amounts.join(","))
+        .add_attribute("fee", fees.join(","))
+        .add_attribute("relayer_tip", tips.join(","))
+        .add_submessages(send);
+    Ok(res)
+}
+
+/// A transfer of tokens escrowed on the channel, with the packet to send them in
+struct Escrowed {
+    packet: Ics20Packet,
+    fee: Uint128,
+    relayer_tip: Uint128,
+}
+
+// Escrows native or cw20 tokens originating on this chain, and prepares their packet
+fn escrow_transfer(
+    deps: DepsMut,
+    env: &Env,
+    msg: &TransferMsg,
+    amount: Amount,
+    sender: &Addr,
+) -> Result<Escrowed, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     // if cw20 token, validate and ensure it is whitelisted, or we set default gas limit
@@ -120,44 +280,158 @@ pub fn execute_transfer(
                 .ok_or(ContractError::NotOnAllowList)?;
         }
     };
+    if let Amount::Native(coin) = &amount {
+        if config.restrict_native && !NATIVE_ALLOW_LIST.has(deps.storage, &coin.denom) {
+            return Err(ContractError::NativeNotAllowed {
+                denom: coin.denom.clone(),
+            });
+        }
+    }
 
-    // delta from user is in seconds
-    let timeout_delta = match msg.timeout {
-        Some(t) => t,
-        None => config.default_timeout,
-    };
-    // timeout is in nanoseconds
-    let timeout = env.block.time.plus_seconds(timeout_delta);
+    let denom = amount.denom();
+    assert_not_paused(deps.storage, &msg.channel, &denom)?;
+
+    // the protocol fee and the relayer tip stay in the contract, only the rest is sent
+    let (fee, relayer_tip) = transfer_fees(deps.storage, &denom, amount.amount())?;
+    let sent = amount.amount() - fee - relayer_tip;
+    if !fee.is_zero() {
+        ACCRUED_FEES.update(deps.storage, &denom, |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default() + fee)
+        })?;
+    }
+
+    // enforce the outflow quota
+    let limit = check_rate_limit(
+        deps.storage,
+        &msg.channel,
+        &denom,
+        Flow::Out,
+        sent,
+        env.block.time,
+    )?;
+    if let Some(limit) = limit {
+        RATE_LIMITS.save(deps.storage, (&msg.channel, &denom), &limit)?;
+    }
 
     // build ics20 packet
-    let packet = Ics20Packet::new(
-        amount.amount(),
-        amount.denom(),
-        sender.as_ref(),
-        &msg.remote_address,
-    );
+    let packet = Ics20Packet::new(sent, denom.clone(), sender.as_ref(), &msg.remote_address)
+        .with_memo(msg.memo.clone());
     packet.validate()?;
 
     // Update the balance now (optimistically) like ibctransfer modules.
     // In on_packet_failure (ack with error message or a timeout), we reduce the balance appropriately.
     // This means the channel works fine if success acks are not relayed.
-    increase_channel_balance(deps.storage, &msg.channel, &amount.denom(), amount.amount())?;
+    increase_channel_balance(deps.storage, &msg.channel, &denom, sent)?;
+    Ok(Escrowed {
+        packet,
+        fee,
+        relayer_tip,
+    })
+}
+
+// Sends the tokens (with their relayer tips) in the packet format of the channel: one packet
+// with all of them on ics20-2 channels, one packet per token on ics20-1 channels.
+// Every transfer is recorded in the reply to its packet, once we know its sequence
+fn send_packets(
+    deps: DepsMut,
+    env: &Env,
+    msg: &TransferMsg,
+    sender: &Addr,
+    tokens: Vec<(Ics20Packet, Uint128)>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    // the callback is called with a gas limit, so it cannot block the acknowledgement
+    let callback = match &msg.callback {
+        Some(contract) => {
+            let contract = deps.api.addr_validate(contract)?;
+            contract_gas_limit(deps.as_ref(), &contract)?;
+            Some(contract)
+        }
+        None => None,
+    };
+    // timeout is in nanoseconds, delta from user is in seconds
+    let timeout_delta = match msg.timeout {
+        Some(t) => t,
+        None => CONFIG.load(deps.storage)?.default_timeout,
+    };
+    let timeout = env.block.time.plus_seconds(timeout_delta);
 
-    // prepare ibc message
-    let msg = IbcMsg::SendPacket {
-        channel_id: msg.channel,
-        data: to_binary(&packet)?,
-        timeout: timeout.into(),
+    let version = channel_version(deps.storage, &msg.channel)?;
+    let packets = if version == ICS20_VERSION_2 {
+        vec![tokens]
+    } else {
+        tokens.into_iter().map(|token| vec![token]).collect()
     };
+    packets
+        .into_iter()
+        .map(|tokens| {
+            let (first, relayer_tip) = &tokens[0];
+            let transfer = TransferInfo {
+                sender: sender.clone(),
+                receiver: first.receiver.clone(),
+                denom: first.denom.clone(),
+                amount: first.amount,
+                timeout,
+                status: TransferStatus::Pending,
+                callback: callback.clone(),
+                relayer_tip: *relayer_tip,
+                batch: tokens[1..]
+                    .iter()
+                    .map(|(packet, relayer_tip)| BatchedToken {
+                        denom: packet.denom.clone(),
+                        amount: packet.amount,
+                        relayer_tip: *relayer_tip,
+                    })
+                    .collect(),
+            };
+            PENDING_TRANSFERS.push_back(deps.storage, &(msg.channel.clone(), transfer))?;
+
+            let packets: Vec<_> = tokens.into_iter().map(|(packet, _)| packet).collect();
+            let send = IbcMsg::SendPacket {
+                channel_id: msg.channel.clone(),
+                data: encode_packets(&version, &packets)?,
+                timeout: timeout.into(),
+            };
+            Ok(SubMsg::reply_on_success(send, TRANSFER_ID))
+        })
+        .collect()
+}
+
+/// Burns the vouchers and sends a packet with the full denom path, so the other side releases
+/// the original tokens. On failure, the vouchers are minted back to the sender.
+fn return_voucher(
+    deps: DepsMut,
+    env: Env,
+    msg: TransferMsg,
+    voucher: Addr,
+    trace: DenomTrace,
+    amount: Uint128,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let packet = Ics20Packet::new(amount, trace.path, sender.as_ref(), &msg.remote_address)
+        .with_memo(msg.memo.clone());
+    packet.validate()?;
+
+    let burn = WasmMsg::Execute {
+        contract_addr: voucher.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    };
+    let send = send_packets(
+        deps,
+        &env,
+        &msg,
+        &sender,
+        vec![(packet.clone(), Uint128::zero())],
+    )?;
 
-    // send response
     let res = Response::new()
-        .add_message(msg)
+        .add_message(burn)
+        .add_submessages(send)
         .add_attribute("action", "transfer")
         .add_attribute("sender", &packet.sender)
         .add_attribute("receiver", &packet.receiver)
         .add_attribute("denom", &packet.denom)
-        .add_attribute("amount", &packet.amount.to_string());
+        .add_attribute("amount", packet.amount.to_string());
     Ok(res)
 }
 
@@ -202,6 +476,323 @@ pub fn execute_allow(
     Ok(res)
 }
 
+/// The gov contract can allow native denoms to be sent, if they are restricted.
+pub fn execute_allow_native(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    NATIVE_ALLOW_LIST.save(deps.storage, &denom, &Empty {})?;
+
+    let res = Response::new()
+        .add_attribute("action", "allow_native")
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// The gov contract can remove a native denom or cw20 token from the allow list.
+/// Unlike lowering the gas limit, this cannot stick tokens in the channel, as none are left there.
+pub fn execute_disallow(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let channels = CHANNEL_INFO
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for channel in channels {
+        let state = CHANNEL_STATE
+            .may_load(deps.storage, (&channel, &denom))?
+            .unwrap_or_default();
+        if !state.outstanding.is_zero() {
+            return Err(ContractError::DenomStillEscrowed { denom, channel });
+        }
+    }
+
+    match denom.strip_prefix("cw20:") {
+        Some(contract) => {
+            let addr = deps.api.addr_validate(contract)?;
+            if !ALLOW_LIST.has(deps.storage, &addr) {
+                return Err(ContractError::NotOnAllowList);
+            }
+            ALLOW_LIST.remove(deps.storage, &addr);
+        }
+        None => {
+            if !NATIVE_ALLOW_LIST.has(deps.storage, &denom) {
+                return Err(ContractError::NotOnAllowList);
+            }
+            NATIVE_ALLOW_LIST.remove(deps.storage, &denom);
+        }
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "disallow")
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// The gov contract can bound the amount of a single transfer of a denom.
+pub fn execute_set_transfer_bounds(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    min: Option<Uint128>,
+    max: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    match (min, max) {
+        (None, None) => TRANSFER_BOUNDS.remove(deps.storage, &denom),
+        (Some(min), Some(max)) if min > max => return Err(ContractError::InvalidBounds {}),
+        _ => TRANSFER_BOUNDS.save(deps.storage, &denom, &TransferBounds { min, max })?,
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_transfer_bounds")
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// The gov contract can allow contracts to be called by wasm hooks on incoming packets.
+pub fn execute_allow_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let addr = deps.api.addr_validate(&contract)?;
+    HOOK_ALLOW_LIST.save(deps.storage, &addr, &Empty {})?;
+
+    let res = Response::new()
+        .add_attribute("action", "allow_hook")
+        .add_attribute("contract", contract);
+    Ok(res)
+}
+
+/// The gov contract can stop wasm hooks from calling a contract.
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let addr = deps.api.addr_validate(&contract)?;
+    HOOK_ALLOW_LIST.remove(deps.storage, &addr);
+
+    let res = Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("contract", contract);
+    Ok(res)
+}
+
+/// The gov contract can limit the tokens flowing through a channel, to contain the damage
+/// a compromised counterparty chain can do.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_set_rate_limit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel: String,
+    denom: String,
+    window: u64,
+    max_inflow: Option<Quota>,
+    max_outflow: Option<Quota>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CHANNEL_INFO.has(deps.storage, &channel) {
+        return Err(ContractError::NoSuchChannel { id: channel });
+    }
+    let outstanding = CHANNEL_STATE
+        .may_load(deps.storage, (&channel, &denom))?
+        .unwrap_or_default()
+        .outstanding;
+
+    let too_high =
+        |quota: &Option<Quota>| matches!(quota, Some(Quota::Percent(p)) if *p > Decimal::one());
+    // outstanding only grows with outflow, so a share of nothing would block all transfers
+    let no_base = matches!(max_outflow, Some(Quota::Percent(_))) && outstanding.is_zero();
+    if window == 0 || too_high(&max_inflow) || too_high(&max_outflow) || no_base {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+
+    let limit = RateLimit::new(window, max_inflow, max_outflow, env.block.time, outstanding);
+    RATE_LIMITS.save(deps.storage, (&channel, &denom), &limit)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_rate_limit")
+        .add_attribute("channel", channel)
+        .add_attribute("denom", denom)
+        .add_attribute("window", window.to_string());
+    Ok(res)
+}
+
+pub fn execute_remove_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel: String,
+    denom: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    RATE_LIMITS.remove(deps.storage, (&channel, &denom));
+
+    let res = Response::new()
+        .add_attribute("action", "remove_rate_limit")
+        .add_attribute("channel", channel)
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// The gov contract can stop all traffic on a channel, eg. when the counterparty chain is halted
+pub fn execute_pause_channel(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel: String,
+    pause: bool,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CHANNEL_INFO.has(deps.storage, &channel) {
+        return Err(ContractError::NoSuchChannel { id: channel });
+    }
+
+    let action = if pause {
+        PAUSED_CHANNELS.save(deps.storage, &channel, &Empty {})?;
+        "pause_channel"
+    } else {
+        PAUSED_CHANNELS.remove(deps.storage, &channel);
+        "resume_channel"
+    };
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("channel", channel);
+    Ok(res)
+}
+
+/// The gov contract can stop all traffic of one denom, eg. when the token is exploited
+pub fn execute_pause_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    pause: bool,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let action = if pause {
+        PAUSED_DENOMS.save(deps.storage, &denom, &Empty {})?;
+        "pause_denom"
+    } else {
+        PAUSED_DENOMS.remove(deps.storage, &denom);
+        "resume_denom"
+    };
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// Pays out all refunds of the sender that failed on ack or timeout.
+/// Every refund is sent on its own, one that fails again stays pending.
+pub fn execute_claim_refund(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let ids = REFUNDS_BY_RECIPIENT
+        .prefix(info.sender.as_str())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if ids.is_empty() {
+        return Err(ContractError::NoPendingRefunds {});
+    }
+
+    let msgs = ids
+        .iter()
+        .map(|id| {
+            let refund = remove_pending_refund(deps.storage, *id)?;
+            Ok(refund_submsg(deps.storage, Some(*id), refund)?)
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    let res = Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "claim_refund")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("count", ids.len().to_string());
+    Ok(res)
+}
+
+/// The gov contract can pay out a refund for recipients that cannot claim it themselves.
+/// If it fails again, it stays pending.
+pub fn execute_retry_refund(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let refund = remove_pending_refund(deps.storage, id)?;
+
+    let res = Response::new()
+        .add_attribute("action", "retry_refund")
+        .add_attribute("refund_id", id.to_string())
+        .add_attribute("recipient", &refund.recipient)
+        .add_submessage(refund_submsg(deps.storage, Some(id), refund)?);
+    Ok(res)
+}
+
+/// The gov contract can set a protocol fee and a relayer tip on transfers of a local denom
+pub fn execute_set_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    fee: Option<Fee>,
+    relayer_tip: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    if let Some(fee) = &fee {
+        fee.validate()?;
+    }
+    let relayer_tip = relayer_tip.filter(|tip| !tip.is_zero());
+    if fee.is_none() && relayer_tip.is_none() {
+        FEES.remove(deps.storage, &denom);
+    } else {
+        FEES.save(deps.storage, &denom, &DenomFee { fee, relayer_tip })?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_fee")
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+/// The gov contract can withdraw the protocol fees accrued in a denom
+pub fn execute_withdraw_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let accrued = ACCRUED_FEES
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default();
+    if accrued.is_zero() {
+        return Err(ContractError::NoAccruedFees { denom });
+    }
+    ACCRUED_FEES.remove(deps.storage, &denom);
+
+    let amount = Amount::from_parts(denom.clone(), accrued);
+    let res = Response::new()
+        .add_message(send_amount(amount, recipient.to_string()))
+        .add_attribute("action", "withdraw_fees")
+        .add_attribute("denom", denom)
+        .add_attribute("amount", accrued.to_string())
+        .add_attribute("recipient", recipient);
+    Ok(res)
+}
+
 const MIGRATE_MIN_VERSION: &str = "0.11.1";
 const MIGRATE_VERSION_2: &str = "0.12.0-alpha1";
 // the new functionality starts in 0.13.1, this is the last release that needs to be migrated to v3
@@ -240,6 +831,9 @@ pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response,
         let config = Config {
             default_timeout: old_config.default_timeout,
             default_gas_limit: None,
+            voucher_code_id: None,
+            restrict_native: false,
+            allow_forwarding: false,
         };
         CONFIG.save(deps.storage, &config)?;
     }
@@ -257,6 +851,31 @@ pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response,
             Ok(old)
         })?;
     }
+    // same for the voucher code id, which enables receiving foreign tokens
+    if msg.voucher_code_id.is_some() {
+        CONFIG.update(deps.storage, |mut old| -> StdResult<_> {
+            old.voucher_code_id = msg.voucher_code_id;
+            Ok(old)
+        })?;
+    }
+
+    // and for restricting native denoms, along with the ones to allow
+    if let Some(denoms) = msg.native_allowlist {
+        CONFIG.update(deps.storage, |mut old| -> StdResult<_> {
+            old.restrict_native = true;
+            Ok(old)
+        })?;
+        for denom in denoms {
+            NATIVE_ALLOW_LIST.save(deps.storage, &denom, &Empty {})?;
+        }
+    }
+    // forwarding can be turned off again, as it affects no tokens in flight
+    if let Some(allow) = msg.allow_forwarding {
+        CONFIG.update(deps.storage, |mut old| -> StdResult<_> {
+            old.allow_forwarding = allow;
+            Ok(old)
+        })?;
+    }
 
     // we don't need to save anything if migrating from the same version
     if storage_version < version {
@@ -271,7 +890,7 @@ fn from_semver(err: semver::Error) -> StdError {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Port {} => to_binary(&query_port(deps)?),
         QueryMsg::ListChannels {} => to_binary(&query_list(deps)?),
@@ -282,6 +901,35 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&list_allowed(deps, start_after, limit)?)
         }
         QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::ListAllowedNative { start_after, limit } => {
+            to_binary(&list_allowed_native(deps, start_after, limit)?)
+        }
+        QueryMsg::TransferBounds { denom } => to_binary(&query_transfer_bounds(deps, denom)?),
+        QueryMsg::ListHooks { start_after, limit } => {
+            to_binary(&list_hooks(deps, start_after, limit)?)
+        }
+        QueryMsg::ListPausedDenoms { start_after, limit } => {
+            to_binary(&list_paused_denoms(deps, start_after, limit)?)
+        }
+        QueryMsg::RateLimits { channel } => to_binary(&query_rate_limits(deps, env, channel)?),
+        QueryMsg::DenomTrace { address } => to_binary(&query_denom_trace(deps, address)?),
+        QueryMsg::ListDenomTraces { start_after, limit } => {
+            to_binary(&list_denom_traces(deps, start_after, limit)?)
+        }
+        QueryMsg::PendingRefunds {
+            recipient,
+            start_after,
+            limit,
+        } => to_binary(&list_pending_refunds(deps, recipient, start_after, limit)?),
+        QueryMsg::Fee { denom } => to_binary(&query_fee(deps, denom)?),
+        QueryMsg::Transfer { channel, sequence } => {
+            to_binary(&query_transfer(deps, channel, sequence)?)
+        }
+        QueryMsg::TransfersBySender {
+            sender,
+            start_after,
+            limit,
+        } => to_binary(&list_transfers_by_sender(deps, sender, start_after, limit)?),
     }
 }
 
@@ -317,10 +965,13 @@ pub fn query_channel(deps: Deps, id: String) -> StdResult<ChannelResponse> {
     // we want (Vec<outstanding>, Vec<total>)
     let (balances, total_sent) = state.into_iter().unzip();
 
+    let paused = PAUSED_CHANNELS.has(deps.storage, &id);
+
     Ok(ChannelResponse {
         info,
         balances,
         total_sent,
+        paused,
     })
 }
 
@@ -331,6 +982,9 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         default_timeout: cfg.default_timeout,
         default_gas_limit: cfg.default_gas_limit,
         gov_contract: admin.into(),
+        voucher_code_id: cfg.voucher_code_id,
+        restrict_native: cfg.restrict_native,
+        allow_forwarding: cfg.allow_forwarding,
     };
     Ok(res)
 }
@@ -377,15 +1031,221 @@ fn list_allowed(
     Ok(ListAllowedResponse { allow })
 }
 
-#[cfg(test)]
+fn list_allowed_native(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListAllowedNativeResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let denoms = NATIVE_ALLOW_LIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(ListAllowedNativeResponse { denoms })
+}
+
+fn query_transfer_bounds(deps: Deps, denom: String) -> StdResult<TransferBoundsResponse> {
+    let (min, max) = match TRANSFER_BOUNDS.may_load(deps.storage, &denom)? {
+        Some(bounds) => (bounds.min, bounds.max),
+        None => (None, None),
+    };
+    Ok(TransferBoundsResponse { denom, min, max })
+}
+
+fn list_paused_denoms(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListPausedDenomsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let denoms = PAUSED_DENOMS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(ListPausedDenomsResponse { denoms })
+}
+
+fn query_rate_limits(deps: Deps, env: Env, channel: String) -> StdResult<RateLimitsResponse> {
+    let limits = RATE_LIMITS
+        .prefix(&channel)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, mut limit) = item?;
+            // show an expired window as the fresh one that the next flow would start
+            let outstanding = CHANNEL_STATE
+                .may_load(deps.storage, (&channel, &denom))?
+                .unwrap_or_default()
+                .outstanding;
+            limit.refresh(env.block.time, outstanding);
+            Ok(RateLimitInfo {
+                inflow_cap: limit.cap(Flow::In),
+                outflow_cap: limit.cap(Flow::Out),
+                window_end: limit.window_end(),
+                denom,
+                window: limit.window,
+                max_inflow: limit.max_inflow,
+                max_outflow: limit.max_outflow,
+                window_start: limit.window_start,
+                inflow: limit.inflow,
+                outflow: limit.outflow,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RateLimitsResponse { limits })
+}
+
+fn list_hooks(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListHooksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let start = addr.as_ref().map(Bound::exclusive);
+
+    let contracts = HOOK_ALLOW_LIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+    Ok(ListHooksResponse { contracts })
+}
+
+fn to_trace_response(address: Addr, trace: DenomTrace) -> DenomTraceResponse {
+    DenomTraceResponse {
+        address: address.into(),
+        channel: trace.channel,
+        path: trace.path,
+    }
+}
+
+fn query_denom_trace(deps: Deps, address: String) -> StdResult<DenomTraceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let trace = DENOM_TRACES.load(deps.storage, &addr)?;
+    Ok(to_trace_response(addr, trace))
+}
+
+fn list_denom_traces(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListDenomTracesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let start = addr.as_ref().map(Bound::exclusive);
+
+    let traces = DENOM_TRACES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(addr, trace)| to_trace_response(addr, trace)))
+        .collect::<StdResult<_>>()?;
+    Ok(ListDenomTracesResponse { traces })
+}
+
+fn list_pending_refunds(
+    deps: Deps,
+    recipient: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingRefundsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let refunds = REFUNDS_BY_RECIPIENT
+        .prefix(&recipient)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| {
+            let id = id?;
+            let refund = PENDING_REFUNDS.load(deps.storage, id)?;
+            Ok(PendingRefund {
+                id,
+                recipient: refund.recipient,
+                amount: refund.amount,
+                mint: refund.mint,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PendingRefundsResponse { refunds })
+}
+
+fn query_fee(deps: Deps, denom: String) -> StdResult<FeeResponse> {
+    let (fee, relayer_tip) = match FEES.may_load(deps.storage, &denom)? {
+        Some(config) => (config.fee, config.relayer_tip),
+        None => (None, None),
+    };
+    let accrued = ACCRUED_FEES
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default();
+    Ok(FeeResponse {
+        denom,
+        fee,
+        relayer_tip,
+        accrued,
+    })
+}
+
+fn to_transfer_response(channel: String, sequence: u64, info: TransferInfo) -> TransferResponse {
+    TransferResponse {
+        channel,
+        sequence,
+        sender: info.sender.into(),
+        receiver: info.receiver,
+        denom: info.denom,
+        amount: info.amount,
+        timeout: info.timeout,
+        status: info.status,
+        callback: info.callback.map(Addr::into_string),
+        relayer_tip: info.relayer_tip,
+        batch: info.batch,
+    }
+}
+
+fn query_transfer(deps: Deps, channel: String, sequence: u64) -> StdResult<TransferResponse> {
+    let info = TRANSFERS.load(deps.storage, (&channel, sequence))?;
+    Ok(to_transfer_response(channel, sequence, info))
+}
+
+fn list_transfers_by_sender(
+    deps: Deps,
+    sender: String,
+    start_after: Option<(String, u64)>,
+    limit: Option<u32>,
+) -> StdResult<TransfersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let sender = deps.api.addr_validate(&sender)?;
+    let start = start_after
+        .as_ref()
+        .map(|(channel, sequence)| Bound::exclusive((channel.as_str(), *sequence)));
+
+    let transfers = TRANSFERS_BY_SENDER
+        .sub_prefix(&sender)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (channel, sequence) = item?;
+            let info = TRANSFERS.load(deps.storage, (&channel, sequence))?;
+            Ok(to_transfer_response(channel, sequence, info))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(TransfersResponse { transfers })
+}
+
+#[cfg(test)]
 mod test {
     use super::*;
     use crate::test_helpers::*;
 
     use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{coin, coins, CosmosMsg, IbcMsg, StdError, Uint128};
+    use cosmwasm_std::{
+        attr, coin, coins, BankMsg, CosmosMsg, IbcMsg, OwnedDeps, StdError, Timestamp, Uint128,
+    };
 
-    use crate::state::ChannelState;
+    use crate::state::{reduce_channel_balance, ChannelState};
     use cw_utils::PaymentError;
 
     #[test]
@@ -431,6 +1291,8 @@ mod test {
             channel: send_channel.to_string(),
             remote_address: "foreign-address".to_string(),
             timeout: None,
+            memo: None,
+            callback: None,
         };
 
         // works with proper funds
@@ -492,6 +1354,8 @@ mod test {
             channel: send_channel.to_string(),
             remote_address: "foreign-address".to_string(),
             timeout: Some(7777),
+            memo: None,
+            callback: None,
         };
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: "my-account".into(),
@@ -538,6 +1402,8 @@ mod test {
             channel: send_channel.to_string(),
             remote_address: "foreign-address".to_string(),
             timeout: Some(7777),
+            memo: None,
+            callback: None,
         };
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: "my-account".into(),
@@ -556,6 +1422,9 @@ mod test {
             mock_env(),
             MigrateMsg {
                 default_gas_limit: Some(123456),
+                voucher_code_id: None,
+                native_allowlist: None,
+                allow_forwarding: None,
             },
         )
         .unwrap();
@@ -595,6 +1464,9 @@ mod test {
             mock_env(),
             MigrateMsg {
                 default_gas_limit: Some(123456),
+                voucher_code_id: None,
+                native_allowlist: None,
+                allow_forwarding: Some(true),
             },
         )
         .unwrap();
@@ -607,5 +1479,616 @@ mod test {
         // check config updates
         let config = query_config(deps.as_ref()).unwrap();
         assert_eq!(config.default_gas_limit, Some(123456));
+        assert!(config.allow_forwarding);
+    }
+
+    #[test]
+    fn outflow_rate_limit() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel], &[]);
+        let set_limit = |window, max_outflow| ExecuteMsg::SetRateLimit {
+            channel: send_channel.to_string(),
+            denom: "ucosm".to_string(),
+            window,
+            max_inflow: None,
+            max_outflow,
+        };
+
+        // only gov can set limits, and they must be valid
+        let msg = set_limit(3600, Some(Quota::Amount(Uint128::new(1000))));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("foobar", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Admin(cw_controllers::AdminError::NotAdmin {})
+        );
+        let msg = set_limit(0, Some(Quota::Amount(Uint128::new(1000))));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRateLimit {});
+        let msg = set_limit(3600, Some(Quota::Percent(Decimal::percent(101))));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRateLimit {});
+
+        let msg = set_limit(3600, Some(Quota::Amount(Uint128::new(1000))));
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+
+        let transfer = ExecuteMsg::Transfer(TransferMsg {
+            channel: send_channel.to_string(),
+            remote_address: "foreign-address".to_string(),
+            timeout: None,
+            memo: None,
+            callback: None,
+        });
+        let info = mock_info("foobar", &coins(600, "ucosm"));
+        execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap();
+
+        // exceeds the window quota
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RateLimitExceeded {
+                channel: send_channel.to_string(),
+                denom: "ucosm".to_string(),
+            }
+        );
+        // other denoms are not limited
+        let other = mock_info("foobar", &coins(5000, "ustake"));
+        execute(deps.as_mut(), mock_env(), other, transfer.clone()).unwrap();
+
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RateLimits {
+                channel: send_channel.to_string(),
+            },
+        )
+        .unwrap();
+        let res: RateLimitsResponse = from_binary(&raw).unwrap();
+        let start = mock_env().block.time;
+        assert_eq!(
+            res.limits,
+            vec![RateLimitInfo {
+                denom: "ucosm".to_string(),
+                window: 3600,
+                max_inflow: None,
+                max_outflow: Some(Quota::Amount(Uint128::new(1000))),
+                window_start: start,
+                window_end: start.plus_seconds(3600),
+                inflow: Uint128::zero(),
+                outflow: Uint128::new(600),
+                inflow_cap: None,
+                outflow_cap: Some(Uint128::new(1000)),
+            }]
+        );
+
+        // the next window has a fresh quota
+        let mut env = mock_env();
+        env.block.time = start.plus_seconds(3600);
+        execute(deps.as_mut(), env.clone(), info.clone(), transfer.clone()).unwrap();
+        let raw = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::RateLimits {
+                channel: send_channel.to_string(),
+            },
+        )
+        .unwrap();
+        let res: RateLimitsResponse = from_binary(&raw).unwrap();
+        assert_eq!(res.limits[0].window_start, env.block.time);
+        assert_eq!(res.limits[0].outflow, Uint128::new(600));
+
+        // and no limit once removed
+        let msg = ExecuteMsg::RemoveRateLimit {
+            channel: send_channel.to_string(),
+            denom: "ucosm".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("gov", &[]), msg).unwrap();
+        execute(deps.as_mut(), env, info, transfer).unwrap();
+    }
+
+    #[test]
+    fn percent_outflow_rate_limit() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel], &[]);
+        let set_limit = |window, max_outflow| ExecuteMsg::SetRateLimit {
+            channel: send_channel.to_string(),
+            denom: "ucosm".to_string(),
+            window,
+            max_inflow: None,
+            max_outflow,
+        };
+        let transfer = ExecuteMsg::Transfer(TransferMsg {
+            channel: send_channel.to_string(),
+            remote_address: "foreign-address".to_string(),
+            timeout: None,
+            memo: None,
+            callback: None,
+        });
+
+        // a share of nothing outstanding would block all transfers
+        let msg = set_limit(3600, Some(Quota::Percent(Decimal::percent(50))));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRateLimit {});
+
+        let info = mock_info("foobar", &coins(1000, "ucosm"));
+        execute(deps.as_mut(), mock_env(), info, transfer.clone()).unwrap();
+        let msg = set_limit(3600, Some(Quota::Percent(Decimal::percent(50))));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("gov", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // half of the 1000 outstanding can go out per window
+        let info = mock_info("foobar", &coins(300, "ucosm"));
+        execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimitExceeded { .. }));
+
+        // once the channel is emptied, the next window keeps the previous base
+        reduce_channel_balance(
+            deps.as_mut().storage,
+            send_channel,
+            "ucosm",
+            Uint128::new(1300),
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        execute(deps.as_mut(), env.clone(), info.clone(), transfer.clone()).unwrap();
+        let raw = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::RateLimits {
+                channel: send_channel.to_string(),
+            },
+        )
+        .unwrap();
+        let res: RateLimitsResponse = from_binary(&raw).unwrap();
+        assert_eq!(res.limits[0].outflow_cap, Some(Uint128::new(500)));
+
+        // a window too long to be represented never ends, instead of overflowing
+        let msg = set_limit(u64::MAX, Some(Quota::Amount(Uint128::new(1000))));
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info, transfer).unwrap();
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RateLimits {
+                channel: send_channel.to_string(),
+            },
+        )
+        .unwrap();
+        let res: RateLimitsResponse = from_binary(&raw).unwrap();
+        assert_eq!(res.limits[0].window_end, Timestamp::from_nanos(u64::MAX));
+    }
+
+    #[test]
+    fn pause_channel_and_denom() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel, "channel-10"], &[]);
+        let transfer = |channel: &str| {
+            ExecuteMsg::Transfer(TransferMsg {
+                channel: channel.to_string(),
+                remote_address: "foreign-address".to_string(),
+                timeout: None,
+                memo: None,
+                callback: None,
+            })
+        };
+        let info = mock_info("foobar", &coins(600, "ucosm"));
+
+        // only gov can pause, and only known channels
+        let pause = ExecuteMsg::PauseChannel {
+            channel: send_channel.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), pause.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Admin(cw_controllers::AdminError::NotAdmin {})
+        );
+        let msg = ExecuteMsg::PauseChannel {
+            channel: "channel-3".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoSuchChannel {
+                id: "channel-3".to_string()
+            }
+        );
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), pause).unwrap();
+
+        // the paused channel rejects transfers, the others work
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(send_channel),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ChannelPaused {
+                channel: send_channel.to_string()
+            }
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer("channel-10"),
+        )
+        .unwrap();
+        let chan = query_channel(deps.as_ref(), send_channel.into()).unwrap();
+        assert!(chan.paused);
+        let chan = query_channel(deps.as_ref(), "channel-10".into()).unwrap();
+        assert!(!chan.paused);
+
+        let resume = ExecuteMsg::ResumeChannel {
+            channel: send_channel.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), resume).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer(send_channel),
+        )
+        .unwrap();
+        let chan = query_channel(deps.as_ref(), send_channel.into()).unwrap();
+        assert!(!chan.paused);
+
+        // a paused denom is rejected on all channels
+        let pause = ExecuteMsg::PauseDenom {
+            denom: "ucosm".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), pause).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            transfer("channel-10"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomPaused {
+                denom: "ucosm".to_string()
+            }
+        );
+        let other = mock_info("foobar", &coins(5000, "ustake"));
+        execute(deps.as_mut(), mock_env(), other, transfer("channel-10")).unwrap();
+
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPausedDenoms {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let res: ListPausedDenomsResponse = from_binary(&raw).unwrap();
+        assert_eq!(res.denoms, vec!["ucosm".to_string()]);
+
+        let resume = ExecuteMsg::ResumeDenom {
+            denom: "ucosm".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), resume).unwrap();
+        execute(deps.as_mut(), mock_env(), info, transfer("channel-10")).unwrap();
+    }
+
+    #[test]
+    fn transfer_fees() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel], &[]);
+        let transfer = ExecuteMsg::Transfer(TransferMsg {
+            channel: send_channel.to_string(),
+            remote_address: "foreign-address".to_string(),
+            timeout: None,
+            memo: None,
+            callback: None,
+        });
+        let set_fee = |fee: Option<Fee>, relayer_tip: Option<u128>| ExecuteMsg::SetFee {
+            denom: "ucosm".to_string(),
+            fee,
+            relayer_tip: relayer_tip.map(Uint128::new),
+        };
+
+        // only gov can set fees, and no more than 100%
+        let msg = set_fee(Some(Fee::Bps(100)), Some(5));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("foobar", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Admin(cw_controllers::AdminError::NotAdmin {})
+        );
+        let invalid = set_fee(Some(Fee::Bps(10_001)), None);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), invalid).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFee {});
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+
+        // 1% fee and the tip are taken from the transfer, only the rest is sent
+        let info = mock_info("foobar", &coins(1000, "ucosm"));
+        let res = execute(deps.as_mut(), mock_env(), info, transfer.clone()).unwrap();
+        let packet = match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => {
+                from_binary::<Ics20Packet>(data).unwrap()
+            }
+            msg => panic!("Unexpected return message: {:?}", msg),
+        };
+        assert_eq!(packet.amount, Uint128::new(985));
+        let chan = query_channel(deps.as_ref(), send_channel.into()).unwrap();
+        assert_eq!(chan.balances, vec![Amount::native(985, "ucosm")]);
+        let (_, tip) = PENDING_TRANSFERS.front(&deps.storage).unwrap().unwrap();
+        assert_eq!(tip.relayer_tip, Uint128::new(5));
+
+        let raw = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Fee {
+                denom: "ucosm".to_string(),
+            },
+        )
+        .unwrap();
+        let res: FeeResponse = from_binary(&raw).unwrap();
+        assert_eq!(
+            res,
+            FeeResponse {
+                denom: "ucosm".to_string(),
+                fee: Some(Fee::Bps(100)),
+                relayer_tip: Some(Uint128::new(5)),
+                accrued: Uint128::new(10),
+            }
+        );
+
+        // nothing would be left to send
+        let msg = set_fee(Some(Fee::Flat(Uint128::new(20))), Some(5));
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+        let info = mock_info("foobar", &coins(25, "ucosm"));
+        let err = execute(deps.as_mut(), mock_env(), info, transfer.clone()).unwrap_err();
+        assert_eq!(err, ContractError::FeeExceedsAmount {});
+        // also when fee and tip together overflow
+        let msg = set_fee(Some(Fee::Flat(Uint128::MAX)), Some(5));
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+        let info = mock_info("foobar", &coins(25, "ucosm"));
+        let err = execute(deps.as_mut(), mock_env(), info, transfer.clone()).unwrap_err();
+        assert_eq!(err, ContractError::FeeExceedsAmount {});
+
+        // gov withdraws the accrued fees, once
+        let withdraw = ExecuteMsg::WithdrawFees {
+            denom: "ucosm".to_string(),
+            recipient: "treasury".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("gov", &[]),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: coins(10, "ucosm"),
+            })
+        );
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), withdraw).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoAccruedFees {
+                denom: "ucosm".to_string()
+            }
+        );
+
+        // removing the fees sends the full amount again
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("gov", &[]),
+            set_fee(None, None),
+        )
+        .unwrap();
+        let info = mock_info("foobar", &coins(25, "ucosm"));
+        let res = execute(deps.as_mut(), mock_env(), info, transfer).unwrap();
+        assert_eq!(res.attributes[4], attr("amount", "25"));
+    }
+
+    #[test]
+    fn native_allow_list_and_transfer_bounds() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel], &[]);
+        let transfer = ExecuteMsg::Transfer(TransferMsg {
+            channel: send_channel.to_string(),
+            remote_address: "foreign-address".to_string(),
+            timeout: None,
+            memo: None,
+            callback: None,
+        });
+        let send = |deps: &mut OwnedDeps<_, _, _>, coin: Coin| {
+            let info = mock_info("foobar", &[coin]);
+            execute(deps.as_mut(), mock_env(), info, transfer.clone())
+        };
+        let gov = mock_info("gov", &[]);
+
+        // any native denom can be sent until they are restricted
+        send(&mut deps, coin(500, "ustake")).unwrap();
+        let msg = MigrateMsg {
+            default_gas_limit: None,
+            voucher_code_id: None,
+            native_allowlist: Some(vec!["ucosm".to_string()]),
+            allow_forwarding: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(query_config(deps.as_ref()).unwrap().restrict_native);
+        let err = send(&mut deps, coin(500, "ustake")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NativeNotAllowed {
+                denom: "ustake".to_string()
+            }
+        );
+        send(&mut deps, coin(500, "ucosm")).unwrap();
+
+        let msg = ExecuteMsg::AllowNative {
+            denom: "uatom".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("foobar", &[]),
+            msg.clone(),
+        );
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::Admin(cw_controllers::AdminError::NotAdmin {})
+        );
+        execute(deps.as_mut(), mock_env(), gov.clone(), msg).unwrap();
+        let res = list_allowed_native(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.denoms, vec!["uatom".to_string(), "ucosm".to_string()]);
+
+        // the amount of a transfer can be bounded
+        let bounds = |min: u128, max: u128| ExecuteMsg::SetTransferBounds {
+            denom: "ucosm".to_string(),
+            min: Some(Uint128::new(min)),
+            max: Some(Uint128::new(max)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), bounds(1000, 100));
+        assert_eq!(err.unwrap_err(), ContractError::InvalidBounds {});
+        execute(deps.as_mut(), mock_env(), gov.clone(), bounds(100, 1000)).unwrap();
+        let err = send(&mut deps, coin(50, "ucosm")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AmountTooSmall {
+                min: Uint128::new(100)
+            }
+        );
+        let err = send(&mut deps, coin(2000, "ucosm")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AmountTooLarge {
+                max: Uint128::new(1000)
+            }
+        );
+        send(&mut deps, coin(1000, "ucosm")).unwrap();
+        let res = query_transfer_bounds(deps.as_ref(), "ucosm".to_string()).unwrap();
+        assert_eq!(res.min, Some(Uint128::new(100)));
+        assert_eq!(res.max, Some(Uint128::new(1000)));
+
+        // denoms can only be removed once nothing is escrowed
+        let disallow = |denom: &str| ExecuteMsg::Disallow {
+            denom: denom.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), disallow("ucosm"));
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::DenomStillEscrowed {
+                denom: "ucosm".to_string(),
+                channel: send_channel.to_string(),
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            gov.clone(),
+            disallow("cw20:token"),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::NotOnAllowList);
+        send(&mut deps, coin(500, "uatom")).unwrap();
+        CHANNEL_STATE
+            .save(
+                deps.as_mut().storage,
+                (send_channel, "uatom"),
+                &ChannelState::default(),
+            )
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), gov, disallow("uatom")).unwrap();
+        let err = send(&mut deps, coin(500, "uatom")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NativeNotAllowed {
+                denom: "uatom".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn transfer_batch() {
+        let send_channel = "channel-5";
+        let mut deps = setup(&[send_channel], &[]);
+        let transfer = TransferMsg {
+            channel: send_channel.to_string(),
+            remote_address: "foreign-address".to_string(),
+            timeout: None,
+            memo: None,
+            callback: None,
+        };
+        let msg = ExecuteMsg::TransferBatch(transfer.clone());
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("foobar", &[]),
+            msg.clone(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::NoFunds {});
+        let other = ExecuteMsg::TransferBatch(TransferMsg {
+            channel: "channel-3".to_string(),
+            ..transfer
+        });
+        let info = mock_info("foobar", &coins(100, "ucosm"));
+        let err = execute(deps.as_mut(), mock_env(), info, other);
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::NoSuchChannel {
+                id: "channel-3".to_string()
+            }
+        );
+
+        // one packet is sent per denom
+        let info = mock_info("foobar", &[coin(100, "ucosm"), coin(250, "ustake")]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let packets: Vec<Ics20Packet> = res
+            .messages
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => from_binary(data).unwrap(),
+                msg => panic!("Unexpected return message: {:?}", msg),
+            })
+            .collect();
+        assert_eq!(
+            packets,
+            vec![
+                Ics20Packet::new(Uint128::new(100), "ucosm", "foobar", "foreign-address"),
+                Ics20Packet::new(Uint128::new(250), "ustake", "foobar", "foreign-address"),
+            ]
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "transfer_batch"),
+                attr("sender", "foobar"),
+                attr("receiver", "foreign-address"),
+                attr("packets", "2"),
+                attr("amount", "100ucosm,250ustake"),
+                attr("fee", "0ucosm,0ustake"),
+                attr("relayer_tip", "0ucosm,0ustake"),
+            ]
+        );
+        assert_eq!(PENDING_TRANSFERS.len(&deps.storage).unwrap(), 2);
+
+        let chan = query_channel(deps.as_ref(), send_channel.into()).unwrap();
+        assert_eq!(
+            chan.balances,
+            vec![Amount::native(100, "ucosm"), Amount::native(250, "ustake")]
+        );
     }
 }
@@ -2,9 +2,9 @@ use std::num::TryFromIntError;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw_controllers::AdminError;
-use cw_utils::PaymentError;
+use cw_utils::{ParseReplyError, PaymentError};
 
 /// Never is a placeholder to ensure we don't return any errors
 #[derive(Error, Debug)]
@@ -21,16 +21,49 @@ pub enum ContractError {
     #[error("{0}")]
     Admin(#[from] AdminError),
 
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
     #[error("Channel doesn't exist: {id}")]
     NoSuchChannel { id: String },
 
+    #[error("Refund doesn't exist: {id}")]
+    NoSuchRefund { id: u64 },
+
+    #[error("You have no pending refunds")]
+    NoPendingRefunds {},
+
+    #[error("Fee cannot be more than 10000 basis points")]
+    InvalidFee {},
+
+    #[error("Fees exceed the transferred amount")]
+    FeeExceedsAmount {},
+
+    #[error("No fees accrued in {denom}")]
+    NoAccruedFees { denom: String },
+
+    #[error("Native denom {denom} is not on the allow list")]
+    NativeNotAllowed { denom: String },
+
+    #[error("Transfer amount below the minimum of {min}")]
+    AmountTooSmall { min: Uint128 },
+
+    #[error("Transfer amount above the maximum of {max}")]
+    AmountTooLarge { max: Uint128 },
+
+    #[error("Minimum transfer amount is larger than the maximum")]
+    InvalidBounds {},
+
+    #[error("Tokens of {denom} are still escrowed on channel {channel}")]
+    DenomStillEscrowed { denom: String, channel: String },
+
     #[error("Didn't send any funds")]
     NoFunds {},
 
     #[error("Amount larger than 2**64, not supported by ics20 packets")]
     AmountOverflow {},
 
-    #[error("Only supports channel with ibc version ics20-1, got {version}")]
+    #[error("Only supports channel with ibc version ics20-1 or ics20-2, got {version}")]
     InvalidIbcVersion { version: String },
 
     #[error("Only supports unordered channel")]
@@ -65,6 +98,57 @@ pub enum ContractError {
 
     #[error("You can only send cw20 tokens that have been explicitly allowed by governance")]
     NotOnAllowList,
+
+    #[error("Contract {contract} is not allowed to be called by wasm hooks")]
+    HookNotAllowed { contract: String },
+
+    #[error("The receiver must be the contract called by the wasm hook")]
+    HookReceiverMismatch,
+
+    #[error("Wasm hooks are only supported for tokens originating on this chain")]
+    HookOnVoucher,
+
+    #[error("Tokens can only be forwarded over the port of this contract, not {port}")]
+    ForwardOtherPort { port: String },
+
+    #[error("Forwarding received tokens is not enabled")]
+    ForwardingDisabled,
+
+    #[error("Tokens cannot be forwarded over the channel they were received on")]
+    ForwardToSourceChannel,
+
+    #[error("A packet cannot both forward tokens and call a wasm hook")]
+    ForwardWithHook,
+
+    #[error("Timeout of the forwarded packet is too far in the future")]
+    ForwardTimeoutTooLong,
+
+    #[error("Packets with several tokens cannot forward them or call a wasm hook")]
+    MemoOnBatch,
+
+    #[error("Forwarding hops of ics20-2 packets are not supported, use a forward memo")]
+    ForwardingNotSupported,
+
+    #[error("Packet contains {denom} more than once")]
+    DuplicateDenom { denom: String },
+
+    #[error("Only the contract itself can do this")]
+    InternalCallOnly,
+
+    #[error("Missing the sequence of the sent packet in the reply")]
+    NoPacketSequence,
+
+    #[error("Rate limit exceeded for {denom} on channel {channel}")]
+    RateLimitExceeded { channel: String, denom: String },
+
+    #[error("Rate limit window must be positive and percentages cannot exceed 100%")]
+    InvalidRateLimit {},
+
+    #[error("Channel {channel} is paused")]
+    ChannelPaused { channel: String },
+
+    #[error("Denom {denom} is paused")]
+    DenomPaused { denom: String },
 }
 
 impl From<FromUtf8Error> for ContractError {
@@ -2,23 +2,39 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_schema::cw_serde;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, entry_point, from_binary, to_binary, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Env,
-    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
-    IbcEndpoint, IbcOrder, IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
-    IbcReceiveResponse, Reply, Response, SubMsg, SubMsgResult, Uint128, WasmMsg,
+    attr, from_binary, from_slice, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut,
+    Empty, Env, Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannel, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcEndpoint, IbcMsg, IbcOrder,
+    IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Timestamp,
+    Uint128, WasmMsg,
 };
 
 use crate::amount::Amount;
 use crate::error::{ContractError, Never};
+use crate::msg::{ExecuteMsg, Ics20CallbackMsg};
 use crate::state::{
-    reduce_channel_balance, undo_reduce_channel_balance, ChannelInfo, ReplyArgs, ALLOW_LIST,
-    CHANNEL_INFO, CONFIG, REPLY_ARGS,
+    add_pending_refund, assert_not_paused, check_rate_limit, increase_channel_balance, receive_tip,
+    reduce_channel_balance, save_pending_refund, set_transfer_status, undo_rate_limit,
+    undo_reduce_channel_balance, ChannelInfo, DenomTrace, Flow, ForwardInfo, Refund, ReplyArgs,
+    TransferInfo, TransferStatus, VoucherForward, ACCRUED_FEES, ALLOW_LIST, CHANNEL_INFO,
+    CHANNEL_STATE, CONFIG, DENOM_TRACES, FORWARDS, HOOK_ALLOW_LIST, PAUSED_CHANNELS,
+    PENDING_FORWARD, PENDING_TRANSFERS, PENDING_VOUCHERS, PENDING_VOUCHER_FORWARD, RATE_LIMITS,
+    REFUND_ARGS, REPLY_ARGS, TRANSFERS, TRANSFERS_BY_SENDER, VOUCHERS,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20Coin, Cw20ExecuteMsg, MinterResponse};
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw_utils::parse_reply_instantiate_data;
 
 pub const ICS20_VERSION: &str = "ics20-1";
+/// ics20-2 packets carry several tokens at once, with their denom traces split into hops
+pub const ICS20_VERSION_2: &str = "ics20-2";
 pub const ICS20_ORDERING: IbcOrder = IbcOrder::Unordered;
+/// ICS20 packets don't carry the decimals of a token, so all vouchers use this
+pub const VOUCHER_DECIMALS: u8 = 6;
 
 /// The format for sending an ics20 packet.
 /// Proto defined here: https://github.com/cosmos/cosmos-sdk/blob/v0.42.0/proto/ibc/applications/transfer/v1/transfer.proto#L11-L20
@@ -33,6 +49,9 @@ pub struct Ics20Packet {
     pub receiver: String,
     /// the sender address
     pub sender: String,
+    /// optional memo, eg. a wasm hook (not part of ics20-1 packets sent by older chains)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
 }
 
 impl Ics20Packet {
@@ -42,9 +61,34 @@ impl Ics20Packet {
             amount,
             sender: sender.to_string(),
             receiver: receiver.to_string(),
+            memo: None,
         }
     }
 
+    pub fn with_memo(mut self, memo: Option<String>) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    /// Returns the wasm hook if the memo is of the form
+    /// `{"wasm": {"contract": "<address>", "msg": "<base64 encoded message>"}}`.
+    /// Any other memo is not meant for us and ignored.
+    pub fn wasm_hook(&self) -> Option<WasmHook> {
+        let memo = self.memo.as_ref()?;
+        from_slice::<HookMemo>(memo.as_bytes())
+            .ok()
+            .map(|memo| memo.wasm)
+    }
+
+    /// Returns the forward if the memo is of the form
+    /// `{"forward": {"receiver": "<address>", "port": "<port>", "channel": "<channel>", "timeout": <seconds>}}`.
+    pub fn forward(&self) -> Option<Forward> {
+        let memo = self.memo.as_ref()?;
+        from_slice::<ForwardMemo>(memo.as_bytes())
+            .ok()
+            .map(|memo| memo.forward)
+    }
+
     pub fn validate(&self) -> Result<(), ContractError> {
         if self.amount.u128() > (u64::MAX as u128) {
             Err(ContractError::AmountOverflow {})
@@ -54,6 +98,198 @@ impl Ics20Packet {
     }
 }
 
+/// The format of an ics20-2 packet, carrying several tokens at once.
+/// Proto defined in ibc-go as `ibc.applications.transfer.v2.FungibleTokenPacketDataV2`.
+/// This is compatible with the JSON serialization
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct Ics20PacketV2 {
+    /// the tokens to transfer, every denom at most once
+    pub tokens: Vec<Token>,
+    /// the sender address
+    pub sender: String,
+    /// the recipient address on the destination chain
+    pub receiver: String,
+    /// optional memo shared by all tokens, eg. a wasm hook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// hops to forward the tokens over. We never set them, and reject packets that do
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forwarding: Option<Forwarding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct Token {
+    pub denom: Denom,
+    /// encoded as a string, but limited to u64 max like in ics20-1
+    pub amount: Uint128,
+}
+
+/// A denom with the channels it was sent over, the last one first
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct Denom {
+    /// the denom on the chain the token originated on, eg. "ucosm"
+    pub base: String,
+    #[serde(default)]
+    pub trace: Vec<Hop>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct Hop {
+    pub port_id: String,
+    pub channel_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug, Default)]
+pub struct Forwarding {
+    #[serde(default)]
+    pub hops: Vec<Hop>,
+    #[serde(default)]
+    pub destination_memo: String,
+}
+
+impl Denom {
+    /// Splits the full denom path of ics20-1 (eg. "transfer/channel-3/ucosm") into its hops
+    pub fn from_path(path: &str) -> Self {
+        let mut trace = vec![];
+        let mut base = path;
+        while let [port_id, channel_id, rest] = base.splitn(3, '/').collect::<Vec<_>>()[..] {
+            if !is_channel_id(channel_id) {
+                break;
+            }
+            trace.push(Hop {
+                port_id: port_id.to_string(),
+                channel_id: channel_id.to_string(),
+            });
+            base = rest;
+        }
+        Denom {
+            base: base.to_string(),
+            trace,
+        }
+    }
+
+    /// The full denom path of ics20-1
+    pub fn path(&self) -> String {
+        self.trace
+            .iter()
+            .map(|hop| format!("{}/{}/", hop.port_id, hop.channel_id))
+            .chain(std::iter::once(self.base.clone()))
+            .collect()
+    }
+}
+
+// channel ids are generated as "channel-<n>", base denoms may contain slashes too (eg. "gamm/pool/1")
+fn is_channel_id(id: &str) -> bool {
+    matches!(id.strip_prefix("channel-"), Some(n) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Encodes the packet data in the format of the channel version. An ics20-1 packet carries exactly
+/// one token, while an ics20-2 packet carries all of them (sharing sender, receiver and memo)
+pub(crate) fn encode_packets(version: &str, packets: &[Ics20Packet]) -> StdResult<Binary> {
+    if version != ICS20_VERSION_2 {
+        return match packets {
+            [packet] => to_binary(packet),
+            _ => Err(StdError::generic_err(
+                "ics20-1 packets carry exactly one token",
+            )),
+        };
+    }
+    let first = packets
+        .first()
+        .ok_or_else(|| StdError::generic_err("no tokens to send"))?;
+    let packet = Ics20PacketV2 {
+        tokens: packets
+            .iter()
+            .map(|packet| Token {
+                denom: Denom::from_path(&packet.denom),
+                amount: packet.amount,
+            })
+            .collect(),
+        sender: first.sender.clone(),
+        receiver: first.receiver.clone(),
+        memo: first.memo.clone(),
+        forwarding: None,
+    };
+    to_binary(&packet)
+}
+
+/// Decodes the packet data in the format of the channel version, into one ics20-1 packet per token
+pub(crate) fn decode_packet(
+    version: &str,
+    data: &Binary,
+) -> Result<Vec<Ics20Packet>, ContractError> {
+    if version != ICS20_VERSION_2 {
+        return Ok(vec![from_binary(data)?]);
+    }
+    let packet: Ics20PacketV2 = from_binary(data)?;
+    if matches!(&packet.forwarding, Some(forwarding) if !forwarding.hops.is_empty()) {
+        return Err(ContractError::ForwardingNotSupported);
+    }
+    if packet.tokens.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+    let mut packets: Vec<Ics20Packet> = Vec::with_capacity(packet.tokens.len());
+    for token in packet.tokens {
+        let denom = token.denom.path();
+        if packets.iter().any(|packet| packet.denom == denom) {
+            return Err(ContractError::DuplicateDenom { denom });
+        }
+        packets.push(
+            Ics20Packet::new(token.amount, denom, &packet.sender, &packet.receiver)
+                .with_memo(packet.memo.clone()),
+        );
+    }
+    Ok(packets)
+}
+
+/// The ics20 version negotiated on the channel, which decides the format of its packets
+pub(crate) fn channel_version(
+    storage: &dyn Storage,
+    channel: &str,
+) -> Result<String, ContractError> {
+    let info =
+        CHANNEL_INFO
+            .may_load(storage, channel)?
+            .ok_or_else(|| ContractError::NoSuchChannel {
+                id: channel.to_string(),
+            })?;
+    Ok(info.version)
+}
+
+/// A memo asking the receiving chain to call a contract with the received tokens
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct HookMemo {
+    pub wasm: WasmHook,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct WasmHook {
+    /// the contract to call, it must be on the hook allow list and be the receiver of the packet
+    pub contract: String,
+    /// the message to execute on the contract. Native tokens are sent along with it, while
+    /// cw20 tokens are sent with `Cw20ExecuteMsg::Send` which wraps it in a `Cw20ReceiveMsg`
+    pub msg: Binary,
+}
+
+/// A memo asking the receiving chain to pass the tokens on over another channel,
+/// like the packet forward middleware does
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ForwardMemo {
+    pub forward: Forward,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct Forward {
+    /// the recipient on the next chain
+    pub receiver: String,
+    /// the port to send the tokens on, this must be the port of the contract
+    pub port: String,
+    /// the local channel to send the tokens on
+    pub channel: String,
+    /// How long the forwarded packet lives in seconds. If not specified, use default_timeout
+    pub timeout: Option<u64>,
+}
+
 /// This is a generic ICS acknowledgement format.
 /// Proto defined here: https://github.com/cosmos/cosmos-sdk/blob/v0.42.0/proto/ibc/core/channel/v1/channel.proto#L141-L147
 /// This is compatible with the JSON serialization
@@ -77,12 +313,33 @@ fn ack_fail(err: String) -> Binary {
 
 const RECEIVE_ID: u64 = 1337;
 const ACK_FAILURE_ID: u64 = 0xfa17;
+const VOUCHER_INSTANTIATE_ID: u64 = 0x70c4;
+const FORWARD_ID: u64 = 0xf0d;
+const FORWARD_VOUCHER_ID: u64 = 0xf0d0;
+pub(crate) const TRANSFER_ID: u64 = 0x5e4d;
+const REFUND_ID: u64 = 0x4ef0;
+const CALLBACK_ID: u64 = 0xca11;
+const TIP_ID: u64 = 0x719;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     match reply.id {
         RECEIVE_ID => match reply.result {
-            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Ok(_) => {
+                // the relayer is only paid once the tokens are released, a failure above
+                // refunds the full amount to the sender
+                let reply_args = REPLY_ARGS.load(deps.storage)?;
+                let res = match reply_args.relayer_tip {
+                    Some((relayer, tip)) => {
+                        let submsg = tip_msg(deps.as_ref(), reply_args.denom, tip, &relayer)?;
+                        Response::new()
+                            .add_submessage(submsg)
+                            .add_attribute("relayer_tip", tip)
+                    }
+                    None => Response::new(),
+                };
+                Ok(res)
+            }
             SubMsgResult::Err(err) => {
                 // Important design note:  with ibcv2 and wasmd 0.22 we can implement this all much easier.
                 // No reply needed... the receive function and submessage should return error on failure and all
@@ -103,6 +360,13 @@ pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, Contrac
                     &reply_args.denom,
                     reply_args.amount,
                 )?;
+                undo_rate_limit(
+                    deps.storage,
+                    &reply_args.channel,
+                    &reply_args.denom,
+                    Flow::In,
+                    reply_args.amount,
+                )?;
 
                 Ok(Response::new().set_data(ack_fail(err)))
             }
@@ -111,19 +375,156 @@ pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, Contrac
             SubMsgResult::Ok(_) => Ok(Response::new()),
             SubMsgResult::Err(err) => Ok(Response::new().set_data(ack_fail(err))),
         },
+        VOUCHER_INSTANTIATE_ID => match reply.result {
+            SubMsgResult::Ok(_) => {
+                // register the new voucher, it already minted the received tokens on instantiation
+                let res = parse_reply_instantiate_data(reply)?;
+                let voucher = deps.api.addr_validate(&res.contract_address)?;
+                let trace = PENDING_VOUCHERS
+                    .pop_front(deps.storage)?
+                    .ok_or_else(|| StdError::not_found("pending voucher"))?;
+                VOUCHERS.save(deps.storage, &trace.path, &voucher)?;
+                DENOM_TRACES.save(deps.storage, &voucher, &trace)?;
+                let res = match PENDING_VOUCHER_FORWARD.may_load(deps.storage)? {
+                    Some(forward) => send_voucher_forward(deps, env, forward)?,
+                    None => Response::new(),
+                };
+                Ok(res
+                    .add_attribute("action", "create_voucher")
+                    .add_attribute("voucher", voucher)
+                    .add_attribute("denom", trace.path))
+            }
+            SubMsgResult::Err(err) => {
+                PENDING_VOUCHERS.pop_front(deps.storage)?;
+                PENDING_VOUCHER_FORWARD.remove(deps.storage);
+                Ok(Response::new().set_data(ack_fail(err)))
+            }
+        },
+        FORWARD_VOUCHER_ID => {
+            let forward = PENDING_VOUCHER_FORWARD.load(deps.storage)?;
+            match reply.result {
+                SubMsgResult::Ok(_) => send_voucher_forward(deps, env, forward),
+                SubMsgResult::Err(err) => {
+                    PENDING_VOUCHER_FORWARD.remove(deps.storage);
+                    Ok(Response::new().set_data(ack_fail(err)))
+                }
+            }
+        }
+        FORWARD_ID => {
+            // the sequence of the forwarded packet is only known now
+            let (channel, forward) = PENDING_FORWARD.load(deps.storage)?;
+            PENDING_FORWARD.remove(deps.storage);
+            match parse_send_sequence(reply.result) {
+                Ok(sequence) => FORWARDS.save(deps.storage, (&channel, sequence), &forward)?,
+                // without a sequence, a failure is refunded to the receiver on this chain,
+                // like for any other packet it sent
+                Err(ContractError::NoPacketSequence) => {}
+                Err(err) => return Err(err),
+            }
+            Ok(Response::new())
+        }
+        TIP_ID => match reply.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            // the tip stays in the contract, it must not block the transfer
+            SubMsgResult::Err(err) => Ok(Response::new().add_attribute("tip_error", err)),
+        },
+        REFUND_ID => {
+            let (id, refund) = REFUND_ARGS
+                .pop_front(deps.storage)?
+                .ok_or_else(|| StdError::not_found("pending refund"))?;
+            match reply.result {
+                SubMsgResult::Ok(_) => Ok(Response::new()),
+                SubMsgResult::Err(err) => {
+                    // keep the tokens claimable, rather than stranding them in the contract
+                    let id = match id {
+                        Some(id) => {
+                            save_pending_refund(deps.storage, id, &refund)?;
+                            id
+                        }
+                        None => add_pending_refund(deps.storage, &refund)?,
+                    };
+                    Ok(Response::new()
+                        .add_attribute("action", "refund_failed")
+                        .add_attribute("refund_id", id.to_string())
+                        .add_attribute("recipient", refund.recipient)
+                        .add_attribute("error", err))
+                }
+            }
+        }
+        CALLBACK_ID => match reply.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            // a failing callback must not revert the acknowledgement and the refund
+            SubMsgResult::Err(err) => Ok(Response::new().add_attribute("callback_error", err)),
+        },
+        TRANSFER_ID => {
+            // track the transfer by the sequence of its packet
+            let (channel, transfer) = PENDING_TRANSFERS
+                .pop_front(deps.storage)?
+                .ok_or_else(|| StdError::not_found("pending transfer"))?;
+            let sequence = match parse_send_sequence(reply.result) {
+                Ok(sequence) => sequence,
+                // the packet is sent all the same, it just cannot be tracked without a sequence.
+                // Its relayer tips could not be paid from the transfer, so they go to the fees
+                Err(ContractError::NoPacketSequence) => {
+                    for (denom, tip) in relayer_tips(&transfer) {
+                        ACCRUED_FEES.update(deps.storage, denom, |accrued| -> StdResult<_> {
+                            Ok(accrued.unwrap_or_default() + tip)
+                        })?;
+                    }
+                    return Ok(Response::new()
+                        .add_attribute("channel", channel)
+                        .add_attribute("packet_sequence", "unknown"));
+                }
+                Err(err) => return Err(err),
+            };
+            TRANSFERS.save(deps.storage, (&channel, sequence), &transfer)?;
+            TRANSFERS_BY_SENDER.save(
+                deps.storage,
+                (&transfer.sender, &channel, sequence),
+                &Empty {},
+            )?;
+            Ok(Response::new()
+                .add_attribute("channel", channel)
+                .add_attribute("packet_sequence", sequence.to_string()))
+        }
         _ => Err(ContractError::UnknownReplyId { id: reply.id }),
     }
 }
 
+// The sequence of a sent packet. wasmd returns the protobuf encoded `MsgIBCSendResponse`
+// as data of `IbcMsg::SendPacket`, where the sequence is the varint in field 1
+fn parse_send_sequence(result: SubMsgResult) -> Result<u64, ContractError> {
+    let data = result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .ok_or(ContractError::NoPacketSequence)?;
+    let varint = match data.as_slice() {
+        [0x08, varint @ ..] => varint,
+        _ => return Err(ContractError::NoPacketSequence),
+    };
+    let mut sequence = 0u64;
+    for (i, byte) in varint.iter().take(10).enumerate() {
+        sequence |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(sequence);
+        }
+    }
+    Err(ContractError::NoPacketSequence)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 /// enforces ordering and versioning constraints
 pub fn ibc_channel_open(
     _deps: DepsMut,
     _env: Env,
     msg: IbcChannelOpenMsg,
-) -> Result<(), ContractError> {
-    enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
-    Ok(())
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    let version = enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
+    // in try, we settle on the version proposed by the counterparty
+    Ok(msg
+        .counterparty_version()
+        .map(|_| Ibc3ChannelOpenResponse { version }))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -134,39 +535,36 @@ pub fn ibc_channel_connect(
     msg: IbcChannelConnectMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
     // we need to check the counter party version in try and ack (sometimes here)
-    enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
+    let version = enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
 
     let channel: IbcChannel = msg.into();
     let info = ChannelInfo {
         id: channel.endpoint.channel_id,
         counterparty_endpoint: channel.counterparty_endpoint,
         connection_id: channel.connection_id,
+        version,
     };
     CHANNEL_INFO.save(deps.storage, &info.id, &info)?;
 
     Ok(IbcBasicResponse::default())
 }
 
+// Returns the negotiated version: the counterparty version once known (in try and ack),
+// otherwise the one we propose
 fn enforce_order_and_version(
     channel: &IbcChannel,
     counterparty_version: Option<&str>,
-) -> Result<(), ContractError> {
-    if channel.version != ICS20_VERSION {
+) -> Result<String, ContractError> {
+    let version = counterparty_version.unwrap_or(&channel.version);
+    if version != ICS20_VERSION && version != ICS20_VERSION_2 {
         return Err(ContractError::InvalidIbcVersion {
-            version: channel.version.clone(),
+            version: version.to_string(),
         });
     }
-    if let Some(version) = counterparty_version {
-        if version != ICS20_VERSION {
-            return Err(ContractError::InvalidIbcVersion {
-                version: version.to_string(),
-            });
-        }
-    }
     if channel.order != ICS20_ORDERING {
         return Err(ContractError::OnlyOrderedChannel {});
     }
-    Ok(())
+    Ok(version.to_string())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -185,12 +583,12 @@ pub fn ibc_channel_close(
 /// We should not return an error if possible, but rather an acknowledgement of failure
 pub fn ibc_packet_receive(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: IbcPacketReceiveMsg,
 ) -> Result<IbcReceiveResponse, Never> {
     let packet = msg.packet;
 
-    do_ibc_packet_receive(deps, &packet).or_else(|err| {
+    do_ibc_packet_receive(deps, env, &packet, &msg.relayer).or_else(|err| {
         Ok(IbcReceiveResponse::new()
             .set_ack(ack_fail(err.to_string()))
             .add_attributes(vec![
@@ -226,33 +624,98 @@ fn parse_voucher_denom<'a>(
     Ok(split_denom[2])
 }
 
+// Returns true if the denom does not start with the port and channel it was sent from,
+// meaning the token originated on (or passed through) the sending chain
+fn is_foreign_denom(denom: &str, source: &IbcEndpoint) -> bool {
+    let prefix = format!("{}/{}/", source.port_id, source.channel_id);
+    !denom.starts_with(&prefix)
+}
+
 // this does the work of ibc_packet_receive, we wrap it to turn errors into acknowledgements
 fn do_ibc_packet_receive(
     deps: DepsMut,
+    env: Env,
     packet: &IbcPacket,
+    relayer: &Addr,
 ) -> Result<IbcReceiveResponse, ContractError> {
-    let msg: Ics20Packet = from_binary(&packet.data)?;
+    let version = channel_version(deps.storage, &packet.dest.channel_id)?;
+    let mut tokens = decode_packet(&version, &packet.data)?;
+    if tokens.len() > 1 {
+        return receive_batch(env, packet, &tokens, relayer);
+    }
+    let msg = tokens.remove(0);
     let channel = packet.dest.channel_id.clone();
 
     // If the token originated on the remote chain, it looks like "ucosm".
     // If it originated on our chain, it looks like "port/channel/ucosm".
+    let hook = msg.wasm_hook();
+    let forward = msg.forward();
+    let config = CONFIG.load(deps.storage)?;
+    if forward.is_some() && !config.allow_forwarding {
+        return Err(ContractError::ForwardingDisabled);
+    }
+    if is_foreign_denom(&msg.denom, &packet.src) {
+        if let Some(code_id) = config.voucher_code_id {
+            let trace = voucher_trace(packet, &msg.denom);
+            assert_not_paused(deps.storage, &channel, &trace.path)?;
+            if hook.is_some() {
+                return Err(ContractError::HookOnVoucher);
+            }
+            if let Some(forward) = forward {
+                return forward_voucher(deps, env, packet, trace, msg, code_id, forward);
+            }
+            return receive_voucher(deps, env, trace, msg, code_id);
+        }
+    }
     let denom = parse_voucher_denom(&msg.denom, &packet.src)?;
+    assert_not_paused(deps.storage, &channel, denom)?;
+    if let Some(forward) = forward {
+        if hook.is_some() {
+            return Err(ContractError::ForwardWithHook);
+        }
+        return forward_packet(deps, env, packet, &msg, denom, forward);
+    }
+    if let Some(hook) = &hook {
+        check_hook(deps.as_ref(), hook, &msg.receiver)?;
+    }
+    // the relayer tip is taken from the released tokens
+    let tip = receive_tip(deps.storage, denom, msg.amount)?;
+
+    // enforce the inflow quota, saved only once we know we can redeem the tokens
+    let limit = check_rate_limit(
+        deps.storage,
+        &channel,
+        denom,
+        Flow::In,
+        msg.amount,
+        env.block.time,
+    )?;
 
     // make sure we have enough balance for this
     reduce_channel_balance(deps.storage, &channel, denom, msg.amount)?;
+    if let Some(limit) = limit {
+        RATE_LIMITS.save(deps.storage, (&channel, denom), &limit)?;
+    }
 
     // we need to save the data to update the balances in reply
     let reply_args = ReplyArgs {
         channel,
         denom: denom.to_string(),
         amount: msg.amount,
+        relayer_tip: Some((relayer.clone(), tip)).filter(|_| !tip.is_zero()),
     };
     REPLY_ARGS.save(deps.storage, &reply_args)?;
 
-    let to_send = Amount::from_parts(denom.to_string(), msg.amount);
+    let to_send = Amount::from_parts(denom.to_string(), msg.amount - tip);
     let gas_limit = check_gas_limit(deps.as_ref(), &to_send)?;
-    let send = send_amount(to_send, msg.receiver.clone());
-    let mut submsg = SubMsg::reply_on_error(send, RECEIVE_ID);
+    let send = match hook {
+        Some(hook) => send_to_hook(to_send, hook),
+        None => send_amount(to_send, msg.receiver.clone()),
+    };
+    let mut submsg = match reply_args.relayer_tip {
+        Some(_) => SubMsg::reply_always(send, RECEIVE_ID),
+        None => SubMsg::reply_on_error(send, RECEIVE_ID),
+    };
     submsg.gas_limit = gas_limit;
 
     let res = IbcReceiveResponse::new()
@@ -268,85 +731,902 @@ fn do_ibc_packet_receive(
     Ok(res)
 }
 
+// Packets with several tokens are released in a call of the contract to itself, so that a failure
+// of any token reverts all of them. Its error is turned into an error ack in the reply
+fn receive_batch(
+    env: Env,
+    packet: &IbcPacket,
+    tokens: &[Ics20Packet],
+    relayer: &Addr,
+) -> Result<IbcReceiveResponse, ContractError> {
+    // the memo is shared by all tokens, while hooks and forwards handle a single one
+    if tokens[0].wasm_hook().is_some() || tokens[0].forward().is_some() {
+        return Err(ContractError::MemoOnBatch);
+    }
+    let release = WasmMsg::Execute {
+        contract_addr: env.contract.address.into(),
+        msg: to_binary(&ExecuteMsg::ReleaseBatch {
+            packet: packet.clone(),
+            relayer: relayer.into(),
+        })?,
+        funds: vec![],
+    };
+    let (denoms, amounts): (Vec<_>, Vec<_>) = tokens
+        .iter()
+        .map(|token| (token.denom.as_str(), token.amount.to_string()))
+        .unzip();
+
+    let res = IbcReceiveResponse::new()
+        .set_ack(ack_success())
+        .add_submessage(SubMsg::reply_on_error(release, ACK_FAILURE_ID))
+        .add_attribute("action", "receive")
+        .add_attribute("sender", &tokens[0].sender)
+        .add_attribute("receiver", &tokens[0].receiver)
+        .add_attribute("denom", denoms.join(","))
+        .add_attribute("amount", amounts.join(","))
+        .add_attribute("success", "true");
+    Ok(res)
+}
+
+/// Releases all tokens of a received packet carrying several of them, see `receive_batch`.
+/// Every token is handled like in `do_ibc_packet_receive`, but as any error reverts the whole
+/// call, no reply has to undo the state updates
+pub(crate) fn release_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    packet: IbcPacket,
+    relayer: String,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::InternalCallOnly);
+    }
+    let relayer = deps.api.addr_validate(&relayer)?;
+    let channel = packet.dest.channel_id.clone();
+    let version = channel_version(deps.storage, &channel)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut res = Response::new().add_attribute("action", "release_batch");
+    for msg in decode_packet(&version, &packet.data)? {
+        if is_foreign_denom(&msg.denom, &packet.src) {
+            if let Some(code_id) = config.voucher_code_id {
+                let trace = voucher_trace(&packet, &msg.denom);
+                assert_not_paused(deps.storage, &channel, &trace.path)?;
+                let submsg = match mint_voucher(deps.branch(), &env, &trace, &msg, code_id)? {
+                    (mint, false) => SubMsg::new(mint),
+                    (instantiate, true) => {
+                        SubMsg::reply_on_success(instantiate, VOUCHER_INSTANTIATE_ID)
+                    }
+                };
+                res = res
+                    .add_submessage(submsg)
+                    .add_attribute("denom", trace.path)
+                    .add_attribute("amount", msg.amount);
+                continue;
+            }
+        }
+
+        let denom = parse_voucher_denom(&msg.denom, &packet.src)?;
+        assert_not_paused(deps.storage, &channel, denom)?;
+        let tip = receive_tip(deps.storage, denom, msg.amount)?;
+        let limit = check_rate_limit(
+            deps.storage,
+            &channel,
+            denom,
+            Flow::In,
+            msg.amount,
+            env.block.time,
+        )?;
+        reduce_channel_balance(deps.storage, &channel, denom, msg.amount)?;
+        if let Some(limit) = limit {
+            RATE_LIMITS.save(deps.storage, (&channel, denom), &limit)?;
+        }
+
+        let to_send = Amount::from_parts(denom.to_string(), msg.amount - tip);
+        let gas_limit = check_gas_limit(deps.as_ref(), &to_send)?;
+        let mut submsg = SubMsg::new(send_amount(to_send, msg.receiver.clone()));
+        submsg.gas_limit = gas_limit;
+        res = res
+            .add_submessage(submsg)
+            .add_attribute("denom", denom)
+            .add_attribute("amount", msg.amount);
+        if !tip.is_zero() {
+            let submsg = tip_msg(deps.as_ref(), denom.to_string(), tip, &relayer)?;
+            res = res.add_submessage(submsg).add_attribute("relayer_tip", tip);
+        }
+    }
+    Ok(res)
+}
+
+// passes the received tokens on over another channel, on behalf of the receiver.
+// The tokens stay escrowed in this contract, they just move from one channel balance to the other.
+// Unlike the packet forward middleware, the incoming packet is acknowledged right away, as the
+// acknowledgement cannot be delayed on CosmWasm 1.x. A failure of the forwarded packet is sent
+// back to the original sender in a new packet, see `refund_forward`.
+fn forward_packet(
+    deps: DepsMut,
+    env: Env,
+    packet: &IbcPacket,
+    msg: &Ics20Packet,
+    denom: &str,
+    forward: Forward,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let channel = &packet.dest.channel_id;
+    let timeout = check_forward(deps.as_ref(), &env, packet, msg, &forward)?;
+    assert_not_paused(deps.storage, &forward.channel, denom)?;
+    // vouchers forwarded to the channel they came from are burnt and sent back as such,
+    // like a transfer of the receiver would
+    let returned = voucher_of_channel(deps.as_ref(), denom, &forward.channel)?;
+
+    // both quotas must allow this, they are saved only once all checks passed
+    let now = env.block.time;
+    let inflow = check_rate_limit(deps.storage, channel, denom, Flow::In, msg.amount, now)?;
+    let outflow = match returned {
+        Some(_) => None,
+        None => check_rate_limit(
+            deps.storage,
+            &forward.channel,
+            denom,
+            Flow::Out,
+            msg.amount,
+            now,
+        )?,
+    };
+
+    reduce_channel_balance(deps.storage, channel, denom, msg.amount)?;
+    if let Some(limit) = inflow {
+        RATE_LIMITS.save(deps.storage, (channel, denom), &limit)?;
+    }
+    let mut res = IbcReceiveResponse::new();
+    let sent_denom = match returned {
+        Some((voucher, trace)) => {
+            let burn = WasmMsg::Execute {
+                contract_addr: voucher.into(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount: msg.amount })?,
+                funds: vec![],
+            };
+            res = res.add_message(burn);
+            trace.path
+        }
+        None => {
+            increase_channel_balance(deps.storage, &forward.channel, denom, msg.amount)?;
+            if let Some(limit) = outflow {
+                RATE_LIMITS.save(deps.storage, (&forward.channel, denom), &limit)?;
+            }
+            denom.to_string()
+        }
+    };
+
+    // remember where the tokens came from, so a failure can be refunded there
+    let info = ForwardInfo {
+        channel: channel.clone(),
+        sender: msg.sender.clone(),
+        receiver: msg.receiver.clone(),
+        denom: denom.to_string(),
+        amount: msg.amount,
+    };
+    let send = forward_submsg(
+        deps.storage,
+        &forward.channel,
+        &forward.receiver,
+        timeout,
+        &sent_denom,
+        info,
+    )?;
+
+    let res = res
+        .set_ack(ack_success())
+        .add_submessage(send)
+        .add_attribute("action", "receive")
+        .add_attribute("sender", &msg.sender)
+        .add_attribute("receiver", &msg.receiver)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("forward_channel", forward.channel)
+        .add_attribute("forward_receiver", forward.receiver)
+        .add_attribute("success", "true");
+    Ok(res)
+}
+
+// Checks that the tokens can be forwarded over the channel of the memo, returning the timeout
+// of the forwarded packet
+fn check_forward(
+    deps: Deps,
+    env: &Env,
+    packet: &IbcPacket,
+    msg: &Ics20Packet,
+    forward: &Forward,
+) -> Result<Timestamp, ContractError> {
+    if forward.port != packet.dest.port_id {
+        return Err(ContractError::ForwardOtherPort {
+            port: forward.port.clone(),
+        });
+    }
+    if forward.channel == packet.dest.channel_id {
+        return Err(ContractError::ForwardToSourceChannel);
+    }
+    if !CHANNEL_INFO.has(deps.storage, &forward.channel) {
+        return Err(ContractError::NoSuchChannel {
+            id: forward.channel.clone(),
+        });
+    }
+    // the denom may not be known yet (eg. a new voucher), but the channel must not be paused
+    if PAUSED_CHANNELS.has(deps.storage, &forward.channel) {
+        return Err(ContractError::ChannelPaused {
+            channel: forward.channel.clone(),
+        });
+    }
+    // the tokens are refunded to the receiver if they cannot be sent back to the sender
+    deps.api.addr_validate(&msg.receiver)?;
+    let timeout_delta = match forward.timeout {
+        Some(t) => t,
+        None => CONFIG.load(deps.storage)?.default_timeout,
+    };
+    packet_timeout(env.block.time, timeout_delta).ok_or(ContractError::ForwardTimeoutTooLong)
+}
+
+// Sends the tokens to the receiver on the next chain, on behalf of the
+// receiver on this chain. The reply records the forward under the sequence of the packet
+fn forward_submsg(
+    storage: &mut dyn Storage,
+    channel: &str,
+    receiver: &str,
+    timeout: Timestamp,
+    denom: &str,
+    info: ForwardInfo,
+) -> Result<SubMsg, ContractError> {
+    let forwarded = Ics20Packet::new(info.amount, denom, &info.receiver, receiver);
+    let version = channel_version(storage, channel)?;
+    let send = IbcMsg::SendPacket {
+        channel_id: channel.to_string(),
+        data: encode_packets(&version, &[forwarded])?,
+        timeout: timeout.into(),
+    };
+    PENDING_FORWARD.save(storage, &(channel.to_string(), info))?;
+    Ok(SubMsg::reply_on_success(send, FORWARD_ID))
+}
+
+// Foreign tokens are forwarded like a transfer of their vouchers by the receiver over another
+// channel: the vouchers are minted to this contract, which escrows them on that channel, and sent
+// on as `cw20:<voucher>`. The packet is only sent in the reply, once the vouchers are minted (or
+// the voucher contract created), so a failure there turns into an error acknowledgement.
+fn forward_voucher(
+    deps: DepsMut,
+    env: Env,
+    packet: &IbcPacket,
+    trace: DenomTrace,
+    msg: Ics20Packet,
+    code_id: u64,
+    forward: Forward,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let timeout = check_forward(deps.as_ref(), &env, packet, &msg, &forward)?;
+    // a new voucher cannot be paused or limited yet
+    if let Some(voucher) = VOUCHERS.may_load(deps.storage, &trace.path)? {
+        let denom = format!("cw20:{}", voucher);
+        assert_not_paused(deps.storage, &forward.channel, &denom)?;
+        let now = env.block.time;
+        check_rate_limit(
+            deps.storage,
+            &forward.channel,
+            &denom,
+            Flow::Out,
+            msg.amount,
+            now,
+        )?;
+    }
+
+    let escrow = Ics20Packet {
+        receiver: env.contract.address.to_string(),
+        ..msg.clone()
+    };
+    let pending = VoucherForward {
+        channel: forward.channel.clone(),
+        receiver: forward.receiver.clone(),
+        timeout,
+        info: ForwardInfo {
+            channel: packet.dest.channel_id.clone(),
+            sender: msg.sender.clone(),
+            receiver: msg.receiver.clone(),
+            denom: trace.path.clone(),
+            amount: msg.amount,
+        },
+    };
+    PENDING_VOUCHER_FORWARD.save(deps.storage, &pending)?;
+    let submsg = match mint_voucher(deps, &env, &trace, &escrow, code_id)? {
+        (mint, false) => SubMsg::reply_always(mint, FORWARD_VOUCHER_ID),
+        (instantiate, true) => SubMsg::reply_always(instantiate, VOUCHER_INSTANTIATE_ID),
+    };
+
+    let res = IbcReceiveResponse::new()
+        .set_ack(ack_success())
+        .add_submessage(submsg)
+        .add_attribute("action", "receive")
+        .add_attribute("sender", msg.sender)
+        .add_attribute("receiver", msg.receiver)
+        .add_attribute("denom", trace.path)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("forward_channel", forward.channel)
+        .add_attribute("forward_receiver", forward.receiver)
+        .add_attribute("success", "true");
+    Ok(res)
+}
+
+// escrows the vouchers minted to this contract on the forward channel and sends them on
+fn send_voucher_forward(
+    deps: DepsMut,
+    env: Env,
+    forward: VoucherForward,
+) -> Result<Response, ContractError> {
+    PENDING_VOUCHER_FORWARD.remove(deps.storage);
+    let voucher = VOUCHERS.load(deps.storage, &forward.info.denom)?;
+    let denom = format!("cw20:{}", voucher);
+    let amount = forward.info.amount;
+
+    let limit = check_rate_limit(
+        deps.storage,
+        &forward.channel,
+        &denom,
+        Flow::Out,
+        amount,
+        env.block.time,
+    )?;
+    if let Some(limit) = limit {
+        RATE_LIMITS.save(deps.storage, (&forward.channel, &denom), &limit)?;
+    }
+    increase_channel_balance(deps.storage, &forward.channel, &denom, amount)?;
+
+    let info = ForwardInfo {
+        denom: denom.clone(),
+        ..forward.info
+    };
+    let send = forward_submsg(
+        deps.storage,
+        &forward.channel,
+        &forward.receiver,
+        forward.timeout,
+        &denom,
+        info,
+    )?;
+    Ok(Response::new().add_submessage(send))
+}
+
+// the timeout of a packet sent at `now`, None if it does not fit into a timestamp
+fn packet_timeout(now: Timestamp, seconds: u64) -> Option<Timestamp> {
+    seconds
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| now.nanos().checked_add(nanos))
+        .map(Timestamp::from_nanos)
+}
+
+// only allowed contracts may be called, and the packet must be addressed to them
+fn check_hook(deps: Deps, hook: &WasmHook, receiver: &str) -> Result<(), ContractError> {
+    if hook.contract != receiver {
+        return Err(ContractError::HookReceiverMismatch);
+    }
+    let contract = deps.api.addr_validate(&hook.contract)?;
+    if !HOOK_ALLOW_LIST.has(deps.storage, &contract) {
+        return Err(ContractError::HookNotAllowed {
+            contract: hook.contract.clone(),
+        });
+    }
+    Ok(())
+}
+
+// the voucher of a foreign token is known by the full denom path on our side of the channel
+fn voucher_trace(packet: &IbcPacket, denom: &str) -> DenomTrace {
+    DenomTrace {
+        channel: packet.dest.channel_id.clone(),
+        path: format!(
+            "{}/{}/{}",
+            packet.dest.port_id, packet.dest.channel_id, denom
+        ),
+    }
+}
+
+// mints vouchers for a foreign token, creating the voucher contract the first time we see the denom
+fn receive_voucher(
+    deps: DepsMut,
+    env: Env,
+    trace: DenomTrace,
+    msg: Ics20Packet,
+    code_id: u64,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let submsg = match mint_voucher(deps, &env, &trace, &msg, code_id)? {
+        (mint, false) => SubMsg::reply_on_error(mint, ACK_FAILURE_ID),
+        (instantiate, true) => SubMsg::reply_always(instantiate, VOUCHER_INSTANTIATE_ID),
+    };
+
+    let res = IbcReceiveResponse::new()
+        .set_ack(ack_success())
+        .add_submessage(submsg)
+        .add_attribute("action", "receive")
+        .add_attribute("sender", msg.sender)
+        .add_attribute("receiver", msg.receiver)
+        .add_attribute("denom", trace.path)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("success", "true");
+    Ok(res)
+}
+
+// The message minting the vouchers, and whether it instantiates the voucher contract.
+// The reply to an instantiation registers the voucher from PENDING_VOUCHERS
+fn mint_voucher(
+    deps: DepsMut,
+    env: &Env,
+    trace: &DenomTrace,
+    msg: &Ics20Packet,
+    code_id: u64,
+) -> Result<(WasmMsg, bool), ContractError> {
+    match VOUCHERS.may_load(deps.storage, &trace.path)? {
+        Some(voucher) => {
+            let mint = Cw20ExecuteMsg::Mint {
+                recipient: msg.receiver.clone(),
+                amount: msg.amount,
+            };
+            let exec = WasmMsg::Execute {
+                contract_addr: voucher.into(),
+                msg: to_binary(&mint)?,
+                funds: vec![],
+            };
+            Ok((exec, false))
+        }
+        None => {
+            let init = voucher_instantiate_msg(env, msg);
+            let instantiate = WasmMsg::Instantiate {
+                admin: Some(env.contract.address.to_string()),
+                code_id,
+                msg: to_binary(&init)?,
+                funds: vec![],
+                label: format!("ics20 voucher {}", trace.path),
+            };
+            PENDING_VOUCHERS.push_back(deps.storage, trace)?;
+            Ok((instantiate, true))
+        }
+    }
+}
+
+// a cw20-base token minted by this contract, with the received amount as initial balance
+fn voucher_instantiate_msg(env: &Env, msg: &Ics20Packet) -> Cw20InstantiateMsg {
+    // the name and symbol are derived from the base denom (eg. "ucosm" from "transfer/channel-3/ucosm"),
+    // clipped to the limits enforced by cw20-base
+    let base_denom = msg.denom.rsplit('/').next().unwrap_or_default();
+    let name: String = format!("ICS20 voucher {}", base_denom)
+        .chars()
+        .take(50)
+        .collect();
+    let mut symbol: String = base_denom
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .take(12)
+        .collect::<String>()
+        .to_uppercase();
+    if symbol.len() < 3 {
+        symbol = "IBC".to_string();
+    }
+
+    Cw20InstantiateMsg {
+        name,
+        symbol,
+        decimals: VOUCHER_DECIMALS,
+        initial_balances: vec![Cw20Coin {
+            address: msg.receiver.clone(),
+            amount: msg.amount,
+        }],
+        mint: Some(MinterResponse {
+            minter: env.contract.address.to_string(),
+            cap: None,
+        }),
+        marketing: None,
+    }
+}
+
 fn check_gas_limit(deps: Deps, amount: &Amount) -> Result<Option<u64>, ContractError> {
     match amount {
         Amount::Cw20(coin) => {
             // if cw20 token, use the registered gas limit, or error if not whitelisted
             let addr = deps.api.addr_validate(&coin.address)?;
-            let allowed = ALLOW_LIST.may_load(deps.storage, &addr)?;
-            match allowed {
-                Some(allow) => Ok(allow.gas_limit),
-                None => match CONFIG.load(deps.storage)?.default_gas_limit {
-                    Some(base) => Ok(Some(base)),
-                    None => Err(ContractError::NotOnAllowList),
-                },
+            contract_gas_limit(deps, &addr)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The gas limit to call a contract with: the one registered on the allow list,
+/// or the default gas limit if it is not on the list (error if there is none)
+pub(crate) fn contract_gas_limit(
+    deps: Deps,
+    contract: &Addr,
+) -> Result<Option<u64>, ContractError> {
+    let allowed = ALLOW_LIST.may_load(deps.storage, contract)?;
+    match allowed {
+        Some(allow) => Ok(allow.gas_limit),
+        None => match CONFIG.load(deps.storage)?.default_gas_limit {
+            Some(base) => Ok(Some(base)),
+            None => Err(ContractError::NotOnAllowList),
+        },
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+/// check if success or failure and update balance, or return funds
+pub fn ibc_packet_ack(
+    mut deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, Never> {
+    // an ack we cannot read does not report success, so the tokens are refunded
+    let ack = from_binary(&msg.acknowledgement.data)
+        .unwrap_or_else(|err| Ics20Ack::Error(format!("invalid acknowledgement: {}", err)));
+    let failed = matches!(ack, Ics20Ack::Error(_));
+    let packet = msg.original_packet.clone();
+    // Errors are trapped, as a failing ack would be retried again and again, blocking the refund.
+    // All checks happen before the first state update, so nothing is applied halfway.
+    do_ibc_packet_ack(deps.branch(), env, ack, msg).or_else(|err| {
+        if failed {
+            Ok(trapped_refund(deps, &packet, err))
+        } else {
+            Ok(trapped_error(err))
+        }
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+/// return fund to original sender (same as failure in ibc_packet_ack)
+pub fn ibc_packet_timeout(
+    mut deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, Never> {
+    // errors are trapped like in ibc_packet_ack
+    let packet = msg.packet.clone();
+    do_ibc_packet_timeout(deps.branch(), env, msg)
+        .or_else(|err| Ok(trapped_refund(deps, &packet, err)))
+}
+
+fn trapped_error(err: ContractError) -> IbcBasicResponse {
+    IbcBasicResponse::new().add_attributes(vec![
+        attr("action", "acknowledge"),
+        attr("success", "false"),
+        attr("error", err.to_string()),
+    ])
+}
+
+// The refund of a failed packet was trapped before the first state update, so the tokens would
+// be stuck in the contract. Instead those still held by the channel are recorded as pending
+// refunds, to be claimed like refunds that failed, and the transfer is marked as failed.
+fn trapped_refund(deps: DepsMut, packet: &IbcPacket, err: ContractError) -> IbcBasicResponse {
+    let error = err.to_string();
+    let res = trapped_error(err);
+    add_trapped_refunds(deps, packet, res.clone(), error).unwrap_or(res)
+}
+
+fn add_trapped_refunds(
+    deps: DepsMut,
+    packet: &IbcPacket,
+    mut res: IbcBasicResponse,
+    error: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    let (channel, sequence) = (packet.src.channel_id.as_str(), packet.sequence);
+    let tokens = decode_packet(&channel_version(deps.storage, channel)?, &packet.data)?;
+    // a failed forward is refunded to the receiver on this chain, like without a sequence
+    FORWARDS.remove(deps.storage, (channel, sequence));
+    for msg in tokens {
+        let refund = if !is_foreign_denom(&msg.denom, &packet.src) {
+            // without a voucher, there is nothing to mint. The transfer shows the error
+            let voucher = match VOUCHERS.may_load(deps.storage, &msg.denom)? {
+                Some(voucher) => voucher,
+                None => continue,
+            };
+            Refund {
+                recipient: msg.sender,
+                amount: Amount::Cw20(Cw20Coin {
+                    address: voucher.into(),
+                    amount: msg.amount,
+                }),
+                mint: true,
+            }
+        } else {
+            // tokens the channel does not hold (any more) were redeemed already, or belong to
+            // other channels. They are not refunded, the transfer shows the error
+            if reduce_channel_balance(deps.storage, channel, &msg.denom, msg.amount).is_err() {
+                continue;
+            }
+            undo_rate_limit(deps.storage, channel, &msg.denom, Flow::Out, msg.amount)?;
+            Refund {
+                recipient: msg.sender,
+                amount: Amount::from_parts(msg.denom, msg.amount),
+                mint: false,
             }
-        }
-        _ => Ok(None),
+        };
+        let id = add_pending_refund(deps.storage, &refund)?;
+        res = res.add_attribute("pending_refund", id.to_string());
     }
+    let status = TransferStatus::Failed {
+        error: error.clone(),
+    };
+    let transfer = set_transfer_status(deps.storage, channel, sequence, status)?;
+    let callback = Ics20CallbackMsg {
+        channel: channel.to_string(),
+        sequence,
+        success: false,
+        error: Some(error),
+    };
+    with_callback(deps.as_ref(), res, transfer, callback)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-/// check if success or failure and update balance, or return funds
-pub fn ibc_packet_ack(
-    deps: DepsMut,
-    _env: Env,
+fn do_ibc_packet_ack(
+    mut deps: DepsMut,
+    env: Env,
+    ics20msg: Ics20Ack,
     msg: IbcPacketAckMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
-    // Design decision: should we trap error like in receive?
-    // TODO: unsure... as it is now a failed ack handling would revert the tx and would be
-    // retried again and again. is that good?
-    let ics20msg: Ics20Ack = from_binary(&msg.acknowledgement.data)?;
-    match ics20msg {
-        Ics20Ack::Result(_) => on_packet_success(deps, msg.original_packet),
-        Ics20Ack::Error(err) => on_packet_failure(deps, msg.original_packet, err),
-    }
+    let relayer = msg.relayer;
+    let packet = msg.original_packet;
+    let (channel, sequence) = (packet.src.channel_id.clone(), packet.sequence);
+    let tokens = decode_packet(&channel_version(deps.storage, &channel)?, &packet.data)?;
+    let (res, status, error) = match ics20msg {
+        Ics20Ack::Result(_) => (
+            on_packet_success(deps.branch(), &packet, tokens),
+            TransferStatus::Acked,
+            None,
+        ),
+        Ics20Ack::Error(err) => (
+            on_packet_failure(deps.branch(), env, &packet, tokens, err.clone())?,
+            TransferStatus::Failed { error: err.clone() },
+            Some(err),
+        ),
+    };
+    let transfer = set_transfer_status(deps.storage, &channel, sequence, status)?;
+    let res = with_tip(deps.as_ref(), res, transfer.as_ref(), &relayer);
+    let callback = Ics20CallbackMsg {
+        channel,
+        sequence,
+        success: error.is_none(),
+        error,
+    };
+    with_callback(deps.as_ref(), res, transfer, callback)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-/// return fund to original sender (same as failure in ibc_packet_ack)
-pub fn ibc_packet_timeout(
-    deps: DepsMut,
-    _env: Env,
+fn do_ibc_packet_timeout(
+    mut deps: DepsMut,
+    env: Env,
     msg: IbcPacketTimeoutMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
-    // TODO: trap error like in receive? (same question as ack above)
     let packet = msg.packet;
-    on_packet_failure(deps, packet, "timeout".to_string())
+    let (channel, sequence) = (packet.src.channel_id.clone(), packet.sequence);
+    let tokens = decode_packet(&channel_version(deps.storage, &channel)?, &packet.data)?;
+    let res = on_packet_failure(deps.branch(), env, &packet, tokens, "timeout".to_string())?;
+    let transfer = set_transfer_status(deps.storage, &channel, sequence, TransferStatus::TimedOut)?;
+    // the relayer proving the timeout is paid like the one relaying an ack
+    let res = with_tip(deps.as_ref(), res, transfer.as_ref(), &msg.relayer);
+    let callback = Ics20CallbackMsg {
+        channel,
+        sequence,
+        success: false,
+        error: Some("timeout".to_string()),
+    };
+    with_callback(deps.as_ref(), res, transfer, callback)
+}
+
+// pays the relayer tips held back from the transfer, if it has any.
+// Errors only leave the tip in the contract, they must not block the refund
+fn with_tip(
+    deps: Deps,
+    mut res: IbcBasicResponse,
+    transfer: Option<&TransferInfo>,
+    relayer: &Addr,
+) -> IbcBasicResponse {
+    let transfer = match transfer {
+        Some(transfer) => transfer,
+        None => return res,
+    };
+    for (denom, tip) in relayer_tips(transfer) {
+        res = match tip_msg(deps, denom.clone(), tip, relayer) {
+            Ok(submsg) => res.add_submessage(submsg).add_attribute("relayer_tip", tip),
+            Err(err) => res.add_attribute("tip_error", err.to_string()),
+        };
+    }
+    res
+}
+
+// the relayer tips held back from the tokens of the transfer
+fn relayer_tips(transfer: &TransferInfo) -> impl Iterator<Item = (&String, Uint128)> {
+    let batch = transfer
+        .batch
+        .iter()
+        .map(|token| (&token.denom, token.relayer_tip));
+    std::iter::once((&transfer.denom, transfer.relayer_tip))
+        .chain(batch)
+        .filter(|(_, tip)| !tip.is_zero())
+}
+
+fn tip_msg(
+    deps: Deps,
+    denom: String,
+    tip: Uint128,
+    relayer: &Addr,
+) -> Result<SubMsg, ContractError> {
+    let amount = Amount::from_parts(denom, tip);
+    let gas_limit = check_gas_limit(deps, &amount)?;
+    let mut submsg = SubMsg::reply_on_error(send_amount(amount, relayer.into()), TIP_ID);
+    submsg.gas_limit = gas_limit;
+    Ok(submsg)
+}
+
+// notifies the callback contract of the transfer, if it has one.
+// It is called last, after the refund, and its errors are ignored in the reply
+fn with_callback(
+    deps: Deps,
+    res: IbcBasicResponse,
+    transfer: Option<TransferInfo>,
+    msg: Ics20CallbackMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let contract = match transfer.and_then(|transfer| transfer.callback) {
+        Some(contract) => contract,
+        None => return Ok(res),
+    };
+    // the allow list may have changed since the transfer
+    let gas_limit = match contract_gas_limit(deps, &contract) {
+        Ok(gas_limit) => gas_limit,
+        Err(err) => return Ok(res.add_attribute("callback_error", err.to_string())),
+    };
+    let mut submsg = SubMsg::reply_on_error(msg.into_cosmos_msg(contract)?, CALLBACK_ID);
+    submsg.gas_limit = gas_limit;
+    Ok(res.add_submessage(submsg))
 }
 
 // update the balance stored on this (channel, denom) index
-fn on_packet_success(_deps: DepsMut, packet: IbcPacket) -> Result<IbcBasicResponse, ContractError> {
-    let msg: Ics20Packet = from_binary(&packet.data)?;
+fn on_packet_success(
+    deps: DepsMut,
+    packet: &IbcPacket,
+    tokens: Vec<Ics20Packet>,
+) -> IbcBasicResponse {
+    FORWARDS.remove(deps.storage, (&packet.src.channel_id, packet.sequence));
 
     // similar event messages like ibctransfer module
-    let attributes = vec![
-        attr("action", "acknowledge"),
-        attr("sender", &msg.sender),
-        attr("receiver", &msg.receiver),
-        attr("denom", &msg.denom),
-        attr("amount", msg.amount),
-        attr("success", "true"),
-    ];
-
-    Ok(IbcBasicResponse::new().add_attributes(attributes))
+    let mut res = IbcBasicResponse::new();
+    for msg in tokens {
+        res = res.add_attributes(vec![
+            attr("action", "acknowledge"),
+            attr("sender", &msg.sender),
+            attr("receiver", &msg.receiver),
+            attr("denom", &msg.denom),
+            attr("amount", msg.amount),
+            attr("success", "true"),
+        ]);
+    }
+    res
 }
 
-// return the tokens to sender
+// return the tokens of the packet to sender
 fn on_packet_failure(
+    mut deps: DepsMut,
+    env: Env,
+    packet: &IbcPacket,
+    tokens: Vec<Ics20Packet>,
+    err: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    if tokens.len() > 1 {
+        check_batch_refund(deps.as_ref(), packet, &tokens)?;
+    }
+    let mut res = IbcBasicResponse::new();
+    for msg in tokens {
+        let refund = refund_token(deps.branch(), env.clone(), packet, msg, err.clone())?;
+        res = res
+            .add_submessages(refund.messages)
+            .add_attributes(refund.attributes);
+    }
+    Ok(res)
+}
+
+// Errors of acks and timeouts are trapped without reverting the state, so refunding a packet with
+// several tokens must not fail halfway. We check what could fail before refunding the first token
+fn check_batch_refund(
+    deps: Deps,
+    packet: &IbcPacket,
+    tokens: &[Ics20Packet],
+) -> Result<(), ContractError> {
+    for msg in tokens {
+        if !is_foreign_denom(&msg.denom, &packet.src) {
+            VOUCHERS.load(deps.storage, &msg.denom)?;
+            continue;
+        }
+        let state = CHANNEL_STATE
+            .may_load(deps.storage, (&packet.src.channel_id, &msg.denom))?
+            .unwrap_or_default();
+        if state.outstanding < msg.amount {
+            return Err(ContractError::InsufficientFunds {});
+        }
+        check_gas_limit(deps, &Amount::from_parts(msg.denom.clone(), msg.amount))?;
+    }
+    Ok(())
+}
+
+fn refund_token(
     deps: DepsMut,
-    packet: IbcPacket,
+    env: Env,
+    packet: &IbcPacket,
+    msg: Ics20Packet,
     err: String,
 ) -> Result<IbcBasicResponse, ContractError> {
-    let msg: Ics20Packet = from_binary(&packet.data)?;
+    let key = (packet.src.channel_id.as_str(), packet.sequence);
+    // vouchers were burnt when sent back, so we mint them again
+    if !is_foreign_denom(&msg.denom, &packet.src) {
+        let voucher = VOUCHERS.load(deps.storage, &msg.denom)?;
+        if let Some(forward) = FORWARDS.may_load(deps.storage, key)? {
+            // the vouchers were escrowed on the channel they were forwarded from
+            let timeout_delta = CONFIG.load(deps.storage)?.default_timeout;
+            let timeout = packet_timeout(env.block.time, timeout_delta)
+                .ok_or(ContractError::ForwardTimeoutTooLong)?;
+            let mint = WasmMsg::Execute {
+                contract_addr: voucher.into(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: env.contract.address.into(),
+                    amount: msg.amount,
+                })?,
+                funds: vec![],
+            };
+            FORWARDS.remove(deps.storage, key);
+            let res = refund_forward(deps, msg, forward, timeout, err)?;
+            return Ok(res.add_message(mint));
+        }
+        let refund = Refund {
+            recipient: msg.sender.clone(),
+            amount: Amount::Cw20(Cw20Coin {
+                address: voucher.into(),
+                amount: msg.amount,
+            }),
+            mint: true,
+        };
+        let res = IbcBasicResponse::new()
+            .add_submessage(refund_submsg(deps.storage, None, refund)?)
+            .add_attribute("action", "acknowledge")
+            .add_attribute("sender", msg.sender)
+            .add_attribute("receiver", msg.receiver)
+            .add_attribute("denom", msg.denom)
+            .add_attribute("amount", msg.amount.to_string())
+            .add_attribute("success", "false")
+            .add_attribute("error", err);
+        return Ok(res);
+    }
+
+    let forward = match FORWARDS.may_load(deps.storage, key)? {
+        // errors are trapped without reverting the state, so the timeout of the packet sending
+        // the tokens back is checked before the first update
+        Some(forward) => {
+            let timeout_delta = CONFIG.load(deps.storage)?.default_timeout;
+            let timeout = packet_timeout(env.block.time, timeout_delta)
+                .ok_or(ContractError::ForwardTimeoutTooLong)?;
+            Some((forward, timeout))
+        }
+        None => None,
+    };
+    let refund = Refund {
+        recipient: msg.sender.clone(),
+        amount: Amount::from_parts(msg.denom.clone(), msg.amount),
+        mint: false,
+    };
+    let gas_limit = match forward {
+        Some(_) => None,
+        None => check_gas_limit(deps.as_ref(), &refund.amount)?,
+    };
 
     // undo the balance update on failure (as we pre-emptively added it on send)
     reduce_channel_balance(deps.storage, &packet.src.channel_id, &msg.denom, msg.amount)?;
+    undo_rate_limit(
+        deps.storage,
+        &packet.src.channel_id,
+        &msg.denom,
+        Flow::Out,
+        msg.amount,
+    )?;
+
+    if let Some((forward, timeout)) = forward {
+        FORWARDS.remove(deps.storage, key);
+        return refund_forward(deps, msg, forward, timeout, err);
+    }
 
-    let to_send = Amount::from_parts(msg.denom.clone(), msg.amount);
-    let gas_limit = check_gas_limit(deps.as_ref(), &to_send)?;
-    let send = send_amount(to_send, msg.sender.clone());
-    let mut submsg = SubMsg::reply_on_error(send, ACK_FAILURE_ID);
+    let mut submsg = refund_submsg(deps.storage, None, refund)?;
     submsg.gas_limit = gas_limit;
 
     // similar event messages like ibctransfer module
@@ -363,7 +1643,157 @@ fn on_packet_failure(
     Ok(res)
 }
 
-fn send_amount(amount: Amount, recipient: String) -> CosmosMsg {
+// sends the tokens back, or mints them again for vouchers
+/// Sends the refund, a failed one is kept in PENDING_REFUNDS to be claimed later.
+/// `id` is set for refunds that are pending already.
+pub(crate) fn refund_submsg(
+    storage: &mut dyn Storage,
+    id: Option<u64>,
+    refund: Refund,
+) -> StdResult<SubMsg> {
+    let msg = refund_msg(refund.clone())?;
+    REFUND_ARGS.push_back(storage, &(id, refund))?;
+    Ok(SubMsg::reply_always(msg, REFUND_ID))
+}
+
+fn refund_msg(refund: Refund) -> StdResult<CosmosMsg> {
+    match refund.amount {
+        Amount::Cw20(coin) if refund.mint => {
+            let mint = Cw20ExecuteMsg::Mint {
+                recipient: refund.recipient,
+                amount: coin.amount,
+            };
+            Ok(WasmMsg::Execute {
+                contract_addr: coin.address,
+                msg: to_binary(&mint)?,
+                funds: vec![],
+            }
+            .into())
+        }
+        amount => Ok(send_amount(amount, refund.recipient)),
+    }
+}
+
+// The voucher behind a `cw20:<voucher>` denom, if it is one of ours for tokens from the channel
+fn voucher_of_channel(
+    deps: Deps,
+    denom: &str,
+    channel: &str,
+) -> Result<Option<(Addr, DenomTrace)>, ContractError> {
+    let voucher = match denom.strip_prefix("cw20:") {
+        Some(voucher) => Addr::unchecked(voucher),
+        None => return Ok(None),
+    };
+    match DENOM_TRACES.may_load(deps.storage, &voucher)? {
+        Some(trace) if trace.channel == channel => Ok(Some((voucher, trace))),
+        _ => Ok(None),
+    }
+}
+
+// The forwarded tokens are sent back to the original sender on the chain they came from.
+// The incoming packet was acknowledged already, so this is a new packet, sent on behalf of the
+// receiver on this chain. It is tracked like a transfer of the receiver, and refunded to them
+// if it fails as well.
+// Forwarded vouchers of tokens from the source channel are burnt and sent back as such.
+fn refund_forward(
+    deps: DepsMut,
+    msg: Ics20Packet,
+    forward: ForwardInfo,
+    timeout: Timestamp,
+    err: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut res = IbcBasicResponse::new();
+    let denom = match voucher_of_channel(deps.as_ref(), &forward.denom, &forward.channel)? {
+        Some((voucher, trace)) => {
+            let burn = WasmMsg::Execute {
+                contract_addr: voucher.into(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: forward.amount,
+                })?,
+                funds: vec![],
+            };
+            res = res.add_message(burn);
+            trace.path
+        }
+        None => {
+            increase_channel_balance(
+                deps.storage,
+                &forward.channel,
+                &forward.denom,
+                forward.amount,
+            )?;
+            undo_rate_limit(
+                deps.storage,
+                &forward.channel,
+                &forward.denom,
+                Flow::In,
+                forward.amount,
+            )?;
+            forward.denom
+        }
+    };
+
+    // if this fails as well, the tokens are refunded to the receiver on this chain
+    let refund = Ics20Packet::new(forward.amount, &denom, &forward.receiver, &forward.sender);
+    let version = channel_version(deps.storage, &forward.channel)?;
+    let send = IbcMsg::SendPacket {
+        channel_id: forward.channel.clone(),
+        data: encode_packets(&version, &[refund])?,
+        timeout: timeout.into(),
+    };
+    let transfer = TransferInfo {
+        // validated when forwarding
+        sender: Addr::unchecked(&forward.receiver),
+        receiver: forward.sender.clone(),
+        denom,
+        amount: forward.amount,
+        timeout,
+        status: TransferStatus::Pending,
+        callback: None,
+        relayer_tip: Uint128::zero(),
+        batch: vec![],
+    };
+    PENDING_TRANSFERS.push_back(deps.storage, &(forward.channel.clone(), transfer))?;
+
+    let res = res
+        .add_submessage(SubMsg::reply_on_success(send, TRANSFER_ID))
+        .add_attribute("action", "acknowledge")
+        .add_attribute("sender", msg.sender)
+        .add_attribute("receiver", msg.receiver)
+        .add_attribute("denom", msg.denom)
+        .add_attribute("amount", msg.amount.to_string())
+        .add_attribute("success", "false")
+        .add_attribute("error", err)
+        .add_attribute("refund_channel", forward.channel)
+        .add_attribute("refund_receiver", forward.sender);
+    Ok(res)
+}
+
+fn send_to_hook(amount: Amount, hook: WasmHook) -> CosmosMsg {
+    match amount {
+        Amount::Native(coin) => WasmMsg::Execute {
+            contract_addr: hook.contract,
+            msg: hook.msg,
+            funds: vec![coin],
+        }
+        .into(),
+        Amount::Cw20(coin) => {
+            let msg = Cw20ExecuteMsg::Send {
+                contract: hook.contract,
+                amount: coin.amount,
+                msg: hook.msg,
+            };
+            WasmMsg::Execute {
+                contract_addr: coin.address,
+                msg: to_binary(&msg).unwrap(),
+                funds: vec![],
+            }
+            .into()
+        }
+    }
+}
+
+pub(crate) fn send_amount(amount: Amount, recipient: String) -> CosmosMsg {
     match amount {
         Amount::Native(coin) => BankMsg::Send {
             to_address: recipient,
@@ -393,9 +1823,14 @@ mod test {
     use crate::contract::{execute, migrate, query_channel};
     use crate::msg::{ExecuteMsg, MigrateMsg, TransferMsg};
     use cosmwasm_std::testing::{mock_env, mock_info};
-    use cosmwasm_std::{coins, to_vec, IbcEndpoint, IbcMsg, IbcTimeout, Timestamp};
+    use cosmwasm_std::{
+        coins, to_vec, Addr, IbcAcknowledgement, IbcEndpoint, IbcMsg, IbcTimeout, SubMsgResponse,
+        Timestamp,
+    };
     use cw20::Cw20ReceiveMsg;
 
+    use crate::state::PENDING_REFUNDS;
+
     #[test]
     fn check_ack_json() {
         let success = Ics20Ack::Result(b"1".into());
@@ -421,6 +1856,250 @@ mod test {
 
         let encdoded = String::from_utf8(to_vec(&packet).unwrap()).unwrap();
         assert_eq!(expected, encdoded.as_str());
+
+        // the memo is only included if set, and optional when parsing
+        let decoded: Ics20Packet = from_slice(expected.as_bytes()).unwrap();
+        assert_eq!(decoded, packet);
+        let packet = packet.with_memo(Some("hello".to_string()));
+        let expected = r#"{"amount":"12345","denom":"ucosm","receiver":"wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc","sender":"cosmos1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n","memo":"hello"}"#;
+        let encdoded = String::from_utf8(to_vec(&packet).unwrap()).unwrap();
+        assert_eq!(expected, encdoded.as_str());
+    }
+
+    #[test]
+    fn check_packet_v2_json() {
+        let packets = vec![
+            Ics20Packet::new(Uint128::new(12345), "ucosm", "sender", "receiver"),
+            Ics20Packet::new(
+                Uint128::new(678),
+                "transfer/channel-3/gamm/pool/1",
+                "sender",
+                "receiver",
+            ),
+        ];
+        let data = encode_packets(ICS20_VERSION_2, &packets).unwrap();
+        let expected = r#"{"tokens":[{"denom":{"base":"ucosm","trace":[]},"amount":"12345"},{"denom":{"base":"gamm/pool/1","trace":[{"port_id":"transfer","channel_id":"channel-3"}]},"amount":"678"}],"sender":"sender","receiver":"receiver"}"#;
+        assert_eq!(expected, String::from_utf8(data.to_vec()).unwrap());
+        assert_eq!(decode_packet(ICS20_VERSION_2, &data).unwrap(), packets);
+
+        // ics20-1 packets carry exactly one token
+        encode_packets(ICS20_VERSION, &packets).unwrap_err();
+        let data = encode_packets(ICS20_VERSION, &packets[..1]).unwrap();
+        assert_eq!(data, to_binary(&packets[0]).unwrap());
+        assert_eq!(decode_packet(ICS20_VERSION, &data).unwrap(), packets[..1]);
+    }
+
+    #[test]
+    fn reject_invalid_packet_v2() {
+        let token = |base: &str| Token {
+            denom: Denom::from_path(base),
+            amount: Uint128::new(1),
+        };
+        let packet = |tokens, forwarding| {
+            let packet = Ics20PacketV2 {
+                tokens,
+                sender: "sender".to_string(),
+                receiver: "receiver".to_string(),
+                memo: None,
+                forwarding,
+            };
+            to_binary(&packet).unwrap()
+        };
+
+        let err = decode_packet(ICS20_VERSION_2, &packet(vec![], None)).unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+        let tokens = vec![token("ucosm"), token("ustake"), token("ucosm")];
+        let err = decode_packet(ICS20_VERSION_2, &packet(tokens, None)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateDenom {
+                denom: "ucosm".to_string()
+            }
+        );
+
+        // only an empty forwarding is accepted
+        let forwarding = Forwarding {
+            hops: vec![Hop {
+                port_id: "transfer".to_string(),
+                channel_id: "channel-4".to_string(),
+            }],
+            destination_memo: String::new(),
+        };
+        let tokens = vec![token("ucosm")];
+        let err =
+            decode_packet(ICS20_VERSION_2, &packet(tokens.clone(), Some(forwarding))).unwrap_err();
+        assert_eq!(err, ContractError::ForwardingNotSupported);
+        let data = packet(tokens, Some(Forwarding::default()));
+        decode_packet(ICS20_VERSION_2, &data).unwrap();
+    }
+
+    #[test]
+    fn negotiate_version() {
+        let mut deps = setup(&[], &[]);
+        let mut channel = mock_channel("channel-1");
+
+        // we propose either version
+        channel.version = ICS20_VERSION_2.to_string();
+        let open = IbcChannelOpenMsg::new_init(channel.clone());
+        let res = ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap();
+        assert_eq!(res, None);
+        channel.version = "ics20-3".to_string();
+        let open = IbcChannelOpenMsg::new_init(channel.clone());
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidIbcVersion {
+                version: "ics20-3".to_string()
+            }
+        );
+
+        // in try, we answer with the version proposed by the counterparty
+        channel.version = ICS20_VERSION.to_string();
+        let open = IbcChannelOpenMsg::new_try(channel.clone(), ICS20_VERSION_2);
+        let res = ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap();
+        assert_eq!(
+            res,
+            Some(Ibc3ChannelOpenResponse {
+                version: ICS20_VERSION_2.to_string()
+            })
+        );
+        let open = IbcChannelOpenMsg::new_try(channel.clone(), "ics20-3");
+        ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap_err();
+
+        // the version the counterparty settled on is stored
+        let connect = IbcChannelConnectMsg::new_ack(channel, ICS20_VERSION_2);
+        ibc_channel_connect(deps.as_mut(), mock_env(), connect).unwrap();
+        let info = query_channel(deps.as_ref(), "channel-1".to_string())
+            .unwrap()
+            .info;
+        assert_eq!(info.version, ICS20_VERSION_2);
+
+        // channels opened before ics20-2 was supported are ics20-1
+        let old = r#"{"id":"channel-1","counterparty_endpoint":{"port_id":"transfer","channel_id":"channel-15"},"connection_id":"connection-2"}"#;
+        let info: ChannelInfo = from_slice(old.as_bytes()).unwrap();
+        assert_eq!(info.version, ICS20_VERSION);
+    }
+
+    #[test]
+    fn parse_wasm_hook() {
+        let packet = Ics20Packet::new(Uint128::new(1), "ucosm", "sender", "hook");
+        assert_eq!(packet.wasm_hook(), None);
+
+        // other memos are ignored
+        let packet = packet.with_memo(Some("hello".to_string()));
+        assert_eq!(packet.wasm_hook(), None);
+        let packet = packet.with_memo(Some(r#"{"forward":{"port":"transfer"}}"#.to_string()));
+        assert_eq!(packet.wasm_hook(), None);
+
+        let memo = r#"{"wasm":{"contract":"hook","msg":"eyJwaW5nIjp7fX0="}}"#;
+        let packet = packet.with_memo(Some(memo.to_string()));
+        assert_eq!(
+            packet.wasm_hook(),
+            Some(WasmHook {
+                contract: "hook".to_string(),
+                msg: br#"{"ping":{}}"#.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_forward() {
+        let packet = Ics20Packet::new(Uint128::new(1), "ucosm", "sender", "hub");
+        assert_eq!(packet.forward(), None);
+
+        // incomplete forwards are ignored
+        let packet = packet.with_memo(Some(r#"{"forward":{"port":"transfer"}}"#.to_string()));
+        assert_eq!(packet.forward(), None);
+
+        let memo = r#"{"forward":{"receiver":"final","port":"transfer","channel":"channel-4","timeout":600}}"#;
+        let packet = packet.with_memo(Some(memo.to_string()));
+        assert_eq!(
+            packet.forward(),
+            Some(Forward {
+                receiver: "final".to_string(),
+                port: "transfer".to_string(),
+                channel: "channel-4".to_string(),
+                timeout: Some(600),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_packet_sequence() {
+        let result = |data: Option<&[u8]>| {
+            SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: data.map(Binary::from),
+            })
+        };
+        assert_eq!(parse_send_sequence(result(Some(&[0x08, 0x07]))).unwrap(), 7);
+        assert_eq!(
+            parse_send_sequence(result(Some(&[0x08, 0xac, 0x02]))).unwrap(),
+            300
+        );
+        assert_eq!(
+            parse_send_sequence(result(None)).unwrap_err(),
+            ContractError::NoPacketSequence
+        );
+        assert_eq!(
+            parse_send_sequence(result(Some(&[0x08, 0xac]))).unwrap_err(),
+            ContractError::NoPacketSequence
+        );
+    }
+
+    #[test]
+    fn transfer_without_packet_sequence() {
+        let send_channel = "channel-9";
+        let mut deps = setup(&[send_channel], &[]);
+        let transfer = |deps: DepsMut| {
+            let msg = ExecuteMsg::Transfer(TransferMsg {
+                channel: send_channel.to_string(),
+                remote_address: "my-remote-address".to_string(),
+                timeout: None,
+                memo: None,
+                callback: None,
+            });
+            let info = mock_info("local-sender", &coins(100, "ucosm"));
+            execute(deps, mock_env(), info, msg).unwrap();
+        };
+        let sent = |data: Option<&[u8]>| Reply {
+            id: TRANSFER_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: data.map(Binary::from),
+            }),
+        };
+
+        // the transfer goes through, but is not tracked
+        transfer(deps.as_mut());
+        let res = reply(deps.as_mut(), mock_env(), sent(None)).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("channel", send_channel),
+                attr("packet_sequence", "unknown")
+            ]
+        );
+        assert!(PENDING_TRANSFERS.is_empty(&deps.storage).unwrap());
+        assert!(TRANSFERS.is_empty(&deps.storage));
+
+        // the next one with a sequence is tracked as usual
+        transfer(deps.as_mut());
+        reply(deps.as_mut(), mock_env(), sent(Some(&[0x08, 0x02]))).unwrap();
+        let transfer_info = TRANSFERS.load(&deps.storage, (send_channel, 2)).unwrap();
+        assert_eq!(transfer_info.amount, Uint128::new(100));
+
+        // a relayer tip cannot be paid without tracking, it is added to the fees instead
+        let msg = ExecuteMsg::SetFee {
+            denom: "ucosm".to_string(),
+            fee: None,
+            relayer_tip: Some(Uint128::new(7)),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+        transfer(deps.as_mut());
+        reply(deps.as_mut(), mock_env(), sent(None)).unwrap();
+        let accrued = ACCRUED_FEES.load(&deps.storage, "ucosm").unwrap();
+        assert_eq!(accrued, Uint128::new(7));
     }
 
     fn cw20_payment(
@@ -465,6 +2144,7 @@ mod test {
             amount: amount.into(),
             sender: "remote-sender".to_string(),
             receiver: receiver.to_string(),
+            memo: None,
         };
         print!("Packet denom: {}", &data.denom);
         IbcPacket::new(
@@ -499,7 +2179,7 @@ mod test {
             mock_receive_packet(send_channel, 1876543210, cw20_denom, "local-rcpt");
 
         // cannot receive this denom yet
-        let msg = IbcPacketReceiveMsg::new(recv_packet.clone());
+        let msg = IbcPacketReceiveMsg::new(recv_packet.clone(), relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert!(res.messages.is_empty());
         let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
@@ -511,6 +2191,8 @@ mod test {
             channel: send_channel.to_string(),
             remote_address: "remote-rcpt".to_string(),
             timeout: None,
+            memo: None,
+            callback: None,
         };
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: "local-sender".to_string(),
@@ -525,15 +2207,19 @@ mod test {
             amount: Uint128::new(987654321),
             sender: "local-sender".to_string(),
             receiver: "remote-rcpt".to_string(),
+            memo: None,
         };
         let timeout = mock_env().block.time.plus_seconds(DEFAULT_TIMEOUT);
         assert_eq!(
             &res.messages[0],
-            &SubMsg::new(IbcMsg::SendPacket {
-                channel_id: send_channel.to_string(),
-                data: to_binary(&expected).unwrap(),
-                timeout: IbcTimeout::with_timestamp(timeout),
-            })
+            &SubMsg::reply_on_success(
+                IbcMsg::SendPacket {
+                    channel_id: send_channel.to_string(),
+                    data: to_binary(&expected).unwrap(),
+                    timeout: IbcTimeout::with_timestamp(timeout),
+                },
+                TRANSFER_ID
+            )
         );
 
         // query channel state|_|
@@ -542,14 +2228,14 @@ mod test {
         assert_eq!(state.total_sent, vec![Amount::cw20(987654321, cw20_addr)]);
 
         // cannot receive more than we sent
-        let msg = IbcPacketReceiveMsg::new(recv_high_packet);
+        let msg = IbcPacketReceiveMsg::new(recv_high_packet, relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert!(res.messages.is_empty());
         let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
         assert_eq!(ack, no_funds);
 
         // we can receive less than we sent
-        let msg = IbcPacketReceiveMsg::new(recv_packet);
+        let msg = IbcPacketReceiveMsg::new(recv_packet, relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert_eq!(1, res.messages.len());
         assert_eq!(
@@ -579,7 +2265,7 @@ mod test {
         let recv_high_packet = mock_receive_packet(send_channel, 1876543210, denom, "local-rcpt");
 
         // cannot receive this denom yet
-        let msg = IbcPacketReceiveMsg::new(recv_packet.clone());
+        let msg = IbcPacketReceiveMsg::new(recv_packet.clone(), relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert!(res.messages.is_empty());
         let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
@@ -591,6 +2277,8 @@ mod test {
             channel: send_channel.to_string(),
             remote_address: "my-remote-address".to_string(),
             timeout: None,
+            memo: None,
+            callback: None,
         });
         let info = mock_info("local-sender", &coins(987654321, denom));
         execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -601,14 +2289,14 @@ mod test {
         assert_eq!(state.total_sent, vec![Amount::native(987654321, denom)]);
 
         // cannot receive more than we sent
-        let msg = IbcPacketReceiveMsg::new(recv_high_packet);
+        let msg = IbcPacketReceiveMsg::new(recv_high_packet, relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert!(res.messages.is_empty());
         let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
         assert_eq!(ack, no_funds);
 
         // we can receive less than we sent
-        let msg = IbcPacketReceiveMsg::new(recv_packet);
+        let msg = IbcPacketReceiveMsg::new(recv_packet, relayer());
         let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
         assert_eq!(1, res.messages.len());
         assert_eq!(
@@ -626,6 +2314,220 @@ mod test {
         assert_eq!(state.total_sent, vec![Amount::native(987654321, denom)]);
     }
 
+    #[test]
+    fn receive_foreign_token_creates_voucher() {
+        let channel = "channel-3";
+        let mut deps = setup(&[channel], &[]);
+        let data = Ics20Packet::new(Uint128::new(5000), "uatom", "remote-sender", "local-rcpt");
+        let packet = IbcPacket::new(
+            to_binary(&data).unwrap(),
+            IbcEndpoint {
+                port_id: REMOTE_PORT.to_string(),
+                channel_id: "channel-35".to_string(),
+            },
+            IbcEndpoint {
+                port_id: CONTRACT_PORT.to_string(),
+                channel_id: channel.to_string(),
+            },
+            3,
+            Timestamp::from_seconds(1665321069).into(),
+        );
+
+        // without a voucher code id, we only accept our own tokens
+        let msg = IbcPacketReceiveMsg::new(packet.clone(), relayer());
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
+        assert_eq!(
+            ack,
+            Ics20Ack::Error(ContractError::NoForeignTokens {}.to_string())
+        );
+
+        let msg = MigrateMsg {
+            default_gas_limit: None,
+            voucher_code_id: Some(17),
+            native_allowlist: None,
+            allow_forwarding: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        // the first packet instantiates a voucher with the amount as initial balance
+        let msg = IbcPacketReceiveMsg::new(packet.clone(), relayer());
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        let ack: Ics20Ack = from_binary(&res.acknowledgement).unwrap();
+        assert!(matches!(ack, Ics20Ack::Result(_)));
+        assert_eq!(1, res.messages.len());
+        assert_eq!(res.messages[0].id, VOUCHER_INSTANTIATE_ID);
+        let path = format!("{}/{}/uatom", CONTRACT_PORT, channel);
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id,
+                msg,
+                label,
+                ..
+            }) => {
+                assert_eq!(*code_id, 17);
+                assert_eq!(label, &format!("ics20 voucher {}", path));
+                let init: Cw20InstantiateMsg = from_binary(msg).unwrap();
+                assert_eq!(init.symbol, "UATOM");
+                assert_eq!(init.decimals, VOUCHER_DECIMALS);
+                assert_eq!(
+                    init.initial_balances,
+                    vec![Cw20Coin {
+                        address: "local-rcpt".to_string(),
+                        amount: Uint128::new(5000),
+                    }]
+                );
+                assert_eq!(
+                    init.mint.unwrap().minter,
+                    mock_env().contract.address.to_string()
+                );
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // the reply registers the new voucher
+        let mut data = vec![0x0a, 8];
+        data.extend_from_slice(b"voucher1");
+        let instantiated = Reply {
+            id: VOUCHER_INSTANTIATE_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(data.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), instantiated).unwrap();
+        let voucher = Addr::unchecked("voucher1");
+        assert_eq!(VOUCHERS.load(&deps.storage, &path).unwrap(), voucher);
+        assert_eq!(
+            DENOM_TRACES.load(&deps.storage, &voucher).unwrap(),
+            DenomTrace {
+                channel: channel.to_string(),
+                path,
+            }
+        );
+
+        // and the next packet mints on the existing voucher
+        let msg = IbcPacketReceiveMsg::new(packet, relayer());
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        let mint = Cw20ExecuteMsg::Mint {
+            recipient: "local-rcpt".to_string(),
+            amount: Uint128::new(5000),
+        };
+        let exec = WasmMsg::Execute {
+            contract_addr: voucher.into(),
+            msg: to_binary(&mint).unwrap(),
+            funds: vec![],
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_error(exec, ACK_FAILURE_ID)]
+        );
+    }
+
+    #[test]
+    fn ack_errors_are_trapped() {
+        let send_channel = "channel-9";
+        let mut deps = setup(&[send_channel], &[]);
+        let packet_of = |denom: &str, sequence| {
+            let data = Ics20Packet::new(Uint128::new(500), denom, "local-sender", "remote-rcpt");
+            IbcPacket::new(
+                to_binary(&data).unwrap(),
+                IbcEndpoint {
+                    port_id: CONTRACT_PORT.to_string(),
+                    channel_id: send_channel.to_string(),
+                },
+                IbcEndpoint {
+                    port_id: REMOTE_PORT.to_string(),
+                    channel_id: "channel-1234".to_string(),
+                },
+                sequence,
+                Timestamp::from_seconds(1665321069).into(),
+            )
+        };
+        let trapped = |err: ContractError| {
+            vec![
+                attr("action", "acknowledge"),
+                attr("success", "false"),
+                attr("error", err.to_string()),
+            ]
+        };
+
+        // we never sent these tokens, so there is no balance to refund them from. They may have
+        // been redeemed already, so nothing can be claimed
+        let packet = packet_of("ucosm", 3);
+        let ack = IbcAcknowledgement::new(ack_fail("rejected".to_string()));
+        let msg = IbcPacketAckMsg::new(ack, packet.clone(), relayer());
+        let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(res.attributes, trapped(ContractError::InsufficientFunds {}));
+
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            IbcPacketTimeoutMsg::new(packet_of("ucosm", 4), relayer()),
+        )
+        .unwrap();
+        assert_eq!(res.attributes, trapped(ContractError::InsufficientFunds {}));
+        assert!(PENDING_REFUNDS.is_empty(&deps.storage));
+        assert!(REFUND_ARGS.is_empty(&deps.storage).unwrap());
+
+        // without the voucher there is nothing to mint, the transfer shows the error
+        let denom = format!("{}/{}/uatom", CONTRACT_PORT, send_channel);
+        let transfer = TransferInfo {
+            sender: Addr::unchecked("local-sender"),
+            receiver: "remote-rcpt".to_string(),
+            denom: denom.clone(),
+            amount: Uint128::new(500),
+            timeout: Timestamp::from_seconds(1665321069),
+            status: TransferStatus::Pending,
+            callback: None,
+            relayer_tip: Uint128::zero(),
+            batch: vec![],
+        };
+        TRANSFERS
+            .save(deps.as_mut().storage, (send_channel, 5), &transfer)
+            .unwrap();
+        let ack = IbcAcknowledgement::new(ack_fail("rejected".to_string()));
+        let msg = IbcPacketAckMsg::new(ack, packet_of(&denom, 5), relayer());
+        let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        let err = StdError::not_found("cosmwasm_std::addresses::Addr");
+        assert_eq!(res.attributes, trapped(err.into()));
+        assert!(PENDING_REFUNDS.is_empty(&deps.storage));
+        let status = TRANSFERS
+            .load(&deps.storage, (send_channel, 5))
+            .unwrap()
+            .status;
+        assert!(matches!(status, TransferStatus::Failed { .. }));
+
+        // an ack we cannot decode is an error, and the tokens are refunded as usual
+        increase_channel_balance(
+            deps.as_mut().storage,
+            send_channel,
+            "ucosm",
+            Uint128::new(500),
+        )
+        .unwrap();
+        let ack = IbcAcknowledgement::new(b"not json".to_vec());
+        let msg = IbcPacketAckMsg::new(ack, packet_of("ucosm", 6), relayer());
+        let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        let send = BankMsg::Send {
+            to_address: "local-sender".to_string(),
+            amount: coins(500, "ucosm"),
+        };
+        assert_eq!(res.messages, vec![SubMsg::reply_always(send, REFUND_ID)]);
+        let error = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "error")
+            .unwrap();
+        assert!(error.value.starts_with("invalid acknowledgement"));
+        let state = CHANNEL_STATE
+            .load(&deps.storage, (send_channel, "ucosm"))
+            .unwrap();
+        assert_eq!(state.outstanding, Uint128::zero());
+    }
+
     #[test]
     fn check_gas_limit_handles_all_cases() {
         let send_channel = "channel-9";
@@ -648,6 +2550,9 @@ mod test {
             mock_env(),
             MigrateMsg {
                 default_gas_limit: Some(def_limit),
+                voucher_code_id: None,
+                native_allowlist: None,
+                allow_forwarding: None,
             },
         )
         .unwrap();
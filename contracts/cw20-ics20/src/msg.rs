@@ -1,8 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{
+    to_binary, Binary, CosmosMsg, IbcPacket, StdResult, Timestamp, Uint128, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
 use crate::amount::Amount;
-use crate::state::ChannelInfo;
+use crate::state::{BatchedToken, ChannelInfo, Fee, Quota, TransferStatus};
 
 #[cw_serde]
 pub struct InitMsg {
@@ -15,6 +18,17 @@ pub struct InitMsg {
     /// If set, contracts off the allowlist will run with this gas limit.
     /// If unset, will refuse to accept any contract off the allow list.
     pub default_gas_limit: Option<u64>,
+    /// If set, foreign tokens are received as cw20 vouchers instantiated from this cw20-base code id.
+    /// If unset, only tokens originating on this chain can be received.
+    pub voucher_code_id: Option<u64>,
+    /// If set, only these native denoms (and the ones allowed later by governance) can be sent.
+    /// If unset, any native denom can be sent.
+    #[serde(default)]
+    pub native_allowlist: Option<Vec<String>>,
+    /// If set, received tokens with a forward memo are sent on to the next chain.
+    /// The incoming packet is acknowledged before the forwarded one, see the README.
+    #[serde(default)]
+    pub allow_forwarding: bool,
 }
 
 #[cw_serde]
@@ -26,6 +40,13 @@ pub struct AllowMsg {
 #[cw_serde]
 pub struct MigrateMsg {
     pub default_gas_limit: Option<u64>,
+    pub voucher_code_id: Option<u64>,
+    /// If set, restricts native denoms to the allow list, adding these denoms to it
+    #[serde(default)]
+    pub native_allowlist: Option<Vec<String>>,
+    /// If set, enables or disables forwarding of received tokens
+    #[serde(default)]
+    pub allow_forwarding: Option<bool>,
 }
 
 #[cw_serde]
@@ -34,10 +55,68 @@ pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     /// This allows us to transfer *exactly one* native token
     Transfer(TransferMsg),
+    /// This allows us to transfer several native tokens at once. Sends one packet with all of them
+    /// on ics20-2 channels, and one packet per denom on ics20-1 channels
+    TransferBatch(TransferMsg),
     /// This must be called by gov_contract, will allow a new cw20 token to be sent
     Allow(AllowMsg),
+    /// This must be called by gov_contract, will allow a native denom to be sent
+    AllowNative { denom: String },
+    /// This must be called by gov_contract, removes a native denom or cw20 token (as `cw20:<address>`)
+    /// from the allow list. Only possible once none of its tokens are escrowed on any channel.
+    Disallow { denom: String },
+    /// This must be called by gov_contract, sets the minimum and maximum amount of one transfer of
+    /// a local denom (eg. `ucosm` or `cw20:<address>`). Removes the bounds if both are unset.
+    SetTransferBounds {
+        denom: String,
+        min: Option<Uint128>,
+        max: Option<Uint128>,
+    },
     /// Change the admin (must be called by current admin)
     UpdateAdmin { admin: String },
+    /// This must be called by gov_contract, will allow a contract to be called by wasm hooks
+    /// in the memo of incoming packets
+    AllowHook { contract: String },
+    /// This must be called by gov_contract, will stop wasm hooks from calling the contract
+    RemoveHook { contract: String },
+    /// This must be called by gov_contract, limits the tokens of one denom that can flow through
+    /// the channel per window (in seconds). Replaces any existing limit and starts a new window.
+    SetRateLimit {
+        channel: String,
+        denom: String,
+        window: u64,
+        max_inflow: Option<Quota>,
+        max_outflow: Option<Quota>,
+    },
+    /// This must be called by gov_contract, removes the rate limit of one denom on the channel
+    RemoveRateLimit { channel: String, denom: String },
+    /// This must be called by gov_contract, stops all transfers over the channel.
+    /// Refunds of packets already sent are still processed.
+    PauseChannel { channel: String },
+    /// This must be called by gov_contract, allows transfers over the channel again
+    ResumeChannel { channel: String },
+    /// This must be called by gov_contract, stops all transfers of the denom on every channel.
+    /// This is the local denom (eg. `ucosm` or `cw20:<address>`) or the full path of a voucher.
+    PauseDenom { denom: String },
+    /// This must be called by gov_contract, allows transfers of the denom again
+    ResumeDenom { denom: String },
+    /// Sends all refunds of failed transfers that could not be paid out to the sender.
+    /// Refunds that fail again stay pending.
+    ClaimRefund {},
+    /// This must be called by gov_contract, sends a refund that could not be paid out
+    RetryRefund { id: u64 },
+    /// This must be called by gov_contract, sets the fees on transfers of a local denom
+    /// (eg. `ucosm` or `cw20:<address>`). Removes them if both are unset.
+    SetFee {
+        denom: String,
+        fee: Option<Fee>,
+        relayer_tip: Option<Uint128>,
+    },
+    /// This must be called by gov_contract, sends all accrued fees of the denom to the recipient
+    WithdrawFees { denom: String, recipient: String },
+    /// Only callable by the contract itself, releases all tokens of a received ics20-2 packet
+    /// carrying several tokens. Being a message of its own, any failure reverts all of them.
+    ReleaseBatch { packet: IbcPacket, relayer: String },
 }
 
 /// This is the message we accept via Receive
@@ -51,6 +130,52 @@ pub struct TransferMsg {
     pub remote_address: String,
     /// How long the packet lives in seconds. If not specified, use default_timeout
     pub timeout: Option<u64>,
+    /// An optional memo to add to the IBC transfer, eg. a wasm hook to be executed on arrival
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// An optional contract to notify with an `Ics20CallbackMsg` once the packet is acknowledged
+    /// or timed out. It must be on the allow list, unless there is a default gas limit.
+    #[serde(default)]
+    pub callback: Option<String>,
+}
+
+/// Sent to the callback contract of a transfer once its packet is acknowledged or timed out.
+/// It should be de/serialized under `Ics20Callback()` variant in a ExecuteMsg
+#[cw_serde]
+pub struct Ics20CallbackMsg {
+    /// the local channel the packet was sent on
+    pub channel: String,
+    /// the sequence of the packet, as returned in the `packet_sequence` attribute of the transfer
+    pub sequence: u64,
+    /// whether the tokens arrived, otherwise they were refunded
+    pub success: bool,
+    /// the error of the counterparty chain, or "timeout"
+    pub error: Option<String>,
+}
+
+impl Ics20CallbackMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = CallbackExecuteMsg::Ics20Callback(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[cw_serde]
+enum CallbackExecuteMsg {
+    Ics20Callback(Ics20CallbackMsg),
 }
 
 #[cw_serde]
@@ -79,6 +204,60 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// List all allowed native denoms.
+    #[returns(ListAllowedNativeResponse)]
+    ListAllowedNative {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show the minimum and maximum amount of one transfer of a local denom.
+    #[returns(TransferBoundsResponse)]
+    TransferBounds { denom: String },
+    /// List all contracts that can be called by wasm hooks.
+    #[returns(ListHooksResponse)]
+    ListHooks {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// List all paused denoms.
+    #[returns(ListPausedDenomsResponse)]
+    ListPausedDenoms {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Show all rate limits on the channel, along with the usage of their current window.
+    #[returns(RateLimitsResponse)]
+    RateLimits { channel: String },
+    /// Returns the denom trace of a voucher contract, error if it is not one of our vouchers.
+    #[returns(DenomTraceResponse)]
+    DenomTrace { address: String },
+    /// List the denom traces of all vouchers we created for foreign tokens.
+    #[returns(ListDenomTracesResponse)]
+    ListDenomTraces {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// List the refunds of the recipient that could not be paid out and can be claimed.
+    #[returns(PendingRefundsResponse)]
+    PendingRefunds {
+        recipient: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Show the fees on transfers of a local denom, along with the fees accrued so far.
+    #[returns(FeeResponse)]
+    Fee { denom: String },
+    /// Returns an outgoing transfer by the sequence of its packet, error if there is none.
+    #[returns(TransferResponse)]
+    Transfer { channel: String, sequence: u64 },
+    /// List all outgoing transfers of the sender. start_after is the (channel, sequence)
+    /// of the last transfer on the previous page.
+    #[returns(TransfersResponse)]
+    TransfersBySender {
+        sender: String,
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -95,6 +274,13 @@ pub struct ChannelResponse {
     /// The total number of tokens that have been sent over this channel
     /// (even if many have been returned, so balance is low)
     pub total_sent: Vec<Amount>,
+    /// Whether all transfers over this channel are stopped
+    pub paused: bool,
+}
+
+#[cw_serde]
+pub struct ListPausedDenomsResponse {
+    pub denoms: Vec<String>,
 }
 
 #[cw_serde]
@@ -107,6 +293,11 @@ pub struct ConfigResponse {
     pub default_timeout: u64,
     pub default_gas_limit: Option<u64>,
     pub gov_contract: String,
+    pub voucher_code_id: Option<u64>,
+    /// whether only native denoms on the allow list can be sent
+    pub restrict_native: bool,
+    /// whether received tokens can be forwarded to another chain
+    pub allow_forwarding: bool,
 }
 
 #[cw_serde]
@@ -125,3 +316,109 @@ pub struct AllowedInfo {
     pub contract: String,
     pub gas_limit: Option<u64>,
 }
+
+#[cw_serde]
+pub struct ListAllowedNativeResponse {
+    pub denoms: Vec<String>,
+}
+
+#[cw_serde]
+pub struct TransferBoundsResponse {
+    pub denom: String,
+    pub min: Option<Uint128>,
+    pub max: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct ListHooksResponse {
+    pub contracts: Vec<String>,
+}
+
+#[cw_serde]
+pub struct RateLimitsResponse {
+    pub limits: Vec<RateLimitInfo>,
+}
+
+#[cw_serde]
+pub struct RateLimitInfo {
+    pub denom: String,
+    /// length of a window in seconds
+    pub window: u64,
+    pub max_inflow: Option<Quota>,
+    pub max_outflow: Option<Quota>,
+    pub window_start: Timestamp,
+    pub window_end: Timestamp,
+    /// tokens received in the current window
+    pub inflow: Uint128,
+    /// tokens sent in the current window
+    pub outflow: Uint128,
+    /// the maximum inflow for the current window, None if unlimited
+    pub inflow_cap: Option<Uint128>,
+    /// the maximum outflow for the current window, None if unlimited
+    pub outflow_cap: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct DenomTraceResponse {
+    /// the cw20 voucher contract
+    pub address: String,
+    /// the local channel the foreign tokens are received on
+    pub channel: String,
+    /// the full denom on this chain: `{port}/{channel}/{denom on the sending chain}`
+    pub path: String,
+}
+
+#[cw_serde]
+pub struct ListDenomTracesResponse {
+    pub traces: Vec<DenomTraceResponse>,
+}
+
+#[cw_serde]
+pub struct TransferResponse {
+    /// the local channel the packet was sent on
+    pub channel: String,
+    pub sequence: u64,
+    pub sender: String,
+    /// the receiver on the counterparty chain
+    pub receiver: String,
+    /// the denom as sent in the packet
+    pub denom: String,
+    pub amount: Uint128,
+    pub timeout: Timestamp,
+    pub status: TransferStatus,
+    /// the contract notified once the packet is acknowledged or timed out
+    pub callback: Option<String>,
+    /// paid to the relayer of the ack or timeout, on top of the amount
+    pub relayer_tip: Uint128,
+    /// the other tokens sent in the same ics20-2 packet
+    pub batch: Vec<BatchedToken>,
+}
+
+#[cw_serde]
+pub struct FeeResponse {
+    pub denom: String,
+    pub fee: Option<Fee>,
+    pub relayer_tip: Option<Uint128>,
+    /// protocol fees collected so far, that can be withdrawn
+    pub accrued: Uint128,
+}
+
+#[cw_serde]
+pub struct TransfersResponse {
+    pub transfers: Vec<TransferResponse>,
+}
+
+#[cw_serde]
+pub struct PendingRefundsResponse {
+    pub refunds: Vec<PendingRefund>,
+}
+
+#[cw_serde]
+pub struct PendingRefund {
+    pub id: u64,
+    pub recipient: String,
+    /// the tokens to send back, for vouchers this is the voucher contract
+    pub amount: Amount,
+    /// vouchers are minted again instead of sent
+    pub mint: bool,
+}
@@ -1,8 +1,10 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, IbcEndpoint, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Decimal, Empty, IbcEndpoint, StdResult, Storage, Timestamp, Uint128};
 use cw_controllers::Admin;
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Deque, Item, Map};
 
+use crate::amount::Amount;
+use crate::ibc::ICS20_VERSION;
 use crate::ContractError;
 
 pub const ADMIN: Admin = Admin::new("admin");
@@ -18,9 +20,81 @@ pub const CHANNEL_INFO: Map<&str, ChannelInfo> = Map::new("channel_info");
 /// indexed by (channel_id, denom) maintaining the balance of the channel in that currency
 pub const CHANNEL_STATE: Map<(&str, &str), ChannelState> = Map::new("channel_state");
 
+/// channels on which all traffic is stopped
+pub const PAUSED_CHANNELS: Map<&str, Empty> = Map::new("paused_channels");
+
+/// denoms that cannot be sent or received on any channel. These are local denoms like
+/// `ucosm` or `cw20:<address>`, or the full path of a voucher
+pub const PAUSED_DENOMS: Map<&str, Empty> = Map::new("paused_denoms");
+
+/// indexed by (channel_id, denom) limiting the tokens that can flow through the channel per time window
+pub const RATE_LIMITS: Map<(&str, &str), RateLimit> = Map::new("rate_limits");
+
+/// fees on transfers of a local denom like `ucosm` or `cw20:<address>`
+pub const FEES: Map<&str, DenomFee> = Map::new("fees");
+
+/// protocol fees collected per local denom, until the admin withdraws them
+pub const ACCRUED_FEES: Map<&str, Uint128> = Map::new("accrued_fees");
+
 /// Every cw20 contract we allow to be sent is stored here, possibly with a gas_limit
 pub const ALLOW_LIST: Map<&Addr, AllowInfo> = Map::new("allow_list");
 
+/// Every native denom we allow to be sent, if the config restricts native tokens
+pub const NATIVE_ALLOW_LIST: Map<&str, Empty> = Map::new("native_allow_list");
+
+/// minimum and maximum amount of one transfer, by local denom like `ucosm` or `cw20:<address>`
+pub const TRANSFER_BOUNDS: Map<&str, TransferBounds> = Map::new("transfer_bounds");
+
+/// Every contract that wasm hooks in the memo of incoming packets may call
+pub const HOOK_ALLOW_LIST: Map<&Addr, Empty> = Map::new("hook_allow_list");
+
+/// cw20 voucher contract for every foreign denom we received, indexed by the full denom path
+pub const VOUCHERS: Map<&str, Addr> = Map::new("vouchers");
+
+/// denom trace of every voucher contract we instantiated
+pub const DENOM_TRACES: Map<&Addr, DenomTrace> = Map::new("denom_traces");
+
+// Used to pass the denom traces of new vouchers to the reply handler of their instantiation.
+// A packet with several tokens may create several vouchers, their replies come in the same order
+pub const PENDING_VOUCHERS: Deque<DenomTrace> = Deque::new("pending_vouchers");
+
+/// received packets that were forwarded over another channel, indexed by (channel_id, sequence)
+/// of the forwarded packet, so a failure can be refunded to the chain the tokens came from
+pub const FORWARDS: Map<(&str, u64), ForwardInfo> = Map::new("forwards");
+
+// Used to pass a forward to the reply handler, which learns the sequence of the forwarded packet.
+// The first element is the channel it is sent on
+pub const PENDING_FORWARD: Item<(String, ForwardInfo)> = Item::new("pending_forward");
+
+// Used to pass a forward of foreign tokens to the reply handler, which sends it once their
+// vouchers are minted to this contract
+pub const PENDING_VOUCHER_FORWARD: Item<VoucherForward> = Item::new("pending_voucher_forward");
+
+/// outgoing transfers, indexed by (channel_id, sequence) of their packet
+pub const TRANSFERS: Map<(&str, u64), TransferInfo> = Map::new("transfers");
+
+/// index of TRANSFERS by sender, (sender, channel_id, sequence)
+pub const TRANSFERS_BY_SENDER: Map<(&Addr, &str, u64), Empty> = Map::new("transfers_by_sender");
+
+// Used to pass transfers to the reply handler, which learns the sequence of their packets.
+// A batch sends several packets, their replies come in the same order.
+// The first element is the channel it is sent on
+pub const PENDING_TRANSFERS: Deque<(String, TransferInfo)> = Deque::new("pending_transfers");
+
+/// refunds that failed on ack or timeout, so they can be claimed later
+pub const PENDING_REFUNDS: Map<u64, Refund> = Map::new("pending_refunds");
+
+/// index of PENDING_REFUNDS by recipient, (recipient, id)
+pub const REFUNDS_BY_RECIPIENT: Map<(&str, u64), Empty> = Map::new("refunds_by_recipient");
+
+/// the id of the last pending refund
+pub const REFUND_COUNT: Item<u64> = Item::new("refund_count");
+
+// Used to pass refunds to the reply handler, which records them if they fail. Refunds that were
+// pending already come with their id, so they are kept under the same one.
+// A packet with several tokens is refunded in several messages, their replies come in the same order
+pub const REFUND_ARGS: Deque<(Option<u64>, Refund)> = Deque::new("refund_args");
+
 #[cw_serde]
 #[derive(Default)]
 pub struct ChannelState {
@@ -28,10 +102,130 @@ pub struct ChannelState {
     pub total_sent: Uint128,
 }
 
+#[cw_serde]
+pub enum Quota {
+    /// maximum amount of tokens per window
+    Amount(Uint128),
+    /// maximum share of the outstanding channel balance (at the start of the window) per window.
+    /// Only allowed as an outflow quota once the channel has an outstanding balance.
+    Percent(Decimal),
+}
+
+impl Quota {
+    fn cap(&self, outstanding: Uint128) -> Uint128 {
+        match self {
+            Quota::Amount(amount) => *amount,
+            Quota::Percent(percent) => outstanding * *percent,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// tokens redeemed by incoming packets
+    In,
+    /// tokens escrowed by outgoing transfers
+    Out,
+}
+
+#[cw_serde]
+pub struct RateLimit {
+    /// length of a window in seconds
+    pub window: u64,
+    pub max_inflow: Option<Quota>,
+    pub max_outflow: Option<Quota>,
+    /// usage of the current window
+    pub window_start: Timestamp,
+    /// the outstanding channel balance at the start of the window, base for percentage quotas
+    pub window_outstanding: Uint128,
+    pub inflow: Uint128,
+    pub outflow: Uint128,
+}
+
+impl RateLimit {
+    pub fn new(
+        window: u64,
+        max_inflow: Option<Quota>,
+        max_outflow: Option<Quota>,
+        now: Timestamp,
+        outstanding: Uint128,
+    ) -> Self {
+        RateLimit {
+            window,
+            max_inflow,
+            max_outflow,
+            window_start: now,
+            window_outstanding: outstanding,
+            inflow: Uint128::zero(),
+            outflow: Uint128::zero(),
+        }
+    }
+
+    /// a window too long to be represented never ends
+    pub fn window_end(&self) -> Timestamp {
+        let nanos = self.window.saturating_mul(1_000_000_000);
+        Timestamp::from_nanos(self.window_start.nanos().saturating_add(nanos))
+    }
+
+    /// starts a new window if the current one is over
+    pub fn refresh(&mut self, now: Timestamp, outstanding: Uint128) {
+        if now >= self.window_end() {
+            self.window_start = now;
+            // an emptied channel keeps the previous base, so a percentage outflow quota
+            // does not drop to zero and block all transfers
+            if !outstanding.is_zero() {
+                self.window_outstanding = outstanding;
+            }
+            self.inflow = Uint128::zero();
+            self.outflow = Uint128::zero();
+        }
+    }
+
+    /// maximum amount that can flow in the current window, None if unlimited
+    pub fn cap(&self, flow: Flow) -> Option<Uint128> {
+        let quota = match flow {
+            Flow::In => &self.max_inflow,
+            Flow::Out => &self.max_outflow,
+        };
+        quota.as_ref().map(|q| q.cap(self.window_outstanding))
+    }
+
+    /// adds the amount to the usage of the window, failing if this exceeds the quota
+    pub fn add(&mut self, flow: Flow, amount: Uint128) -> Option<()> {
+        let cap = self.cap(flow);
+        let used = match flow {
+            Flow::In => &mut self.inflow,
+            Flow::Out => &mut self.outflow,
+        };
+        let total = used.checked_add(amount).ok()?;
+        if matches!(cap, Some(cap) if total > cap) {
+            return None;
+        }
+        *used = total;
+        Some(())
+    }
+}
+
 #[cw_serde]
 pub struct Config {
     pub default_timeout: u64,
     pub default_gas_limit: Option<u64>,
+    /// cw20-base code id used to create vouchers for foreign tokens.
+    /// If unset, we only accept tokens that originate on this chain.
+    #[serde(default)]
+    pub voucher_code_id: Option<u64>,
+    /// If set, only native denoms on the NATIVE_ALLOW_LIST can be sent
+    #[serde(default)]
+    pub restrict_native: bool,
+    /// If set, received tokens with a forward memo are sent on to the next chain
+    #[serde(default)]
+    pub allow_forwarding: bool,
+}
+
+#[cw_serde]
+pub struct TransferBounds {
+    pub min: Option<Uint128>,
+    pub max: Option<Uint128>,
 }
 
 #[cw_serde]
@@ -42,6 +236,14 @@ pub struct ChannelInfo {
     pub counterparty_endpoint: IbcEndpoint,
     /// the connection this exists on (you can use to query client/consensus info)
     pub connection_id: String,
+    /// the negotiated ics20 version, which decides the format of the packets
+    #[serde(default = "default_version")]
+    pub version: String,
+}
+
+// channels opened before ics20-2 was supported
+fn default_version() -> String {
+    ICS20_VERSION.to_string()
 }
 
 #[cw_serde]
@@ -49,11 +251,166 @@ pub struct AllowInfo {
     pub gas_limit: Option<u64>,
 }
 
+#[cw_serde]
+pub struct DenomTrace {
+    /// the local channel the foreign tokens are received on
+    pub channel: String,
+    /// the full denom on this chain: `{port}/{channel}/{denom on the sending chain}`
+    pub path: String,
+}
+
+#[cw_serde]
+pub struct ForwardInfo {
+    /// the local channel the tokens were received on
+    pub channel: String,
+    /// the sender on the counterparty chain, who gets the refund
+    pub sender: String,
+    /// the receiver on this chain, the forwarded packet is sent on its behalf
+    pub receiver: String,
+    /// the local denom (eg. `ucosm` or `cw20:<address>`)
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+/// A forward of foreign tokens, waiting for their vouchers to be minted to this contract
+#[cw_serde]
+pub struct VoucherForward {
+    /// the local channel to send the vouchers on
+    pub channel: String,
+    /// the recipient on the next chain
+    pub receiver: String,
+    pub timeout: Timestamp,
+    /// where the tokens came from. Its denom is the full denom path of the voucher
+    pub info: ForwardInfo,
+}
+
+#[cw_serde]
+pub struct DenomFee {
+    /// protocol fee on outgoing transfers, kept by the contract
+    pub fee: Option<Fee>,
+    /// paid to the relayer that completes a transfer, out of the transferred amount
+    pub relayer_tip: Option<Uint128>,
+}
+
+#[cw_serde]
+pub enum Fee {
+    /// a fixed amount per transfer
+    Flat(Uint128),
+    /// basis points (1/10000) of the transferred amount
+    Bps(u16),
+}
+
+impl Fee {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match self {
+            Fee::Bps(bps) if *bps > 10_000 => Err(ContractError::InvalidFee {}),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn compute(&self, amount: Uint128) -> Uint128 {
+        match self {
+            Fee::Flat(fee) => *fee,
+            Fee::Bps(bps) => amount.multiply_ratio(*bps, 10_000u128),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct TransferInfo {
+    /// the sender on this chain
+    pub sender: Addr,
+    /// the receiver on the counterparty chain
+    pub receiver: String,
+    /// the denom as sent in the packet
+    pub denom: String,
+    pub amount: Uint128,
+    pub timeout: Timestamp,
+    pub status: TransferStatus,
+    /// the contract to notify once the packet is acknowledged or timed out
+    #[serde(default)]
+    pub callback: Option<Addr>,
+    /// held back from the transfer and paid to the relayer of the ack or timeout
+    #[serde(default)]
+    pub relayer_tip: Uint128,
+    /// the other tokens sent in the same ics20-2 packet
+    #[serde(default)]
+    pub batch: Vec<BatchedToken>,
+}
+
+#[cw_serde]
+pub struct BatchedToken {
+    pub denom: String,
+    pub amount: Uint128,
+    pub relayer_tip: Uint128,
+}
+
+#[cw_serde]
+pub enum TransferStatus {
+    /// the packet was sent, but not acknowledged yet
+    Pending,
+    /// the tokens arrived on the counterparty chain
+    Acked,
+    /// the counterparty chain rejected the packet, the tokens were refunded
+    Failed { error: String },
+    /// the packet timed out, the tokens were refunded
+    TimedOut,
+}
+
+/// Updates the status of a tracked transfer and returns it, packets not sent by `Transfer` are ignored
+pub fn set_transfer_status(
+    storage: &mut dyn Storage,
+    channel: &str,
+    sequence: u64,
+    status: TransferStatus,
+) -> StdResult<Option<TransferInfo>> {
+    let transfer = TRANSFERS.may_load(storage, (channel, sequence))?;
+    let transfer = transfer.map(|transfer| TransferInfo { status, ..transfer });
+    if let Some(transfer) = &transfer {
+        TRANSFERS.save(storage, (channel, sequence), transfer)?;
+    }
+    Ok(transfer)
+}
+
+#[cw_serde]
+pub struct Refund {
+    /// the sender of the failed transfer on this chain
+    pub recipient: String,
+    /// the tokens to send back, for vouchers this is the voucher contract
+    pub amount: Amount,
+    /// vouchers were burnt when sent, so they are minted again instead of sent
+    pub mint: bool,
+}
+
+pub fn add_pending_refund(storage: &mut dyn Storage, refund: &Refund) -> StdResult<u64> {
+    let id = REFUND_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    REFUND_COUNT.save(storage, &id)?;
+    save_pending_refund(storage, id, refund)?;
+    Ok(id)
+}
+
+pub fn save_pending_refund(storage: &mut dyn Storage, id: u64, refund: &Refund) -> StdResult<()> {
+    PENDING_REFUNDS.save(storage, id, refund)?;
+    REFUNDS_BY_RECIPIENT.save(storage, (&refund.recipient, id), &Empty {})
+}
+
+pub fn remove_pending_refund(storage: &mut dyn Storage, id: u64) -> Result<Refund, ContractError> {
+    let refund = PENDING_REFUNDS
+        .may_load(storage, id)?
+        .ok_or(ContractError::NoSuchRefund { id })?;
+    PENDING_REFUNDS.remove(storage, id);
+    REFUNDS_BY_RECIPIENT.remove(storage, (&refund.recipient, id));
+    Ok(refund)
+}
+
 #[cw_serde]
 pub struct ReplyArgs {
     pub channel: String,
     pub denom: String,
     pub amount: Uint128,
+    /// the relayer and its tip, paid once the tokens are released
+    #[serde(default)]
+    pub relayer_tip: Option<(Addr, Uint128)>,
 }
 
 pub fn increase_channel_balance(
@@ -108,3 +465,125 @@ pub fn undo_reduce_channel_balance(
     })?;
     Ok(())
 }
+
+/// Returns the rate limit on (channel, denom) with the amount added to its usage, or an error if
+/// this exceeds the quota. The result must be saved with `RATE_LIMITS` once the flow is accepted.
+pub fn check_rate_limit(
+    storage: &dyn Storage,
+    channel: &str,
+    denom: &str,
+    flow: Flow,
+    amount: Uint128,
+    now: Timestamp,
+) -> Result<Option<RateLimit>, ContractError> {
+    let mut limit = match RATE_LIMITS.may_load(storage, (channel, denom))? {
+        Some(limit) => limit,
+        None => return Ok(None),
+    };
+    let outstanding = CHANNEL_STATE
+        .may_load(storage, (channel, denom))?
+        .unwrap_or_default()
+        .outstanding;
+    limit.refresh(now, outstanding);
+    limit
+        .add(flow, amount)
+        .ok_or_else(|| ContractError::RateLimitExceeded {
+            channel: channel.to_string(),
+            denom: denom.to_string(),
+        })?;
+    Ok(Some(limit))
+}
+
+/// Frees up the quota used by a flow that was reverted (eg. a transfer that timed out)
+pub fn undo_rate_limit(
+    storage: &mut dyn Storage,
+    channel: &str,
+    denom: &str,
+    flow: Flow,
+    amount: Uint128,
+) -> StdResult<()> {
+    if let Some(mut limit) = RATE_LIMITS.may_load(storage, (channel, denom))? {
+        match flow {
+            Flow::In => limit.inflow = limit.inflow.saturating_sub(amount),
+            Flow::Out => limit.outflow = limit.outflow.saturating_sub(amount),
+        }
+        RATE_LIMITS.save(storage, (channel, denom), &limit)?;
+    }
+    Ok(())
+}
+
+/// Fails if the channel or the denom is paused
+pub fn assert_not_paused(
+    storage: &dyn Storage,
+    channel: &str,
+    denom: &str,
+) -> Result<(), ContractError> {
+    if PAUSED_CHANNELS.has(storage, channel) {
+        return Err(ContractError::ChannelPaused {
+            channel: channel.to_string(),
+        });
+    }
+    if PAUSED_DENOMS.has(storage, denom) {
+        return Err(ContractError::DenomPaused {
+            denom: denom.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the (protocol fee, relayer tip) to take from an outgoing transfer of `amount`.
+/// Fails if nothing would be left to send.
+pub fn transfer_fees(
+    storage: &dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<(Uint128, Uint128), ContractError> {
+    let config = FEES.may_load(storage, denom)?.unwrap_or(DenomFee {
+        fee: None,
+        relayer_tip: None,
+    });
+    let fee = config
+        .fee
+        .map(|fee| fee.compute(amount))
+        .unwrap_or_default();
+    let tip = config.relayer_tip.unwrap_or_default();
+    match fee.checked_add(tip) {
+        Ok(total) if total < amount => {}
+        _ => return Err(ContractError::FeeExceedsAmount {}),
+    }
+    Ok((fee, tip))
+}
+
+/// Returns the relayer tip to take from an incoming transfer of `amount`.
+/// Fails if nothing would be left to release.
+pub fn receive_tip(
+    storage: &dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let tip = FEES
+        .may_load(storage, denom)?
+        .and_then(|config| config.relayer_tip)
+        .unwrap_or_default();
+    if !tip.is_zero() && tip >= amount {
+        return Err(ContractError::FeeExceedsAmount {});
+    }
+    Ok(tip)
+}
+
+/// Fails if the amount is out of the bounds set for the denom
+pub fn assert_transfer_bounds(
+    storage: &dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let bounds = match TRANSFER_BOUNDS.may_load(storage, denom)? {
+        Some(bounds) => bounds,
+        None => return Ok(()),
+    };
+    match (bounds.min, bounds.max) {
+        (Some(min), _) if amount < min => Err(ContractError::AmountTooSmall { min }),
+        (_, Some(max)) if amount > max => Err(ContractError::AmountTooLarge { max }),
+        _ => Ok(()),
+    }
+}
@@ -8,7 +8,7 @@ use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
 use cosmwasm_std::{
-    DepsMut, IbcChannel, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcEndpoint, OwnedDeps,
+    Addr, DepsMut, IbcChannel, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcEndpoint, OwnedDeps,
 };
 
 use crate::msg::{AllowMsg, InitMsg};
@@ -17,6 +17,7 @@ pub const DEFAULT_TIMEOUT: u64 = 3600; // 1 hour,
 pub const CONTRACT_PORT: &str = "ibc:wasm1234567890abcdef";
 pub const REMOTE_PORT: &str = "transfer";
 pub const CONNECTION_ID: &str = "connection-2";
+pub const RELAYER: &str = "relayer";
 
 pub fn mock_channel(channel_id: &str) -> IbcChannel {
     IbcChannel::new(
@@ -34,6 +35,10 @@ pub fn mock_channel(channel_id: &str) -> IbcChannel {
     )
 }
 
+pub fn relayer() -> Addr {
+    Addr::unchecked(RELAYER)
+}
+
 pub fn mock_channel_info(channel_id: &str) -> ChannelInfo {
     ChannelInfo {
         id: channel_id.to_string(),
@@ -42,6 +47,7 @@ pub fn mock_channel_info(channel_id: &str) -> ChannelInfo {
             channel_id: format!("{}5", channel_id),
         },
         connection_id: CONNECTION_ID.into(),
+        version: ICS20_VERSION.into(),
     }
 }
 
@@ -72,8 +78,11 @@ pub fn setup(
     let instantiate_msg = InitMsg {
         default_gas_limit: None,
         default_timeout: DEFAULT_TIMEOUT,
+        voucher_code_id: None,
         gov_contract: "gov".to_string(),
         allowlist,
+        native_allowlist: None,
+        allow_forwarding: false,
     };
     let info = mock_info(&String::from("anyone"), &[]);
     let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
@@ -0,0 +1,2406 @@
+#![cfg(test)]
+//! An in-process relayer connecting two simulated chains that both run this contract.
+//!
+//! Every chain keeps its own contract storage, a tiny bank / cw20 ledger and an outbox of
+//! packets emitted via `IbcMsg::SendPacket`. The `Relayer` delivers those packets to
+//! `ibc_packet_receive` on the other side, executes the resulting submessages (calling
+//! `reply` like wasmd does), and feeds the acknowledgement back to `ibc_packet_ack`.
+//! Voucher contracts for foreign tokens are simulated by the cw20 ledger as well.
+//! Packets whose timeout has passed on the destination chain are handed to
+//! `ibc_packet_timeout` instead, so tests trigger timeouts by advancing the block time.
+
+use std::collections::BTreeMap;
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Env,
+    IbcAcknowledgement, IbcChannel, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcEndpoint, IbcMsg,
+    IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, Order, OwnedDeps,
+    ReplyOn, Response, Storage, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use serde::de::DeserializeOwned;
+
+use crate::contract::{execute, instantiate, query};
+use crate::ibc::{
+    ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive, ibc_packet_timeout,
+    reply, Ics20Ack, ICS20_ORDERING, ICS20_VERSION,
+};
+use crate::msg::{ExecuteMsg, InitMsg, QueryMsg, TransferMsg};
+use crate::test_helpers::{CONNECTION_ID, DEFAULT_TIMEOUT};
+
+pub const GOV: &str = "gov";
+pub const VOUCHER_CODE_ID: u64 = 7;
+/// the address that relays all packets
+pub const RELAYER: &str = "relayer";
+const BLOCK_TIME: u64 = 5;
+// burnt cw20 tokens are moved here
+const BURNT: &str = "burnt";
+
+/// Which of the two chains managed by the `Relayer`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// What happened to a packet when it was relayed
+#[derive(Clone, Debug, PartialEq)]
+pub enum Relayed {
+    Ack(Ics20Ack),
+    Timeout,
+}
+
+/// A call made to a hook contract registered with `Chain::add_hook_contract`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookCall {
+    pub contract: String,
+    /// the executed message, for cw20 tokens this is the `Cw20ReceiveMsg`
+    pub msg: Binary,
+    pub funds: Vec<Coin>,
+}
+
+pub fn transfer_msg(channel: &str, remote_address: &str) -> TransferMsg {
+    TransferMsg {
+        channel: channel.to_string(),
+        remote_address: remote_address.to_string(),
+        timeout: None,
+        memo: None,
+        callback: None,
+    }
+}
+
+/// Native and cw20 balances of every account on one chain
+#[derive(Clone, Debug, Default)]
+struct Ledger {
+    /// indexed by (address, denom)
+    native: BTreeMap<(String, String), u128>,
+    /// indexed by (token, address)
+    cw20: BTreeMap<(String, String), u128>,
+    /// minter of every cw20 voucher instantiated on this chain
+    minters: BTreeMap<String, String>,
+}
+
+fn move_funds(
+    balances: &mut BTreeMap<(String, String), u128>,
+    from: (String, String),
+    to: (String, String),
+    amount: u128,
+) -> Result<(), String> {
+    let available = balances.get(&from).copied().unwrap_or_default();
+    let remaining = available
+        .checked_sub(amount)
+        .ok_or_else(|| format!("insufficient funds: {} < {}", available, amount))?;
+    balances.insert(from, remaining);
+    *balances.entry(to).or_default() += amount;
+    Ok(())
+}
+
+impl Ledger {
+    fn send_native(&mut self, from: &str, to: &str, coin: &Coin) -> Result<(), String> {
+        move_funds(
+            &mut self.native,
+            (from.to_string(), coin.denom.clone()),
+            (to.to_string(), coin.denom.clone()),
+            coin.amount.u128(),
+        )
+    }
+
+    fn send_cw20(&mut self, token: &str, from: &str, to: &str, amount: u128) -> Result<(), String> {
+        move_funds(
+            &mut self.cw20,
+            (token.to_string(), from.to_string()),
+            (token.to_string(), to.to_string()),
+            amount,
+        )
+    }
+}
+
+/// protobuf encoded `MsgInstantiateContractResponse`, like wasmd returns it to the reply
+fn encode_instantiate_response(address: &str) -> Binary {
+    let mut data = vec![0x0a, address.len() as u8];
+    data.extend_from_slice(address.as_bytes());
+    data.into()
+}
+
+/// protobuf encoded `MsgIBCSendResponse`, like wasmd returns it to the reply
+fn encode_send_response(mut sequence: u64) -> Binary {
+    let mut data = vec![0x08];
+    while sequence >= 0x80 {
+        data.push((sequence as u8) | 0x80);
+        sequence >>= 7;
+    }
+    data.push(sequence as u8);
+    data.into()
+}
+
+/// Everything we need to roll back a failed transaction
+struct Snapshot {
+    storage: Vec<(Vec<u8>, Vec<u8>)>,
+    ledger: Ledger,
+    outbox: usize,
+    hook_calls: usize,
+}
+
+pub struct Chain {
+    pub deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    pub env: Env,
+    pub port: String,
+    channels: BTreeMap<String, IbcChannel>,
+    ledger: Ledger,
+    outbox: Vec<IbcPacket>,
+    /// contracts that accept (true) or reject (false) all calls
+    hook_contracts: BTreeMap<String, bool>,
+    pub hook_calls: Vec<HookCall>,
+    next_channel: u64,
+    next_sequence: u64,
+}
+
+impl Chain {
+    fn new(chain_id: &str) -> Self {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.chain_id = chain_id.to_string();
+
+        let msg = InitMsg {
+            default_gas_limit: None,
+            default_timeout: DEFAULT_TIMEOUT,
+            gov_contract: GOV.to_string(),
+            allowlist: vec![],
+            voucher_code_id: Some(VOUCHER_CODE_ID),
+            native_allowlist: None,
+            allow_forwarding: true,
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        Chain {
+            deps,
+            env,
+            port: format!("wasm.{}", chain_id),
+            channels: BTreeMap::new(),
+            ledger: Ledger::default(),
+            outbox: vec![],
+            hook_contracts: BTreeMap::new(),
+            hook_calls: vec![],
+            next_channel: 0,
+            next_sequence: 1,
+        }
+    }
+
+    pub fn contract(&self) -> Addr {
+        self.env.contract.address.clone()
+    }
+
+    pub fn set_balance(&mut self, addr: &str, coin: Coin) {
+        self.ledger
+            .native
+            .insert((addr.to_string(), coin.denom), coin.amount.u128());
+    }
+
+    pub fn balance(&self, addr: &str, denom: &str) -> u128 {
+        self.ledger
+            .native
+            .get(&(addr.to_string(), denom.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_cw20_balance(&mut self, token: &str, addr: &str, amount: u128) {
+        self.ledger
+            .cw20
+            .insert((token.to_string(), addr.to_string()), amount);
+    }
+
+    pub fn cw20_balance(&self, token: &str, addr: &str) -> u128 {
+        self.ledger
+            .cw20
+            .get(&(token.to_string(), addr.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Packets sent by this chain that have not been relayed yet
+    pub fn pending_packets(&self) -> &[IbcPacket] {
+        &self.outbox
+    }
+
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.env.block.height += blocks;
+        self.env.block.time = self.env.block.time.plus_seconds(blocks * BLOCK_TIME);
+    }
+
+    pub fn query<T: DeserializeOwned>(&self, msg: QueryMsg) -> T {
+        from_binary(&query(self.deps.as_ref(), self.env.clone(), msg).unwrap()).unwrap()
+    }
+
+    /// Executes a message on the contract, moving the attached funds from sender
+    /// to the contract. All changes are reverted on error.
+    pub fn execute(
+        &mut self,
+        sender: &str,
+        funds: &[Coin],
+        msg: ExecuteMsg,
+    ) -> Result<Response, String> {
+        let snapshot = self.snapshot();
+        let res = self.do_execute(sender, funds, msg);
+        if res.is_err() {
+            self.restore(snapshot);
+        }
+        res
+    }
+
+    fn do_execute(
+        &mut self,
+        sender: &str,
+        funds: &[Coin],
+        msg: ExecuteMsg,
+    ) -> Result<Response, String> {
+        let contract = self.contract();
+        for coin in funds {
+            self.ledger.send_native(sender, contract.as_str(), coin)?;
+        }
+        let res = execute(
+            self.deps.as_mut(),
+            self.env.clone(),
+            mock_info(sender, funds),
+            msg,
+        )
+        .map_err(|err| err.to_string())?;
+        self.dispatch(res.messages.clone())?;
+        Ok(res)
+    }
+
+    /// Sends native tokens over the given channel
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        coin: Coin,
+        channel: &str,
+        remote_address: &str,
+    ) -> Result<Response, String> {
+        self.transfer_with(sender, coin, transfer_msg(channel, remote_address))
+    }
+
+    pub fn transfer_with(
+        &mut self,
+        sender: &str,
+        coin: Coin,
+        transfer: TransferMsg,
+    ) -> Result<Response, String> {
+        self.execute(sender, &[coin], ExecuteMsg::Transfer(transfer))
+    }
+
+    /// Simulates a `Cw20ExecuteMsg::Send` from sender to the contract, which moves the tokens
+    /// and then triggers the `Receive` hook carrying a `TransferMsg`
+    pub fn send_cw20(
+        &mut self,
+        token: &str,
+        sender: &str,
+        amount: u128,
+        channel: &str,
+        remote_address: &str,
+    ) -> Result<Response, String> {
+        self.send_cw20_with(token, sender, amount, transfer_msg(channel, remote_address))
+    }
+
+    pub fn send_cw20_with(
+        &mut self,
+        token: &str,
+        sender: &str,
+        amount: u128,
+        transfer: TransferMsg,
+    ) -> Result<Response, String> {
+        let snapshot = self.snapshot();
+        let contract = self.contract();
+        let res = self
+            .ledger
+            .send_cw20(token, sender, contract.as_str(), amount)
+            .and_then(|_| {
+                let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: sender.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&transfer).unwrap(),
+                });
+                self.do_execute(token, &[], msg)
+            });
+        if res.is_err() {
+            self.restore(snapshot);
+        }
+        res
+    }
+
+    /// Registers a contract that accepts (or rejects) every call made to it.
+    /// Accepted calls are recorded in `hook_calls`.
+    pub fn add_hook_contract(&mut self, contract: &str, accept: bool) {
+        self.hook_contracts.insert(contract.to_string(), accept);
+    }
+
+    fn call_hook(&mut self, contract: &str, msg: Binary, funds: Vec<Coin>) -> Result<(), String> {
+        if self.hook_contracts.get(contract) != Some(&true) {
+            return Err(format!("call to {} failed", contract));
+        }
+        self.hook_calls.push(HookCall {
+            contract: contract.to_string(),
+            msg,
+            funds,
+        });
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            storage: self
+                .deps
+                .storage
+                .range(None, None, Order::Ascending)
+                .collect(),
+            ledger: self.ledger.clone(),
+            outbox: self.outbox.len(),
+            hook_calls: self.hook_calls.len(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        let keys: Vec<Vec<u8>> = self
+            .deps
+            .storage
+            .range(None, None, Order::Ascending)
+            .map(|(k, _)| k)
+            .collect();
+        for key in keys {
+            self.deps.storage.remove(&key);
+        }
+        for (key, value) in snapshot.storage {
+            self.deps.storage.set(&key, &value);
+        }
+        self.ledger = snapshot.ledger;
+        self.outbox.truncate(snapshot.outbox);
+        self.hook_calls.truncate(snapshot.hook_calls);
+    }
+
+    /// Executes the messages returned by the contract. If a reply overwrites the data,
+    /// the last value is returned (this is how the ack can be replaced on receive).
+    fn dispatch(&mut self, messages: Vec<SubMsg>) -> Result<Option<Binary>, String> {
+        let mut data = None;
+        for msg in messages {
+            let ledger = self.ledger.clone();
+            let result = self.execute_cosmos_msg(msg.msg);
+            let reply_result = match (result, msg.reply_on) {
+                (Ok(data), ReplyOn::Always | ReplyOn::Success) => {
+                    SubMsgResult::Ok(SubMsgResponse {
+                        events: vec![],
+                        data,
+                    })
+                }
+                (Ok(_), _) => continue,
+                (Err(err), ReplyOn::Always | ReplyOn::Error) => {
+                    self.ledger = ledger;
+                    SubMsgResult::Err(err)
+                }
+                (Err(err), _) => return Err(err),
+            };
+            let res = reply(
+                self.deps.as_mut(),
+                self.env.clone(),
+                cosmwasm_std::Reply {
+                    id: msg.id,
+                    result: reply_result,
+                },
+            )
+            .map_err(|err| err.to_string())?;
+            if let Some(new_data) = self.dispatch(res.messages)? {
+                data = Some(new_data);
+            }
+            if let Some(new_data) = res.data {
+                data = Some(new_data);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Executes one message, returning the data the message sets (if any)
+    fn execute_cosmos_msg(&mut self, msg: CosmosMsg) -> Result<Option<Binary>, String> {
+        let contract = self.contract();
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                for coin in &amount {
+                    self.ledger
+                        .send_native(contract.as_str(), &to_address, coin)?;
+                }
+                Ok(None)
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) if contract_addr == contract.as_str() => {
+                // a call of the contract to itself, all its changes are reverted when it fails
+                let snapshot = self.snapshot();
+                let msg = from_slice(&msg).map_err(|err| err.to_string())?;
+                let res = execute(
+                    self.deps.as_mut(),
+                    self.env.clone(),
+                    mock_info(contract.as_str(), &funds),
+                    msg,
+                )
+                .map_err(|err| err.to_string())
+                .and_then(|res| Ok(self.dispatch(res.messages)?.or(res.data)));
+                if res.is_err() {
+                    self.restore(snapshot);
+                }
+                res
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) if self.hook_contracts.contains_key(&contract_addr) => {
+                for coin in &funds {
+                    self.ledger
+                        .send_native(contract.as_str(), &contract_addr, coin)?;
+                }
+                self.call_hook(&contract_addr, msg, funds)?;
+                Ok(None)
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                let token = contract_addr.as_str();
+                match from_slice(&msg).map_err(|err| err.to_string())? {
+                    Cw20ExecuteMsg::Send {
+                        contract: target,
+                        amount,
+                        msg,
+                    } => {
+                        self.ledger
+                            .send_cw20(token, contract.as_str(), &target, amount.u128())?;
+                        let receive = Cw20ReceiveMsg {
+                            sender: contract.to_string(),
+                            amount,
+                            msg,
+                        };
+                        self.call_hook(&target, to_binary(&receive).unwrap(), vec![])?;
+                    }
+                    Cw20ExecuteMsg::Transfer { recipient, amount } => self.ledger.send_cw20(
+                        token,
+                        contract.as_str(),
+                        &recipient,
+                        amount.u128(),
+                    )?,
+                    Cw20ExecuteMsg::Burn { amount } => {
+                        self.ledger
+                            .send_cw20(token, contract.as_str(), BURNT, amount.u128())?
+                    }
+                    Cw20ExecuteMsg::Mint { recipient, amount } => {
+                        if self.ledger.minters.get(token) != Some(&contract.to_string()) {
+                            return Err("Unauthorized".to_string());
+                        }
+                        *self
+                            .ledger
+                            .cw20
+                            .entry((token.to_string(), recipient))
+                            .or_default() += amount.u128();
+                    }
+                    other => return Err(format!("unsupported cw20 message: {:?}", other)),
+                }
+                Ok(None)
+            }
+            CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, msg, .. }) => {
+                // the only contract we know how to run is a cw20 voucher
+                if code_id != VOUCHER_CODE_ID {
+                    return Err(format!("unknown code id: {}", code_id));
+                }
+                let init: Cw20InstantiateMsg = from_slice(&msg).map_err(|err| err.to_string())?;
+                init.validate().map_err(|err| err.to_string())?;
+                let address = format!("voucher{}", self.ledger.minters.len());
+                for coin in init.initial_balances {
+                    self.ledger
+                        .cw20
+                        .insert((address.clone(), coin.address), coin.amount.u128());
+                }
+                let minter = init.mint.map(|m| m.minter).unwrap_or_default();
+                self.ledger.minters.insert(address.clone(), minter);
+                Ok(Some(encode_instantiate_response(&address)))
+            }
+            CosmosMsg::Ibc(IbcMsg::SendPacket {
+                channel_id,
+                data,
+                timeout,
+            }) => {
+                let channel = self
+                    .channels
+                    .get(&channel_id)
+                    .ok_or_else(|| format!("unknown channel: {}", channel_id))?;
+                let packet = IbcPacket::new(
+                    data,
+                    channel.endpoint.clone(),
+                    channel.counterparty_endpoint.clone(),
+                    self.next_sequence,
+                    timeout,
+                );
+                self.next_sequence += 1;
+                self.outbox.push(packet);
+                Ok(Some(encode_send_response(self.next_sequence - 1)))
+            }
+            other => Err(format!("unsupported message: {:?}", other)),
+        }
+    }
+
+    fn is_timed_out(&self, packet: &IbcPacket) -> bool {
+        let by_time =
+            matches!(packet.timeout.timestamp(), Some(time) if time <= self.env.block.time);
+        let by_height =
+            matches!(packet.timeout.block(), Some(block) if block.height <= self.env.block.height);
+        by_time || by_height
+    }
+
+    /// Runs `ibc_packet_receive` and executes the resulting messages, returning the
+    /// final acknowledgement
+    fn receive_packet(&mut self, packet: IbcPacket) -> Result<Binary, String> {
+        let snapshot = self.snapshot();
+        let res = ibc_packet_receive(
+            self.deps.as_mut(),
+            self.env.clone(),
+            IbcPacketReceiveMsg::new(packet, Addr::unchecked(RELAYER)),
+        )
+        .unwrap();
+        match self.dispatch(res.messages) {
+            Ok(data) => Ok(data.unwrap_or(res.acknowledgement)),
+            Err(err) => {
+                self.restore(snapshot);
+                Err(err)
+            }
+        }
+    }
+
+    fn acknowledge_packet(&mut self, packet: IbcPacket, ack: Binary) -> Result<(), String> {
+        let snapshot = self.snapshot();
+        let msg = IbcPacketAckMsg::new(
+            IbcAcknowledgement::new(ack),
+            packet,
+            Addr::unchecked(RELAYER),
+        );
+        let res = ibc_packet_ack(self.deps.as_mut(), self.env.clone(), msg)
+            .map_err(|err| err.to_string())
+            .and_then(|res| self.dispatch(res.messages));
+        if res.is_err() {
+            self.restore(snapshot);
+        }
+        res.map(|_| ())
+    }
+
+    fn timeout_packet(&mut self, packet: IbcPacket) -> Result<(), String> {
+        let snapshot = self.snapshot();
+        let msg = IbcPacketTimeoutMsg::new(packet, Addr::unchecked(RELAYER));
+        let res = ibc_packet_timeout(self.deps.as_mut(), self.env.clone(), msg)
+            .map_err(|err| err.to_string())
+            .and_then(|res| self.dispatch(res.messages));
+        if res.is_err() {
+            self.restore(snapshot);
+        }
+        res.map(|_| ())
+    }
+
+    fn next_channel_id(&mut self) -> String {
+        let id = format!("channel-{}", self.next_channel);
+        self.next_channel += 1;
+        id
+    }
+}
+
+pub struct Relayer {
+    pub a: Chain,
+    pub b: Chain,
+}
+
+impl Relayer {
+    pub fn new() -> Self {
+        let mut b = Chain::new("chain-b");
+        // make sure the channel ids differ on both sides to catch any mix-ups
+        b.next_channel = 7;
+        Relayer {
+            a: Chain::new("chain-a"),
+            b,
+        }
+    }
+
+    /// Returns (source, destination) for packets sent from the given side
+    fn route(&mut self, from: Side) -> (&mut Chain, &mut Chain) {
+        match from {
+            Side::A => (&mut self.a, &mut self.b),
+            Side::B => (&mut self.b, &mut self.a),
+        }
+    }
+
+    /// Runs the full channel handshake (init on A, try on B, ack on A, confirm on B)
+    /// and returns the channel ids on (A, B)
+    pub fn open_channel(&mut self) -> (String, String) {
+        self.open_channel_with_version(ICS20_VERSION)
+    }
+
+    /// Like `open_channel`, with A proposing the given version
+    pub fn open_channel_with_version(&mut self, version: &str) -> (String, String) {
+        let a_endpoint = IbcEndpoint {
+            port_id: self.a.port.clone(),
+            channel_id: self.a.next_channel_id(),
+        };
+        let b_endpoint = IbcEndpoint {
+            port_id: self.b.port.clone(),
+            channel_id: self.b.next_channel_id(),
+        };
+        let a_channel = IbcChannel::new(
+            a_endpoint.clone(),
+            b_endpoint.clone(),
+            ICS20_ORDERING,
+            version,
+            CONNECTION_ID,
+        );
+        let mut b_channel = IbcChannel::new(
+            b_endpoint.clone(),
+            a_endpoint.clone(),
+            ICS20_ORDERING,
+            version,
+            CONNECTION_ID,
+        );
+
+        let (a, b) = (&mut self.a, &mut self.b);
+        ibc_channel_open(
+            a.deps.as_mut(),
+            a.env.clone(),
+            IbcChannelOpenMsg::new_init(a_channel.clone()),
+        )
+        .unwrap();
+        // B may answer with another version than it was asked to open with
+        let res = ibc_channel_open(
+            b.deps.as_mut(),
+            b.env.clone(),
+            IbcChannelOpenMsg::new_try(b_channel.clone(), version),
+        )
+        .unwrap();
+        if let Some(res) = res {
+            b_channel.version = res.version;
+        }
+        ibc_channel_connect(
+            a.deps.as_mut(),
+            a.env.clone(),
+            IbcChannelConnectMsg::new_ack(a_channel.clone(), &b_channel.version),
+        )
+        .unwrap();
+        ibc_channel_connect(
+            b.deps.as_mut(),
+            b.env.clone(),
+            IbcChannelConnectMsg::new_confirm(b_channel.clone()),
+        )
+        .unwrap();
+
+        a.channels.insert(a_endpoint.channel_id.clone(), a_channel);
+        b.channels.insert(b_endpoint.channel_id.clone(), b_channel);
+        (a_endpoint.channel_id, b_endpoint.channel_id)
+    }
+
+    /// Relays all pending packets sent from one side, in order. Packets that timed out
+    /// on the destination are timed out on the source, all others are received on the
+    /// destination and the ack is relayed back.
+    pub fn relay(&mut self, from: Side) -> Result<Vec<Relayed>, String> {
+        let (src, dest) = self.route(from);
+        let packets: Vec<IbcPacket> = src.outbox.drain(..).collect();
+        let mut relayed = vec![];
+        for packet in packets {
+            if dest.is_timed_out(&packet) {
+                src.timeout_packet(packet)?;
+                relayed.push(Relayed::Timeout);
+            } else {
+                let ack = dest.receive_packet(packet.clone())?;
+                src.acknowledge_packet(packet, ack.clone())?;
+                relayed.push(Relayed::Ack(from_binary(&ack).unwrap()));
+            }
+        }
+        Ok(relayed)
+    }
+
+    /// Relays packets in both directions until no chain has any pending packets left
+    pub fn relay_all(&mut self) -> Result<Vec<Relayed>, String> {
+        let mut relayed = vec![];
+        while !self.a.outbox.is_empty() || !self.b.outbox.is_empty() {
+            relayed.extend(self.relay(Side::A)?);
+            relayed.extend(self.relay(Side::B)?);
+        }
+        Ok(relayed)
+    }
+}
+
+mod test {
+    use super::*;
+
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{coin, Decimal, StdResult};
+
+    use crate::amount::Amount;
+    use crate::error::ContractError;
+    use crate::ibc::{
+        encode_packets, Denom, Hop, Ics20Packet, Ics20PacketV2, Token, ICS20_VERSION_2,
+    };
+    use crate::msg::{AllowMsg, ChannelResponse, DenomTraceResponse, ListDenomTracesResponse};
+    use crate::msg::{
+        Ics20CallbackMsg, PendingRefund, PendingRefundsResponse, TransferResponse,
+        TransfersResponse,
+    };
+    use crate::state::{
+        BatchedToken, Fee, Quota, TransferStatus, CONFIG, FORWARDS, PENDING_VOUCHER_FORWARD,
+    };
+
+    const SENDER: &str = "sender";
+    const RECEIVER: &str = "receiver";
+    const TOKEN: &str = "token";
+
+    fn channel_state(chain: &Chain, channel: &str) -> ChannelResponse {
+        chain.query(QueryMsg::Channel {
+            id: channel.to_string(),
+        })
+    }
+
+    fn denom_traces(chain: &Chain) -> Vec<DenomTraceResponse> {
+        let res: ListDenomTracesResponse = chain.query(QueryMsg::ListDenomTraces {
+            start_after: None,
+            limit: None,
+        });
+        res.traces
+    }
+
+    fn allow_token(chain: &mut Chain) {
+        let allow = ExecuteMsg::Allow(AllowMsg {
+            contract: TOKEN.to_string(),
+            gas_limit: None,
+        });
+        chain.execute(GOV, &[], allow).unwrap();
+    }
+
+    #[test]
+    fn handshake_registers_counterparties() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        assert_eq!(a_channel, "channel-0");
+        assert_eq!(b_channel, "channel-7");
+
+        let a_info = channel_state(&relayer.a, &a_channel).info;
+        assert_eq!(a_info.counterparty_endpoint.port_id, relayer.b.port);
+        assert_eq!(a_info.counterparty_endpoint.channel_id, b_channel);
+        let b_info = channel_state(&relayer.b, &b_channel).info;
+        assert_eq!(b_info.counterparty_endpoint.port_id, relayer.a.port);
+        assert_eq!(b_info.counterparty_endpoint.channel_id, a_channel);
+    }
+
+    #[test]
+    fn native_round_trip() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+
+        relayer
+            .a
+            .transfer(SENDER, coin(600, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 400);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 600);
+        let pending = relayer.a.pending_packets();
+        assert_eq!(pending.len(), 1);
+        let packet: Ics20Packet = from_binary(&pending[0].data).unwrap();
+        assert_eq!(
+            packet,
+            Ics20Packet::new(Uint128::new(600), "ucosm", SENDER, RECEIVER)
+        );
+
+        // the remote contract creates a voucher for the foreign token
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        let path = format!("{}/{}/ucosm", relayer.b.port, b_channel);
+        let traces = denom_traces(&relayer.b);
+        assert_eq!(
+            traces,
+            vec![DenomTraceResponse {
+                address: "voucher0".to_string(),
+                channel: b_channel.clone(),
+                path: path.clone(),
+            }]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(600, "ucosm")]);
+
+        // more tokens reuse the same voucher
+        relayer
+            .a
+            .transfer(SENDER, coin(100, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+        assert_eq!(denom_traces(&relayer.b).len(), 1);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 700);
+
+        // send some of them back, they are burnt here and released on the other side
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 250, &b_channel, "rcpt-home")
+            .unwrap();
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 450);
+        let packet: Ics20Packet = from_binary(&relayer.b.pending_packets()[0].data).unwrap();
+        assert_eq!(packet.denom, path);
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+
+        assert_eq!(relayer.a.balance("rcpt-home", "ucosm"), 250);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 450);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(450, "ucosm")]);
+        assert_eq!(state.total_sent, vec![Amount::native(700, "ucosm")]);
+        // vouchers never show up in the escrow balances
+        let state = channel_state(&relayer.b, &b_channel);
+        assert_eq!(state.balances, vec![]);
+    }
+
+    #[test]
+    fn cw20_round_trip() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_cw20_balance(TOKEN, SENDER, 5000);
+
+        // not on the allow list yet
+        let err = relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 2000, &a_channel, RECEIVER)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotOnAllowList.to_string());
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 5000);
+        assert!(relayer.a.pending_packets().is_empty());
+
+        allow_token(&mut relayer.a);
+        relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 2000, &a_channel, RECEIVER)
+            .unwrap();
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 3000);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, contract.as_str()), 2000);
+
+        relayer.relay_all().unwrap();
+        let traces = denom_traces(&relayer.b);
+        assert_eq!(
+            traces[0].path,
+            format!("{}/{}/cw20:{}", relayer.b.port, b_channel, TOKEN)
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 2000);
+
+        // all the way back
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 2000, &b_channel, SENDER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 0);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 5000);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, contract.as_str()), 0);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::cw20(0, TOKEN)]);
+        assert_eq!(state.total_sent, vec![Amount::cw20(2000, TOKEN)]);
+    }
+
+    #[test]
+    fn rejected_transfer_is_refunded() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        // the remote contract only redeems its own tokens
+        CONFIG
+            .update(&mut relayer.b.deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.voucher_code_id = None;
+                Ok(cfg)
+            })
+            .unwrap();
+
+        relayer
+            .a
+            .transfer(SENDER, coin(600, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::NoForeignTokens {}.to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+
+        // nothing was paid out on the remote side
+        assert_eq!(denom_traces(&relayer.b), vec![]);
+        let state = channel_state(&relayer.b, &b_channel);
+        assert_eq!(state.balances, vec![]);
+
+        // and the sender got the tokens back
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 0);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(0, "ucosm")]);
+        assert_eq!(state.total_sent, vec![Amount::native(600, "ucosm")]);
+    }
+
+    #[test]
+    fn packets_flow_in_both_directions() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(100, "ucosm"));
+        relayer.b.set_balance(SENDER, coin(200, "ustake"));
+
+        relayer
+            .a
+            .transfer(SENDER, coin(100, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer
+            .b
+            .transfer(SENDER, coin(200, "ustake"), &b_channel, RECEIVER)
+            .unwrap();
+
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed.len(), 2);
+        assert!(relayed
+            .iter()
+            .all(|r| matches!(r, Relayed::Ack(Ics20Ack::Result(_)))));
+        assert_eq!(relayer.a.cw20_balance("voucher0", RECEIVER), 200);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 100);
+        assert_eq!(
+            denom_traces(&relayer.a)[0].path,
+            format!("{}/{}/ustake", relayer.a.port, a_channel)
+        );
+        assert!(relayer.a.pending_packets().is_empty());
+        assert!(relayer.b.pending_packets().is_empty());
+    }
+
+    #[test]
+    fn timeout_refunds_sender() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer.a.set_cw20_balance(TOKEN, SENDER, 1000);
+        allow_token(&mut relayer.a);
+
+        relayer
+            .a
+            .transfer(SENDER, coin(250, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 750, &a_channel, RECEIVER)
+            .unwrap();
+
+        // the relayer is too slow, the remote chain moves past the timeout
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout, Relayed::Timeout]);
+
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 1000);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 0);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, contract.as_str()), 0);
+
+        // the packets never reached the other side
+        assert_eq!(denom_traces(&relayer.b), vec![]);
+        let state = channel_state(&relayer.b, &b_channel);
+        assert_eq!(state.total_sent, vec![]);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(
+            state.balances,
+            vec![Amount::cw20(0, TOKEN), Amount::native(0, "ucosm")]
+        );
+    }
+
+    #[test]
+    fn returned_voucher_is_minted_again_on_timeout() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 400, &b_channel, SENDER)
+            .unwrap();
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+
+        relayer.a.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout]);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 0);
+    }
+
+    #[test]
+    fn voucher_on_other_channel_is_a_plain_cw20() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        let (_, other_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        // this would need to be escrowed like any other cw20, which requires the allow list
+        let err = relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 400, &other_channel, SENDER)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotOnAllowList.to_string());
+    }
+
+    #[test]
+    fn inflow_rate_limit_returns_error_ack() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        // at most half of the escrowed tokens can be redeemed per day
+        let limit = ExecuteMsg::SetRateLimit {
+            channel: a_channel.clone(),
+            denom: "ucosm".to_string(),
+            window: 86400,
+            max_inflow: Some(Quota::Percent(Decimal::percent(50))),
+            max_outflow: None,
+        };
+        relayer.a.execute(GOV, &[], limit).unwrap();
+
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 400, &b_channel, SENDER)
+            .unwrap();
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 200, &b_channel, SENDER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::RateLimitExceeded {
+            channel: a_channel.clone(),
+            denom: "ucosm".to_string(),
+        }
+        .to_string();
+        assert_eq!(relayed[1..], vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert!(matches!(relayed[0], Relayed::Ack(Ics20Ack::Result(_))));
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 400);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+
+        // a day later, the quota is half of the remaining 600
+        relayer.a.advance_blocks(86400 / BLOCK_TIME);
+        relayer.b.advance_blocks(86400 / BLOCK_TIME);
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 300, &b_channel, SENDER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 700);
+    }
+
+    const HOOK: &str = "hook";
+
+    fn hook_memo(contract: &str) -> Option<String> {
+        let msg = to_binary(&"ping").unwrap();
+        Some(format!(
+            r#"{{"wasm":{{"contract":"{}","msg":"{}"}}}}"#,
+            contract,
+            msg.to_base64()
+        ))
+    }
+
+    fn return_with_hook(relayer: &mut Relayer, channel: &str, contract: &str, amount: u128) {
+        let transfer = TransferMsg {
+            memo: hook_memo(contract),
+            ..transfer_msg(channel, contract)
+        };
+        relayer
+            .b
+            .send_cw20_with("voucher0", RECEIVER, amount, transfer)
+            .unwrap();
+    }
+
+    #[test]
+    fn wasm_hook_receives_native_tokens() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer.a.add_hook_contract(HOOK, true);
+        let allow = ExecuteMsg::AllowHook {
+            contract: HOOK.to_string(),
+        };
+        relayer.a.execute(GOV, &[], allow).unwrap();
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        // the memo is passed along in the packet
+        return_with_hook(&mut relayer, &b_channel, HOOK, 300);
+        let packet: Ics20Packet = from_binary(&relayer.b.pending_packets()[0].data).unwrap();
+        assert_eq!(packet.memo, hook_memo(HOOK));
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+
+        assert_eq!(
+            relayer.a.hook_calls,
+            vec![HookCall {
+                contract: HOOK.to_string(),
+                msg: to_binary(&"ping").unwrap(),
+                funds: vec![coin(300, "ucosm")],
+            }]
+        );
+        assert_eq!(relayer.a.balance(HOOK, "ucosm"), 300);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(700, "ucosm")]);
+    }
+
+    #[test]
+    fn wasm_hook_receives_cw20_tokens() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_cw20_balance(TOKEN, SENDER, 1000);
+        allow_token(&mut relayer.a);
+        relayer.a.add_hook_contract(HOOK, true);
+        let allow = ExecuteMsg::AllowHook {
+            contract: HOOK.to_string(),
+        };
+        relayer.a.execute(GOV, &[], allow).unwrap();
+        relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 1000, &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        return_with_hook(&mut relayer, &b_channel, HOOK, 400);
+        relayer.relay_all().unwrap();
+
+        let receive = Cw20ReceiveMsg {
+            sender: relayer.a.contract().into(),
+            amount: Uint128::new(400),
+            msg: to_binary(&"ping").unwrap(),
+        };
+        assert_eq!(
+            relayer.a.hook_calls,
+            vec![HookCall {
+                contract: HOOK.to_string(),
+                msg: to_binary(&receive).unwrap(),
+                funds: vec![],
+            }]
+        );
+        assert_eq!(relayer.a.cw20_balance(TOKEN, HOOK), 400);
+    }
+
+    #[test]
+    fn failing_wasm_hooks_return_error_acks() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        // not on the hook allow list
+        relayer.a.add_hook_contract(HOOK, false);
+        return_with_hook(&mut relayer, &b_channel, HOOK, 100);
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::HookNotAllowed {
+            contract: HOOK.to_string(),
+        }
+        .to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        // the vouchers are minted again
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+
+        // the receiver must be the hook contract
+        let allow = ExecuteMsg::AllowHook {
+            contract: HOOK.to_string(),
+        };
+        relayer.a.execute(GOV, &[], allow).unwrap();
+        let transfer = TransferMsg {
+            memo: hook_memo(HOOK),
+            ..transfer_msg(&b_channel, "someone-else")
+        };
+        relayer
+            .b
+            .send_cw20_with("voucher0", RECEIVER, 100, transfer)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::HookReceiverMismatch.to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+
+        // the hook contract fails
+        return_with_hook(&mut relayer, &b_channel, HOOK, 100);
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Error(_))]));
+
+        assert!(relayer.a.hook_calls.is_empty());
+        assert_eq!(relayer.a.balance(HOOK, "ucosm"), 0);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(1000, "ucosm")]);
+
+        // once removed from the allow list, it cannot be called anymore
+        relayer.a.add_hook_contract(HOOK, true);
+        let remove = ExecuteMsg::RemoveHook {
+            contract: HOOK.to_string(),
+        };
+        relayer.a.execute(GOV, &[], remove).unwrap();
+        return_with_hook(&mut relayer, &b_channel, HOOK, 100);
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Error(_))]));
+        assert!(relayer.a.hook_calls.is_empty());
+    }
+
+    #[test]
+    fn wasm_hooks_are_rejected_for_vouchers() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        let transfer = TransferMsg {
+            memo: hook_memo(HOOK),
+            ..transfer_msg(&a_channel, HOOK)
+        };
+        relayer
+            .a
+            .transfer_with(SENDER, coin(1000, "ucosm"), transfer)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::HookOnVoucher.to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+    }
+
+    #[test]
+    fn failed_transfer_is_reverted() {
+        let mut relayer = Relayer::new();
+        relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+
+        let err = relayer
+            .a
+            .transfer(SENDER, coin(500, "ucosm"), "channel-3", RECEIVER)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoSuchChannel {
+                id: "channel-3".to_string()
+            }
+            .to_string()
+        );
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+        assert!(relayer.a.pending_packets().is_empty());
+
+        // cannot send more than we own
+        relayer
+            .a
+            .transfer(SENDER, coin(5000, "ucosm"), "channel-0", RECEIVER)
+            .unwrap_err();
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+    }
+
+    #[test]
+    fn paused_channel_returns_error_ack() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+
+        let pause = ExecuteMsg::PauseChannel {
+            channel: a_channel.clone(),
+        };
+        relayer.a.execute(GOV, &[], pause).unwrap();
+        assert!(channel_state(&relayer.a, &a_channel).paused);
+
+        // returning tokens are refunded on the other side
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 400, &b_channel, SENDER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::ChannelPaused {
+            channel: a_channel.clone(),
+        }
+        .to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 0);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+
+        let resume = ExecuteMsg::ResumeChannel {
+            channel: a_channel.clone(),
+        };
+        relayer.a.execute(GOV, &[], resume).unwrap();
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 400, &b_channel, SENDER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 400);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+    }
+
+    #[test]
+    fn paused_voucher_returns_error_ack() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+
+        // vouchers are paused by their full path
+        let pause = ExecuteMsg::PauseDenom {
+            denom: format!("{}/{}/ucosm", relayer.b.port, b_channel),
+        };
+        relayer.b.execute(GOV, &[], pause).unwrap();
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Error(_))]));
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 1000);
+        assert!(denom_traces(&relayer.b).is_empty());
+    }
+
+    fn forward_memo(port: &str, channel: &str, receiver: &str) -> Option<String> {
+        Some(format!(
+            r#"{{"forward":{{"receiver":"{}","port":"{}","channel":"{}"}}}}"#,
+            receiver, port, channel
+        ))
+    }
+
+    // A is the hub: vouchers of its ucosm are sent back from B and forwarded to B over another channel
+    fn setup_forward() -> (Relayer, [(String, String); 2]) {
+        let mut relayer = Relayer::new();
+        let first = relayer.open_channel();
+        let second = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer
+            .a
+            .transfer(SENDER, coin(1000, "ucosm"), &first.0, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+        (relayer, [first, second])
+    }
+
+    fn forward_voucher(relayer: &mut Relayer, channels: &[(String, String); 2], forward: &str) {
+        let memo = forward_memo(&relayer.a.port.clone(), forward, "final");
+        forward_voucher_with(relayer, channels, memo);
+    }
+
+    fn forward_voucher_with(
+        relayer: &mut Relayer,
+        channels: &[(String, String); 2],
+        memo: Option<String>,
+    ) {
+        let transfer = TransferMsg {
+            memo,
+            ..transfer_msg(&channels[0].1, "hub")
+        };
+        relayer
+            .b
+            .send_cw20_with("voucher0", RECEIVER, 400, transfer)
+            .unwrap();
+    }
+
+    fn pending_forwards(chain: &Chain) -> usize {
+        FORWARDS
+            .range(&chain.deps.storage, None, None, Order::Ascending)
+            .count()
+    }
+
+    #[test]
+    fn forward_over_hub() {
+        let (mut relayer, channels) = setup_forward();
+        let [(a_first, _), (a_second, _)] = &channels;
+        forward_voucher(&mut relayer, &channels, a_second);
+
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(
+            relayed[..],
+            [
+                Relayed::Ack(Ics20Ack::Result(_)),
+                Relayed::Ack(Ics20Ack::Result(_))
+            ]
+        ));
+
+        // the tokens moved from one channel to the other, without paying out on the hub
+        assert_eq!(relayer.a.balance("hub", "ucosm"), 0);
+        assert_eq!(
+            channel_state(&relayer.a, a_first).balances,
+            vec![Amount::native(600, "ucosm")]
+        );
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::native(400, "ucosm")]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+        assert_eq!(relayer.b.cw20_balance("voucher1", "final"), 400);
+        assert_eq!(pending_forwards(&relayer.a), 0);
+    }
+
+    #[test]
+    fn failed_forward_is_refunded_to_the_original_sender() {
+        let (mut relayer, channels) = setup_forward();
+        let [(a_first, _), (a_second, b_second)] = &channels;
+        let pause = ExecuteMsg::PauseChannel {
+            channel: b_second.clone(),
+        };
+        relayer.b.execute(GOV, &[], pause).unwrap();
+        forward_voucher(&mut relayer, &channels, a_second);
+
+        // the forward is acknowledged before we know it failed
+        relayer.relay(Side::B).unwrap();
+        assert_eq!(pending_forwards(&relayer.a), 1);
+
+        // the failure is propagated back with another packet
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::ChannelPaused {
+            channel: b_second.clone(),
+        }
+        .to_string();
+        assert_eq!(relayed[0], Relayed::Ack(Ics20Ack::Error(err)));
+        assert!(matches!(relayed[1..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+        assert_eq!(
+            channel_state(&relayer.a, a_first).balances,
+            vec![Amount::native(1000, "ucosm")]
+        );
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::native(0, "ucosm")]
+        );
+        assert_eq!(pending_forwards(&relayer.a), 0);
+
+        // the packet sending them back is tracked as a transfer of the hub receiver
+        let res: TransfersResponse = relayer.a.query(QueryMsg::TransfersBySender {
+            sender: "hub".to_string(),
+            start_after: None,
+            limit: None,
+        });
+        let transfers: Vec<_> = res
+            .transfers
+            .into_iter()
+            .map(|t| (t.channel, t.receiver, t.amount.u128(), t.status))
+            .collect();
+        assert_eq!(
+            transfers,
+            vec![(
+                a_first.clone(),
+                RECEIVER.to_string(),
+                400,
+                TransferStatus::Acked
+            )]
+        );
+    }
+
+    #[test]
+    fn failed_forward_with_overflowing_refund_timeout_is_trapped() {
+        let (mut relayer, channels) = setup_forward();
+        let [(a_first, _), (a_second, b_second)] = &channels;
+        let pause = ExecuteMsg::PauseChannel {
+            channel: b_second.clone(),
+        };
+        relayer.b.execute(GOV, &[], pause).unwrap();
+        forward_voucher(&mut relayer, &channels, a_second);
+        relayer.relay(Side::B).unwrap();
+
+        // the packet sending the tokens back cannot get a timeout
+        CONFIG
+            .update(&mut relayer.a.deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.default_timeout = u64::MAX;
+                Ok(cfg)
+            })
+            .unwrap();
+        let relayed = relayer.relay(Side::A).unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Error(_))]));
+
+        // the error is trapped rather than panicking, and the tokens are kept for the hub receiver
+        assert!(relayer.a.pending_packets().is_empty());
+        assert_eq!(
+            channel_state(&relayer.a, a_first).balances,
+            vec![Amount::native(600, "ucosm")]
+        );
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::native(0, "ucosm")]
+        );
+        let refunds = pending_refunds(&relayer.a, "hub");
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].amount, Amount::native(400, "ucosm"));
+        assert_eq!(pending_forwards(&relayer.a), 0);
+    }
+
+    #[test]
+    fn invalid_forward_returns_error_ack() {
+        let (mut relayer, channels) = setup_forward();
+        forward_voucher(&mut relayer, &channels, "channel-9");
+
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::NoSuchChannel {
+            id: "channel-9".to_string(),
+        }
+        .to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+        assert_eq!(
+            channel_state(&relayer.a, &channels[0].0).balances,
+            vec![Amount::native(1000, "ucosm")]
+        );
+
+        // a timeout too far in the future fails rather than overflowing
+        let memo = format!(
+            r#"{{"forward":{{"receiver":"final","port":"{}","channel":"{}","timeout":{}}}}}"#,
+            relayer.a.port,
+            channels[1].0,
+            u64::MAX
+        );
+        forward_voucher_with(&mut relayer, &channels, Some(memo));
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::ForwardTimeoutTooLong.to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+    }
+
+    #[test]
+    fn forwarding_must_be_enabled() {
+        let (mut relayer, channels) = setup_forward();
+        CONFIG
+            .update(&mut relayer.a.deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.allow_forwarding = false;
+                Ok(cfg)
+            })
+            .unwrap();
+        forward_voucher(&mut relayer, &channels, &channels[1].0.clone());
+
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::ForwardingDisabled.to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert_eq!(relayer.a.balance("hub", "ucosm"), 0);
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 1000);
+        assert_eq!(
+            channel_state(&relayer.a, &channels[0].0).balances,
+            vec![Amount::native(1000, "ucosm")]
+        );
+    }
+
+    // B's ustake is forwarded by the hub A back to B over another channel, as vouchers of A
+    fn forward_foreign_tokens(relayer: &mut Relayer, channels: &[(String, String); 2]) {
+        relayer.b.set_balance(SENDER, coin(1000, "ustake"));
+        let transfer = TransferMsg {
+            memo: forward_memo(&relayer.a.port.clone(), &channels[1].0, "final"),
+            ..transfer_msg(&channels[0].1, "hub")
+        };
+        relayer
+            .b
+            .transfer_with(SENDER, coin(1000, "ustake"), transfer)
+            .unwrap();
+    }
+
+    #[test]
+    fn forward_foreign_tokens_over_hub() {
+        let mut relayer = Relayer::new();
+        let channels = [relayer.open_channel(), relayer.open_channel()];
+        let [(a_first, b_first), (a_second, b_second)] = &channels;
+        forward_foreign_tokens(&mut relayer, &channels);
+
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(
+            relayed[..],
+            [
+                Relayed::Ack(Ics20Ack::Result(_)),
+                Relayed::Ack(Ics20Ack::Result(_))
+            ]
+        ));
+
+        // the hub escrows its vouchers on the second channel, and B mints vouchers of them
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.cw20_balance("voucher0", "hub"), 0);
+        assert_eq!(relayer.a.cw20_balance("voucher0", contract.as_str()), 1000);
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::cw20(1000, "voucher0")]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", "final"), 1000);
+        assert_eq!(
+            channel_state(&relayer.b, b_first).balances,
+            vec![Amount::native(1000, "ustake")]
+        );
+        assert!(PENDING_VOUCHER_FORWARD
+            .may_load(&relayer.a.deps.storage)
+            .unwrap()
+            .is_none());
+
+        // sending them back over the hub burns the vouchers of the hub and releases the ustake
+        let transfer = TransferMsg {
+            memo: forward_memo(&relayer.a.port.clone(), a_first, RECEIVER),
+            ..transfer_msg(b_second, "hub")
+        };
+        relayer
+            .b
+            .send_cw20_with("voucher0", "final", 1000, transfer)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(
+            relayed[..],
+            [
+                Relayed::Ack(Ics20Ack::Result(_)),
+                Relayed::Ack(Ics20Ack::Result(_))
+            ]
+        ));
+        assert_eq!(relayer.a.cw20_balance("voucher0", contract.as_str()), 0);
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::cw20(0, "voucher0")]
+        );
+        assert_eq!(relayer.b.balance(RECEIVER, "ustake"), 1000);
+        assert_eq!(
+            channel_state(&relayer.b, b_first).balances,
+            vec![Amount::native(0, "ustake")]
+        );
+        assert_eq!(pending_forwards(&relayer.a), 0);
+    }
+
+    #[test]
+    fn forward_of_foreign_tokens_onto_paused_channel_returns_error_ack() {
+        let mut relayer = Relayer::new();
+        let channels = [relayer.open_channel(), relayer.open_channel()];
+        let [(_, b_first), (a_second, _)] = &channels;
+        let pause = ExecuteMsg::PauseChannel {
+            channel: a_second.clone(),
+        };
+        relayer.a.execute(GOV, &[], pause).unwrap();
+        forward_foreign_tokens(&mut relayer, &channels);
+
+        // the voucher does not exist yet, the paused channel still stops the forward
+        let relayed = relayer.relay_all().unwrap();
+        let err = ContractError::ChannelPaused {
+            channel: a_second.clone(),
+        }
+        .to_string();
+        assert_eq!(relayed, vec![Relayed::Ack(Ics20Ack::Error(err))]);
+        assert!(denom_traces(&relayer.a).is_empty());
+        assert_eq!(relayer.b.balance(SENDER, "ustake"), 1000);
+        assert_eq!(
+            channel_state(&relayer.b, b_first).balances,
+            vec![Amount::native(0, "ustake")]
+        );
+    }
+
+    #[test]
+    fn failed_forward_of_foreign_tokens_is_refunded() {
+        let mut relayer = Relayer::new();
+        let channels = [relayer.open_channel(), relayer.open_channel()];
+        let [(_, b_first), (a_second, b_second)] = &channels;
+        let pause = ExecuteMsg::PauseChannel {
+            channel: b_second.clone(),
+        };
+        relayer.b.execute(GOV, &[], pause).unwrap();
+        forward_foreign_tokens(&mut relayer, &channels);
+
+        relayer.relay(Side::B).unwrap();
+        assert_eq!(pending_forwards(&relayer.a), 1);
+
+        // the vouchers of the hub are burnt, and the ustake is released on B
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[0], Relayed::Ack(Ics20Ack::Error(_))));
+        assert!(matches!(relayed[1..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.cw20_balance("voucher0", contract.as_str()), 0);
+        assert_eq!(relayer.a.cw20_balance("voucher0", "hub"), 0);
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::cw20(0, "voucher0")]
+        );
+        assert_eq!(relayer.b.balance(SENDER, "ustake"), 1000);
+        assert_eq!(
+            channel_state(&relayer.b, b_first).balances,
+            vec![Amount::native(0, "ustake")]
+        );
+        assert_eq!(pending_forwards(&relayer.a), 0);
+    }
+
+    #[test]
+    fn failed_return_of_forwarded_vouchers_is_refunded() {
+        let mut relayer = Relayer::new();
+        let channels = [relayer.open_channel(), relayer.open_channel()];
+        let [(a_first, b_first), (a_second, b_second)] = &channels;
+        forward_foreign_tokens(&mut relayer, &channels);
+        relayer.relay_all().unwrap();
+
+        // B refuses the ustake, so the hub escrows its vouchers again and returns them
+        let pause = ExecuteMsg::PauseChannel {
+            channel: b_first.clone(),
+        };
+        relayer.b.execute(GOV, &[], pause).unwrap();
+        let transfer = TransferMsg {
+            memo: forward_memo(&relayer.a.port.clone(), a_first, RECEIVER),
+            ..transfer_msg(b_second, "hub")
+        };
+        relayer
+            .b
+            .send_cw20_with("voucher0", "final", 1000, transfer)
+            .unwrap();
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(
+            relayed[..],
+            [
+                Relayed::Ack(Ics20Ack::Result(_)),
+                Relayed::Ack(Ics20Ack::Error(_)),
+                Relayed::Ack(Ics20Ack::Result(_))
+            ]
+        ));
+        let contract = relayer.a.contract();
+        assert_eq!(relayer.a.cw20_balance("voucher0", contract.as_str()), 1000);
+        assert_eq!(
+            channel_state(&relayer.a, a_second).balances,
+            vec![Amount::cw20(1000, "voucher0")]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", "final"), 1000);
+        assert_eq!(pending_forwards(&relayer.a), 0);
+    }
+
+    fn transfer_status(chain: &Chain, channel: &str, sequence: u64) -> TransferStatus {
+        let res: TransferResponse = chain.query(QueryMsg::Transfer {
+            channel: channel.to_string(),
+            sequence,
+        });
+        res.status
+    }
+
+    #[test]
+    fn transfers_are_tracked_by_sequence() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+
+        relayer
+            .a
+            .transfer(SENDER, coin(100, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let acked = relayer.a.pending_packets()[0].sequence;
+        let res: TransferResponse = relayer.a.query(QueryMsg::Transfer {
+            channel: a_channel.clone(),
+            sequence: acked,
+        });
+        assert_eq!(
+            res,
+            TransferResponse {
+                channel: a_channel.clone(),
+                sequence: acked,
+                sender: SENDER.to_string(),
+                receiver: RECEIVER.to_string(),
+                denom: "ucosm".to_string(),
+                amount: Uint128::new(100),
+                timeout: relayer.a.env.block.time.plus_seconds(DEFAULT_TIMEOUT),
+                status: TransferStatus::Pending,
+                callback: None,
+                relayer_tip: Uint128::zero(),
+                batch: vec![],
+            }
+        );
+        relayer.relay_all().unwrap();
+        assert_eq!(
+            transfer_status(&relayer.a, &a_channel, acked),
+            TransferStatus::Acked
+        );
+
+        // the remote contract stops accepting foreign tokens
+        CONFIG
+            .update(&mut relayer.b.deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.voucher_code_id = None;
+                Ok(cfg)
+            })
+            .unwrap();
+        relayer
+            .a
+            .transfer(SENDER, coin(200, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let failed = relayer.a.pending_packets()[0].sequence;
+        relayer.relay_all().unwrap();
+        assert_eq!(
+            transfer_status(&relayer.a, &a_channel, failed),
+            TransferStatus::Failed {
+                error: ContractError::NoForeignTokens {}.to_string()
+            }
+        );
+
+        relayer
+            .a
+            .transfer(SENDER, coin(300, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let timed_out = relayer.a.pending_packets()[0].sequence;
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        relayer.relay_all().unwrap();
+        assert_eq!(
+            transfer_status(&relayer.a, &a_channel, timed_out),
+            TransferStatus::TimedOut
+        );
+
+        // all transfers of the sender, paginated
+        let list = |start_after: Option<u64>| {
+            let res: TransfersResponse = relayer.a.query(QueryMsg::TransfersBySender {
+                sender: SENDER.to_string(),
+                start_after: start_after.map(|sequence| (a_channel.clone(), sequence)),
+                limit: Some(2),
+            });
+            res.transfers
+                .into_iter()
+                .map(|transfer| (transfer.sequence, transfer.amount.u128()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(list(None), vec![(acked, 100), (failed, 200)]);
+        assert_eq!(list(Some(failed)), vec![(timed_out, 300)]);
+        let res: TransfersResponse = relayer.a.query(QueryMsg::TransfersBySender {
+            sender: RECEIVER.to_string(),
+            start_after: None,
+            limit: None,
+        });
+        assert_eq!(res.transfers, vec![]);
+    }
+
+    fn pending_refunds(chain: &Chain, recipient: &str) -> Vec<PendingRefund> {
+        let res: PendingRefundsResponse = chain.query(QueryMsg::PendingRefunds {
+            recipient: recipient.to_string(),
+            start_after: None,
+            limit: None,
+        });
+        res.refunds
+    }
+
+    #[test]
+    fn failed_refunds_can_be_claimed() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        relayer.a.set_cw20_balance(TOKEN, SENDER, 1000);
+        allow_token(&mut relayer.a);
+        relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 250, &a_channel, RECEIVER)
+            .unwrap();
+        relayer
+            .a
+            .send_cw20(TOKEN, SENDER, 750, &a_channel, RECEIVER)
+            .unwrap();
+
+        // the escrowed tokens are gone (eg. the cw20 contract misbehaves), so the refunds fail
+        let contract = relayer.a.contract();
+        relayer.a.set_cw20_balance(TOKEN, contract.as_str(), 0);
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout, Relayed::Timeout]);
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 0);
+        assert_eq!(
+            pending_refunds(&relayer.a, SENDER),
+            vec![
+                PendingRefund {
+                    id: 1,
+                    recipient: SENDER.to_string(),
+                    amount: Amount::cw20(250, TOKEN),
+                    mint: false,
+                },
+                PendingRefund {
+                    id: 2,
+                    recipient: SENDER.to_string(),
+                    amount: Amount::cw20(750, TOKEN),
+                    mint: false,
+                }
+            ]
+        );
+
+        // refunds that fail again are kept under the same id
+        let pending = pending_refunds(&relayer.a, SENDER);
+        relayer
+            .a
+            .execute(SENDER, &[], ExecuteMsg::ClaimRefund {})
+            .unwrap();
+        assert_eq!(pending_refunds(&relayer.a, SENDER), pending);
+
+        // with enough tokens for one of them, that one is paid out and the other one kept
+        relayer.a.set_cw20_balance(TOKEN, contract.as_str(), 250);
+        relayer
+            .a
+            .execute(SENDER, &[], ExecuteMsg::ClaimRefund {})
+            .unwrap();
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 250);
+        assert_eq!(pending_refunds(&relayer.a, SENDER), pending[1..]);
+
+        // only gov can pay out single refunds, and a failed one stays pending as well
+        let retry = ExecuteMsg::RetryRefund { id: 2 };
+        relayer.a.execute(SENDER, &[], retry.clone()).unwrap_err();
+        relayer.a.execute(GOV, &[], retry.clone()).unwrap();
+        assert_eq!(pending_refunds(&relayer.a, SENDER), pending[1..]);
+        relayer.a.set_cw20_balance(TOKEN, contract.as_str(), 750);
+        relayer.a.execute(GOV, &[], retry.clone()).unwrap();
+        assert_eq!(relayer.a.cw20_balance(TOKEN, SENDER), 1000);
+        assert_eq!(pending_refunds(&relayer.a, SENDER), vec![]);
+        let err = relayer.a.execute(GOV, &[], retry).unwrap_err();
+        assert_eq!(err, ContractError::NoSuchRefund { id: 2 }.to_string());
+        let err = relayer
+            .a
+            .execute(SENDER, &[], ExecuteMsg::ClaimRefund {})
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingRefunds {}.to_string());
+    }
+
+    const VAULT: &str = "vault";
+
+    // the callback message as the receiving contract parses it
+    #[cw_serde]
+    enum CallbackExecuteMsg {
+        Ics20Callback(Ics20CallbackMsg),
+    }
+
+    fn allow_callback(chain: &mut Chain, contract: &str, accept: bool) {
+        chain.add_hook_contract(contract, accept);
+        let allow = ExecuteMsg::Allow(AllowMsg {
+            contract: contract.to_string(),
+            gas_limit: Some(500_000),
+        });
+        chain.execute(GOV, &[], allow).unwrap();
+    }
+
+    fn callbacks(chain: &Chain) -> Vec<Ics20CallbackMsg> {
+        chain
+            .hook_calls
+            .iter()
+            .map(|call| match from_binary(&call.msg).unwrap() {
+                CallbackExecuteMsg::Ics20Callback(msg) => msg,
+            })
+            .collect()
+    }
+
+    fn transfer_with_callback(relayer: &mut Relayer, channel: &str, callback: &str) -> u64 {
+        let transfer = TransferMsg {
+            callback: Some(callback.to_string()),
+            ..transfer_msg(channel, RECEIVER)
+        };
+        relayer
+            .a
+            .transfer_with(VAULT, coin(100, "ucosm"), transfer)
+            .unwrap();
+        relayer.a.pending_packets().last().unwrap().sequence
+    }
+
+    #[test]
+    fn callbacks_report_the_outcome() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        relayer.a.set_balance(VAULT, coin(1000, "ucosm"));
+        allow_callback(&mut relayer.a, VAULT, true);
+
+        let acked = transfer_with_callback(&mut relayer, &a_channel, VAULT);
+        relayer.relay_all().unwrap();
+        let timed_out = transfer_with_callback(&mut relayer, &a_channel, VAULT);
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        relayer.relay_all().unwrap();
+        relayer.a.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        CONFIG
+            .update(&mut relayer.b.deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.voucher_code_id = None;
+                Ok(cfg)
+            })
+            .unwrap();
+        let failed = transfer_with_callback(&mut relayer, &a_channel, VAULT);
+        relayer.relay_all().unwrap();
+
+        assert_eq!(
+            callbacks(&relayer.a),
+            vec![
+                Ics20CallbackMsg {
+                    channel: a_channel.clone(),
+                    sequence: acked,
+                    success: true,
+                    error: None,
+                },
+                Ics20CallbackMsg {
+                    channel: a_channel.clone(),
+                    sequence: timed_out,
+                    success: false,
+                    error: Some("timeout".to_string()),
+                },
+                Ics20CallbackMsg {
+                    channel: a_channel.clone(),
+                    sequence: failed,
+                    success: false,
+                    error: Some(ContractError::NoForeignTokens {}.to_string()),
+                },
+            ]
+        );
+        assert_eq!(relayer.a.balance(VAULT, "ucosm"), 900);
+    }
+
+    #[test]
+    fn failing_callback_does_not_block_the_refund() {
+        let mut relayer = Relayer::new();
+        let (a_channel, _) = relayer.open_channel();
+        relayer.a.set_balance(VAULT, coin(1000, "ucosm"));
+        allow_callback(&mut relayer.a, "broken", false);
+
+        // callbacks must be on the allow list, so they get a gas limit
+        let transfer = TransferMsg {
+            callback: Some("unknown".to_string()),
+            ..transfer_msg(&a_channel, RECEIVER)
+        };
+        let err = relayer
+            .a
+            .transfer_with(VAULT, coin(100, "ucosm"), transfer)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotOnAllowList.to_string());
+
+        transfer_with_callback(&mut relayer, &a_channel, "broken");
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout]);
+        assert_eq!(relayer.a.balance(VAULT, "ucosm"), 1000);
+        assert_eq!(callbacks(&relayer.a), vec![]);
+    }
+
+    #[test]
+    fn fees_and_relayer_tips() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        let contract = relayer.a.contract();
+        let set_fee = ExecuteMsg::SetFee {
+            denom: "ucosm".to_string(),
+            fee: Some(Fee::Flat(Uint128::new(10))),
+            relayer_tip: Some(Uint128::new(5)),
+        };
+        relayer.a.execute(GOV, &[], set_fee).unwrap();
+
+        // the fee and the tip are held back, the relayer of the ack gets the tip
+        relayer
+            .a
+            .transfer(SENDER, coin(100, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.relay_all().unwrap();
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 85);
+        assert_eq!(relayer.a.balance(RELAYER, "ucosm"), 5);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 95);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(85, "ucosm")]);
+
+        // the relayer of a returning packet is tipped from the released tokens
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 85, &b_channel, "rcpt-home")
+            .unwrap();
+        relayer.relay_all().unwrap();
+        assert_eq!(relayer.a.balance("rcpt-home", "ucosm"), 80);
+        assert_eq!(relayer.a.balance(RELAYER, "ucosm"), 10);
+
+        // on timeout the sender gets the sent tokens back, the relayer still gets the tip
+        relayer
+            .a
+            .transfer(SENDER, coin(100, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        relayer.b.advance_blocks(DEFAULT_TIMEOUT / BLOCK_TIME);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout]);
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 885);
+        assert_eq!(relayer.a.balance(RELAYER, "ucosm"), 15);
+
+        // only the accrued fees are left in the contract
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(0, "ucosm")]);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 20);
+        let withdraw = ExecuteMsg::WithdrawFees {
+            denom: "ucosm".to_string(),
+            recipient: "treasury".to_string(),
+        };
+        relayer.a.execute(GOV, &[], withdraw).unwrap();
+        assert_eq!(relayer.a.balance("treasury", "ucosm"), 20);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 0);
+    }
+
+    #[test]
+    fn batch_transfer_sends_one_packet_per_denom() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel();
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer.a.set_balance(SENDER, coin(1000, "ustake"));
+
+        let funds = [coin(100, "ucosm"), coin(300, "ustake")];
+        let batch = ExecuteMsg::TransferBatch(transfer_msg(&a_channel, RECEIVER));
+        relayer.a.execute(SENDER, &funds, batch).unwrap();
+        let sequences: Vec<u64> = relayer
+            .a
+            .pending_packets()
+            .iter()
+            .map(|packet| packet.sequence)
+            .collect();
+        assert_eq!(sequences.len(), 2);
+
+        // every packet is tracked under its own sequence
+        let res: TransfersResponse = relayer.a.query(QueryMsg::TransfersBySender {
+            sender: SENDER.to_string(),
+            start_after: None,
+            limit: None,
+        });
+        let tracked: Vec<_> = res
+            .transfers
+            .iter()
+            .map(|transfer| (transfer.sequence, transfer.denom.as_str()))
+            .collect();
+        assert_eq!(
+            tracked,
+            vec![(sequences[0], "ucosm"), (sequences[1], "ustake")]
+        );
+
+        relayer.relay_all().unwrap();
+        let traces = denom_traces(&relayer.b);
+        let vouchers: Vec<_> = traces.iter().map(|trace| trace.path.clone()).collect();
+        assert_eq!(
+            vouchers,
+            vec![
+                format!("{}/{}/ucosm", relayer.b.port, b_channel),
+                format!("{}/{}/ustake", relayer.b.port, b_channel),
+            ]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 100);
+        assert_eq!(relayer.b.cw20_balance("voucher1", RECEIVER), 300);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(
+            state.balances,
+            vec![Amount::native(100, "ucosm"), Amount::native(300, "ustake")]
+        );
+        for sequence in sequences {
+            assert_eq!(
+                transfer_status(&relayer.a, &a_channel, sequence),
+                TransferStatus::Acked
+            );
+        }
+    }
+
+    #[test]
+    fn ics20_2_round_trip() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel_with_version(ICS20_VERSION_2);
+        let a_info = channel_state(&relayer.a, &a_channel).info;
+        assert_eq!(a_info.version, ICS20_VERSION_2);
+        let b_info = channel_state(&relayer.b, &b_channel).info;
+        assert_eq!(b_info.version, ICS20_VERSION_2);
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+
+        relayer
+            .a
+            .transfer(SENDER, coin(600, "ucosm"), &a_channel, RECEIVER)
+            .unwrap();
+        let packet: Ics20PacketV2 = from_binary(&relayer.a.pending_packets()[0].data).unwrap();
+        assert_eq!(
+            packet.tokens,
+            vec![Token {
+                denom: Denom {
+                    base: "ucosm".to_string(),
+                    trace: vec![],
+                },
+                amount: Uint128::new(600),
+            }]
+        );
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 600);
+
+        // the vouchers are sent back with the channel they came over as hop
+        relayer
+            .b
+            .send_cw20("voucher0", RECEIVER, 250, &b_channel, "rcpt-home")
+            .unwrap();
+        let packet: Ics20PacketV2 = from_binary(&relayer.b.pending_packets()[0].data).unwrap();
+        assert_eq!(
+            packet.tokens[0].denom,
+            Denom {
+                base: "ucosm".to_string(),
+                trace: vec![Hop {
+                    port_id: relayer.b.port.clone(),
+                    channel_id: b_channel.clone(),
+                }],
+            }
+        );
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        assert_eq!(relayer.a.balance("rcpt-home", "ucosm"), 250);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(state.balances, vec![Amount::native(350, "ucosm")]);
+    }
+
+    #[test]
+    fn ics20_2_batch_sends_one_packet() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel_with_version(ICS20_VERSION_2);
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer.a.set_balance(SENDER, coin(1000, "ustake"));
+
+        let funds = [coin(100, "ucosm"), coin(300, "ustake")];
+        let batch = ExecuteMsg::TransferBatch(transfer_msg(&a_channel, RECEIVER));
+        relayer.a.execute(SENDER, &funds, batch).unwrap();
+        let pending = relayer.a.pending_packets();
+        assert_eq!(pending.len(), 1);
+        let sequence = pending[0].sequence;
+        let packet: Ics20PacketV2 = from_binary(&pending[0].data).unwrap();
+        assert_eq!(packet.tokens.len(), 2);
+
+        // the transfer is tracked with all its tokens
+        let res: TransferResponse = relayer.a.query(QueryMsg::Transfer {
+            channel: a_channel.clone(),
+            sequence,
+        });
+        assert_eq!((res.denom.as_str(), res.amount.u128()), ("ucosm", 100));
+        assert_eq!(
+            res.batch,
+            vec![BatchedToken {
+                denom: "ustake".to_string(),
+                amount: Uint128::new(300),
+                relayer_tip: Uint128::zero(),
+            }]
+        );
+
+        // both vouchers are created from the one packet
+        let relayed = relayer.relay_all().unwrap();
+        assert!(matches!(relayed[..], [Relayed::Ack(Ics20Ack::Result(_))]));
+        let vouchers: Vec<_> = denom_traces(&relayer.b)
+            .into_iter()
+            .map(|trace| trace.path)
+            .collect();
+        assert_eq!(
+            vouchers,
+            vec![
+                format!("{}/{}/ucosm", relayer.b.port, b_channel),
+                format!("{}/{}/ustake", relayer.b.port, b_channel),
+            ]
+        );
+        assert_eq!(relayer.b.cw20_balance("voucher0", RECEIVER), 100);
+        assert_eq!(relayer.b.cw20_balance("voucher1", RECEIVER), 300);
+        assert_eq!(
+            transfer_status(&relayer.a, &a_channel, sequence),
+            TransferStatus::Acked
+        );
+
+        // a timeout refunds all tokens of the packet
+        let mut transfer = transfer_msg(&a_channel, RECEIVER);
+        transfer.timeout = Some(BLOCK_TIME);
+        let batch = ExecuteMsg::TransferBatch(transfer);
+        relayer.a.execute(SENDER, &funds, batch).unwrap();
+        let sequence = relayer.a.pending_packets()[0].sequence;
+        relayer.b.advance_blocks(2);
+        let relayed = relayer.relay_all().unwrap();
+        assert_eq!(relayed, vec![Relayed::Timeout]);
+        assert_eq!(relayer.a.balance(SENDER, "ucosm"), 900);
+        assert_eq!(relayer.a.balance(SENDER, "ustake"), 700);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(
+            state.balances,
+            vec![Amount::native(100, "ucosm"), Amount::native(300, "ustake")]
+        );
+        assert_eq!(
+            transfer_status(&relayer.a, &a_channel, sequence),
+            TransferStatus::TimedOut
+        );
+    }
+
+    #[test]
+    fn ics20_2_batch_is_released_atomically() {
+        let mut relayer = Relayer::new();
+        let (a_channel, b_channel) = relayer.open_channel_with_version(ICS20_VERSION_2);
+        relayer.a.set_balance(SENDER, coin(1000, "ucosm"));
+        relayer.a.set_balance(SENDER, coin(1000, "ustake"));
+        let funds = [coin(100, "ucosm"), coin(300, "ustake")];
+        let batch = ExecuteMsg::TransferBatch(transfer_msg(&a_channel, RECEIVER));
+        relayer.a.execute(SENDER, &funds, batch).unwrap();
+        relayer.relay_all().unwrap();
+
+        // B only sends vouchers back one by one, so we build the packet returning both
+        let prefix = format!("{}/{}", relayer.b.port, b_channel);
+        let tokens = [
+            Ics20Packet::new(
+                Uint128::new(50),
+                format!("{}/ucosm", prefix),
+                RECEIVER,
+                "home",
+            ),
+            Ics20Packet::new(
+                Uint128::new(100),
+                format!("{}/ustake", prefix),
+                RECEIVER,
+                "home",
+            ),
+        ];
+        let src = IbcEndpoint {
+            port_id: relayer.b.port.clone(),
+            channel_id: b_channel.clone(),
+        };
+        let dest = IbcEndpoint {
+            port_id: relayer.a.port.clone(),
+            channel_id: a_channel.clone(),
+        };
+        let timeout = relayer.a.env.block.time.plus_seconds(60);
+        let packet = |tokens: &[Ics20Packet]| {
+            let data = encode_packets(ICS20_VERSION_2, tokens).unwrap();
+            IbcPacket::new(data, src.clone(), dest.clone(), 99, timeout.into())
+        };
+        let returned = packet(&tokens);
+
+        // sending the second token fails, so the first one is not released either
+        let contract = relayer.a.contract();
+        relayer.a.set_balance(contract.as_str(), coin(0, "ustake"));
+        let ack = relayer.a.receive_packet(returned.clone()).unwrap();
+        assert!(matches!(from_binary(&ack).unwrap(), Ics20Ack::Error(_)));
+        assert_eq!(relayer.a.balance("home", "ucosm"), 0);
+        assert_eq!(relayer.a.balance(contract.as_str(), "ucosm"), 100);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(
+            state.balances,
+            vec![Amount::native(100, "ucosm"), Amount::native(300, "ustake")]
+        );
+
+        relayer
+            .a
+            .set_balance(contract.as_str(), coin(300, "ustake"));
+        let ack = relayer.a.receive_packet(returned.clone()).unwrap();
+        assert!(matches!(from_binary(&ack).unwrap(), Ics20Ack::Result(_)));
+        assert_eq!(relayer.a.balance("home", "ucosm"), 50);
+        assert_eq!(relayer.a.balance("home", "ustake"), 100);
+        let state = channel_state(&relayer.a, &a_channel);
+        assert_eq!(
+            state.balances,
+            vec![Amount::native(50, "ucosm"), Amount::native(200, "ustake")]
+        );
+
+        // only the contract itself can release a batch
+        let release = ExecuteMsg::ReleaseBatch {
+            packet: returned,
+            relayer: RELAYER.to_string(),
+        };
+        let err = relayer.a.execute(SENDER, &[], release).unwrap_err();
+        assert_eq!(err, ContractError::InternalCallOnly.to_string());
+
+        // wasm hooks and forwards handle a single token
+        let hooked: Vec<_> = tokens
+            .iter()
+            .cloned()
+            .map(|token| token.with_memo(hook_memo("hook")))
+            .collect();
+        let ack = relayer.a.receive_packet(packet(&hooked)).unwrap();
+        assert_eq!(
+            from_binary::<Ics20Ack>(&ack).unwrap(),
+            Ics20Ack::Error(ContractError::MemoOnBatch.to_string())
+        );
+    }
+}
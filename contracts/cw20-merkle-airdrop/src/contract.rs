@@ -0,0 +1,793 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Uint128, WasmMsg,
+};
+use sha2::{Digest, Sha256};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
+    MerkleRootResponse, QueryMsg, ReceiveMsg,
+};
+use crate::state::{Config, Stage, CLAIMED, CONFIG, LATEST_STAGE, STAGES};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-merkle-airdrop";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let config = Config {
+        owner: Some(owner),
+        cw20_token_address: deps.api.addr_validate(&msg.cw20_token_address)?,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    LATEST_STAGE.save(deps.storage, &0)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { new_owner } => execute_update_config(deps, info, new_owner),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+        } => execute_register_merkle_root(deps, env, info, merkle_root, expiration),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Claim {
+            stage,
+            amount,
+            proof,
+        } => execute_claim(deps, env, info, stage, amount, proof),
+        ExecuteMsg::Burn { stage } => execute_burn(deps, env, info, stage),
+        ExecuteMsg::Withdraw { stage, address } => {
+            execute_withdraw(deps, env, info, stage, address)
+        }
+    }
+}
+
+fn ensure_owner(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    match &config.owner {
+        Some(owner) if owner == sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    config.owner = new_owner
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let owner = config.owner.map(String::from).unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("owner", owner))
+}
+
+/// Decodes a hex encoded sha256 hash
+fn decode_hash(hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut buf = [0u8; 32];
+    hex::decode_to_slice(hash, &mut buf).map_err(|_| ContractError::InvalidHash {})?;
+    Ok(buf)
+}
+
+pub fn execute_register_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let merkle_root = decode_hash(&merkle_root)?;
+    let stage = LATEST_STAGE
+        .load(deps.storage)?
+        .checked_add(1)
+        .ok_or(ContractError::TooManyStages {})?;
+    let expiration = expiration.unwrap_or_default();
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration { stage });
+    }
+
+    let new_stage = Stage {
+        merkle_root,
+        expiration,
+        funded: Uint128::zero(),
+        claimed: Uint128::zero(),
+    };
+    STAGES.save(deps.storage, stage, &new_stage)?;
+    LATEST_STAGE.save(deps.storage, &stage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_merkle_root")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("merkle_root", hex::encode(merkle_root)))
+}
+
+fn load_active_stage(deps: Deps, env: &Env, stage: u8) -> Result<Stage, ContractError> {
+    let airdrop = STAGES
+        .may_load(deps.storage, stage)?
+        .ok_or(ContractError::StageNotFound { stage })?;
+    if airdrop.expiration.is_expired(&env.block) {
+        return Err(ContractError::StageExpired { stage });
+    }
+    Ok(airdrop)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.cw20_token_address {
+        return Err(ContractError::InvalidToken {});
+    }
+
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    match msg {
+        ReceiveMsg::Fund { stage } => {
+            let mut airdrop = load_active_stage(deps.as_ref(), &env, stage)?;
+            airdrop.funded += wrapper.amount;
+            STAGES.save(deps.storage, stage, &airdrop)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "fund")
+                .add_attribute("stage", stage.to_string())
+                .add_attribute("sender", wrapper.sender)
+                .add_attribute("amount", wrapper.amount))
+        }
+    }
+}
+
+/// The leaf of the merkle tree for the given recipient
+pub fn leaf_hash(address: &str, amount: Uint128) -> [u8; 32] {
+    Sha256::digest(format!("{}{}", address, amount).as_bytes()).into()
+}
+
+/// Hashes two nodes of the merkle tree into their parent. Pairs are sorted before hashing,
+/// so proofs don't need to contain the position of the nodes.
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut airdrop = load_active_stage(deps.as_ref(), &env, stage)?;
+    if CLAIMED.has(deps.storage, (stage, &info.sender)) {
+        return Err(ContractError::Claimed {});
+    }
+
+    let root = proof
+        .iter()
+        .try_fold(leaf_hash(info.sender.as_str(), amount), |hash, node| {
+            Ok::<_, ContractError>(hash_pair(&hash, &decode_hash(node)?))
+        })?;
+    if root != airdrop.merkle_root {
+        return Err(ContractError::VerificationFailed {});
+    }
+
+    let available = airdrop.available();
+    if amount > available {
+        return Err(ContractError::InsufficientFunds { available });
+    }
+    airdrop.claimed += amount;
+    STAGES.save(deps.storage, stage, &airdrop)?;
+    CLAIMED.save(deps.storage, (stage, &info.sender), &true)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let transfer = WasmMsg::Execute {
+        contract_addr: config.cw20_token_address.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(transfer)
+        .add_attribute("action", "claim")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount))
+}
+
+/// Marks all unclaimed tokens of an expired stage as withdrawn and returns their amount
+fn take_unclaimed(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    stage: u8,
+) -> Result<(Config, Uint128), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let mut airdrop = STAGES
+        .may_load(deps.storage, stage)?
+        .ok_or(ContractError::StageNotFound { stage })?;
+    if !airdrop.expiration.is_expired(&env.block) {
+        return Err(ContractError::StageNotExpired { stage });
+    }
+    let amount = airdrop.available();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw { stage });
+    }
+    airdrop.funded = airdrop.claimed;
+    STAGES.save(deps.storage, stage, &airdrop)?;
+
+    Ok((config, amount))
+}
+
+pub fn execute_burn(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+) -> Result<Response, ContractError> {
+    let (config, amount) = take_unclaimed(deps, &env, &info, stage)?;
+    let burn = WasmMsg::Execute {
+        contract_addr: config.cw20_token_address.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(burn)
+        .add_attribute("action", "burn")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    address: String,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&address)?;
+    let (config, amount) = take_unclaimed(deps, &env, &info, stage)?;
+    let transfer = WasmMsg::Execute {
+        contract_addr: config.cw20_token_address.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(transfer)
+        .add_attribute("action", "withdraw")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("address", recipient)
+        .add_attribute("amount", amount))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
+        QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            to_binary(&query_is_claimed(deps, stage, address)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner.map(String::from),
+        cw20_token_address: config.cw20_token_address.into(),
+    })
+}
+
+pub fn query_merkle_root(deps: Deps, stage: u8) -> StdResult<MerkleRootResponse> {
+    let airdrop = STAGES.load(deps.storage, stage)?;
+    Ok(MerkleRootResponse {
+        stage,
+        merkle_root: hex::encode(airdrop.merkle_root),
+        expiration: airdrop.expiration,
+        funded: airdrop.funded,
+        claimed: airdrop.claimed,
+    })
+}
+
+pub fn query_latest_stage(deps: Deps) -> StdResult<LatestStageResponse> {
+    let latest_stage = LATEST_STAGE.load(deps.storage)?;
+    Ok(LatestStageResponse { latest_stage })
+}
+
+pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let is_claimed = CLAIMED.has(deps.storage, (stage, &address));
+    Ok(IsClaimedResponse { is_claimed })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{Empty, SubMsg};
+    use cw20::{BalanceResponse, Cw20Coin, TokenInfoResponse};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+    use super::*;
+
+    const TOKEN: &str = "token";
+    const OWNER: &str = "owner";
+
+    /// A merkle tree over three recipients: `root = H(H(alice, bob), carl)`
+    struct Tree {
+        root: String,
+        alice: Vec<String>,
+        bob: Vec<String>,
+        carl: Vec<String>,
+    }
+
+    fn build_tree(alice: &str, bob: &str, carl: &str) -> Tree {
+        let a = leaf_hash(alice, Uint128::new(100));
+        let b = leaf_hash(bob, Uint128::new(200));
+        let c = leaf_hash(carl, Uint128::new(300));
+        let ab = hash_pair(&a, &b);
+        Tree {
+            root: hex::encode(hash_pair(&ab, &c)),
+            alice: vec![hex::encode(b), hex::encode(c)],
+            bob: vec![hex::encode(a), hex::encode(c)],
+            carl: vec![hex::encode(ab)],
+        }
+    }
+
+    fn fund(deps: DepsMut, stage: u8, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: OWNER.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Fund { stage }).unwrap(),
+        });
+        execute(deps, mock_env(), mock_info(TOKEN, &[]), msg)
+    }
+
+    fn claim(
+        deps: DepsMut,
+        sender: &str,
+        stage: u8,
+        amount: u128,
+        proof: &[String],
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Claim {
+            stage,
+            amount: Uint128::new(amount),
+            proof: proof.to_vec(),
+        };
+        execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    fn setup(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            owner: Some(OWNER.to_string()),
+            cw20_token_address: TOKEN.to_string(),
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn register(deps: DepsMut, tree: &Tree, expiration: Option<Expiration>) {
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: tree.root.clone(),
+            expiration,
+        };
+        execute(deps, mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn register_stages() {
+        let mut deps = mock_dependencies();
+        let tree = build_tree("alice", "bob", "carl");
+        setup(deps.as_mut());
+
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap(),
+            ConfigResponse {
+                owner: Some(OWNER.to_string()),
+                cw20_token_address: TOKEN.to_string(),
+            }
+        );
+        assert_eq!(query_latest_stage(deps.as_ref()).unwrap().latest_stage, 0);
+
+        // only owner
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: tree.root.clone(),
+            expiration: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // must be a sha256 hash
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: "deadbeef".to_string(),
+            expiration: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHash {});
+
+        // cannot be expired already
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: tree.root.clone(),
+            expiration: Some(Expiration::AtHeight(mock_env().block.height)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExpiration { stage: 1 });
+
+        register(deps.as_mut(), &tree, None);
+        let expiration = Expiration::AtHeight(mock_env().block.height + 100);
+        register(deps.as_mut(), &tree, Some(expiration));
+        assert_eq!(query_latest_stage(deps.as_ref()).unwrap().latest_stage, 2);
+        assert_eq!(
+            query_merkle_root(deps.as_ref(), 2).unwrap(),
+            MerkleRootResponse {
+                stage: 2,
+                merkle_root: tree.root.clone(),
+                expiration,
+                funded: Uint128::zero(),
+                claimed: Uint128::zero(),
+            }
+        );
+
+        // stages are numbered with a u8
+        LATEST_STAGE.save(deps.as_mut().storage, &u8::MAX).unwrap();
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: tree.root,
+            expiration: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyStages {});
+    }
+
+    #[test]
+    fn fund_stage() {
+        let mut deps = mock_dependencies();
+        let tree = build_tree("alice", "bob", "carl");
+        setup(deps.as_mut());
+
+        let err = fund(deps.as_mut(), 1, 600).unwrap_err();
+        assert_eq!(err, ContractError::StageNotFound { stage: 1 });
+
+        register(deps.as_mut(), &tree, None);
+        fund(deps.as_mut(), 1, 400).unwrap();
+        fund(deps.as_mut(), 1, 200).unwrap();
+        assert_eq!(
+            query_merkle_root(deps.as_ref(), 1).unwrap().funded,
+            Uint128::new(600)
+        );
+
+        // other tokens are rejected
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: OWNER.to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::Fund { stage: 1 }).unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidToken {});
+    }
+
+    #[test]
+    fn claim_with_proof() {
+        let mut deps = mock_dependencies();
+        let tree = build_tree("alice", "bob", "carl");
+        setup(deps.as_mut());
+        register(deps.as_mut(), &tree, None);
+        fund(deps.as_mut(), 1, 300).unwrap();
+
+        // wrong amount, wrong proof or wrong sender
+        let err = claim(deps.as_mut(), "alice", 1, 200, &tree.alice).unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+        let err = claim(deps.as_mut(), "alice", 1, 100, &tree.bob).unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+        let err = claim(deps.as_mut(), "bob", 1, 100, &tree.alice).unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+        let err = claim(deps.as_mut(), "alice", 1, 100, &["zz".to_string()]).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHash {});
+
+        let res = claim(deps.as_mut(), "alice", 1, 100, &tree.alice).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "alice".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+        assert!(
+            query_is_claimed(deps.as_ref(), 1, "alice".to_string())
+                .unwrap()
+                .is_claimed
+        );
+        assert!(
+            !query_is_claimed(deps.as_ref(), 1, "bob".to_string())
+                .unwrap()
+                .is_claimed
+        );
+
+        let err = claim(deps.as_mut(), "alice", 1, 100, &tree.alice).unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+
+        // odd leaf with a shorter proof
+        let err = claim(deps.as_mut(), "carl", 1, 300, &tree.carl).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientFunds {
+                available: Uint128::new(200)
+            }
+        );
+        fund(deps.as_mut(), 1, 300).unwrap();
+        claim(deps.as_mut(), "carl", 1, 300, &tree.carl).unwrap();
+        claim(deps.as_mut(), "bob", 1, 200, &tree.bob).unwrap();
+
+        let stage = query_merkle_root(deps.as_ref(), 1).unwrap();
+        assert_eq!(stage.funded, Uint128::new(600));
+        assert_eq!(stage.claimed, Uint128::new(600));
+    }
+
+    #[test]
+    fn expiration_and_unclaimed_tokens() {
+        let mut deps = mock_dependencies();
+        let tree = build_tree("alice", "bob", "carl");
+        setup(deps.as_mut());
+        let expiration = Expiration::AtHeight(mock_env().block.height + 10);
+        register(deps.as_mut(), &tree, Some(expiration));
+        fund(deps.as_mut(), 1, 600).unwrap();
+        claim(deps.as_mut(), "bob", 1, 200, &tree.bob).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Burn { stage: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::StageNotExpired { stage: 1 });
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let msg = ExecuteMsg::Claim {
+            stage: 1,
+            amount: Uint128::new(100),
+            proof: tree.alice.clone(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StageExpired { stage: 1 });
+
+        let msg = ExecuteMsg::Withdraw {
+            stage: 1,
+            address: "treasury".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "treasury".to_string(),
+                    amount: Uint128::new(400),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+
+        // everything is gone
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Burn { stage: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToWithdraw { stage: 1 });
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies();
+        let tree = build_tree("alice", "bob", "carl");
+        setup(deps.as_mut());
+
+        let msg = ExecuteMsg::UpdateConfig {
+            new_owner: Some("alice".to_string()),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig { new_owner: None };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().owner, None);
+
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: tree.root,
+            expiration: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    fn contract_airdrop() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn airdrop_cw20() {
+        let owner = Addr::unchecked(OWNER);
+        let mut app = App::default();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let token = app
+            .instantiate_contract(
+                cw20_id,
+                owner.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Airdrop".to_string(),
+                    symbol: "DROP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: owner.to_string(),
+                        amount: Uint128::new(1000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+
+        let airdrop_id = app.store_code(contract_airdrop());
+        let airdrop = app
+            .instantiate_contract(
+                airdrop_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    owner: None,
+                    cw20_token_address: token.to_string(),
+                },
+                &[],
+                "airdrop",
+                None,
+            )
+            .unwrap();
+
+        let tree = build_tree("alice", "bob", "carl");
+        let expiration = Expiration::AtHeight(app.block_info().height + 10);
+        app.execute_contract(
+            owner.clone(),
+            airdrop.clone(),
+            &ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: tree.root.clone(),
+                expiration: Some(expiration),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: airdrop.to_string(),
+                amount: Uint128::new(600),
+                msg: to_binary(&ReceiveMsg::Fund { stage: 1 }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("carl"),
+            airdrop.clone(),
+            &ExecuteMsg::Claim {
+                stage: 1,
+                amount: Uint128::new(300),
+                proof: tree.carl,
+            },
+            &[],
+        )
+        .unwrap();
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: "carl".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(300));
+
+        // nobody else claims, so burn the rest once expired
+        app.update_block(|block| block.height += 10);
+        app.execute_contract(owner, airdrop, &ExecuteMsg::Burn { stage: 1 }, &[])
+            .unwrap();
+        let info: TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&token, &cw20::Cw20QueryMsg::TokenInfo {})
+            .unwrap();
+        assert_eq!(info.total_supply, Uint128::new(700));
+    }
+}
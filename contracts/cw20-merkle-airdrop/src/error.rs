@@ -0,0 +1,44 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid merkle root or proof element, expected a hex encoded sha256 hash")]
+    InvalidHash {},
+
+    #[error("Merkle proof verification failed")]
+    VerificationFailed {},
+
+    #[error("Already claimed")]
+    Claimed {},
+
+    #[error("Stage {stage} not found")]
+    StageNotFound { stage: u8 },
+
+    #[error("Stage {stage} expired")]
+    StageExpired { stage: u8 },
+
+    #[error("Stage {stage} has not expired yet")]
+    StageNotExpired { stage: u8 },
+
+    #[error("Stage {stage} cannot be created with an already expired expiration")]
+    InvalidExpiration { stage: u8 },
+
+    #[error("Only the airdropped cw20 token can fund a stage")]
+    InvalidToken {},
+
+    #[error("Insufficient funds in stage: {available} available")]
+    InsufficientFunds { available: Uint128 },
+
+    #[error("No unclaimed tokens left in stage {stage}")]
+    NothingToWithdraw { stage: u8 },
+
+    #[error("No more stages can be registered")]
+    TooManyStages {},
+}
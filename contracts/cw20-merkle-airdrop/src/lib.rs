@@ -0,0 +1,18 @@
+/*!
+This is an airdrop contract for cw20 tokens. Instead of sending thousands of transfers, the owner
+registers the merkle root of all `(address, amount)` pairs of an airdrop stage, and funds the stage
+by sending it the cw20 tokens. Every recipient then claims their own tokens by providing a merkle
+proof of inclusion.
+
+Stages can expire, after which the owner can burn or withdraw all unclaimed tokens.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-merkle-airdrop/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
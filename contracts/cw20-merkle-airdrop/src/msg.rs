@@ -0,0 +1,89 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Owner if none set to info.sender.
+    pub owner: Option<String>,
+    /// The cw20 token to be airdropped
+    pub cw20_token_address: String,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Transfers ownership to another address. Only callable by the owner.
+    /// Setting None renounces ownership, which makes it impossible to register new stages
+    /// or to recover unclaimed tokens.
+    UpdateConfig { new_owner: Option<String> },
+    /// Registers a new airdrop stage with the given merkle root (hex encoded sha256 hash).
+    /// Only callable by the owner. Stages are numbered from 1.
+    RegisterMerkleRoot {
+        merkle_root: String,
+        expiration: Option<Expiration>,
+    },
+    /// Funds a stage with the airdropped cw20 token. The only valid message is `ReceiveMsg::Fund`.
+    Receive(Cw20ReceiveMsg),
+    /// Claims the airdrop of the sender. `proof` is the list of hex encoded sibling hashes
+    /// on the path from the `(sender, amount)` leaf to the merkle root of the stage.
+    Claim {
+        stage: u8,
+        amount: Uint128,
+        proof: Vec<String>,
+    },
+    /// Burns all unclaimed tokens of an expired stage. Only callable by the owner.
+    Burn { stage: u8 },
+    /// Sends all unclaimed tokens of an expired stage to `address`. Only callable by the owner.
+    Withdraw { stage: u8, address: String },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Deposits the received tokens into the given stage
+    Fund { stage: u8 },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the merkle root and the state of the given stage
+    #[returns(MerkleRootResponse)]
+    MerkleRoot { stage: u8 },
+    /// Returns the number of the latest registered stage, 0 if there is none
+    #[returns(LatestStageResponse)]
+    LatestStage {},
+    /// Returns whether the address has already claimed its airdrop in the given stage
+    #[returns(IsClaimedResponse)]
+    IsClaimed { stage: u8, address: String },
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub owner: Option<String>,
+    pub cw20_token_address: String,
+}
+
+#[cw_serde]
+pub struct MerkleRootResponse {
+    pub stage: u8,
+    /// Hex encoded sha256 hash
+    pub merkle_root: String,
+    pub expiration: Expiration,
+    /// Total tokens deposited for this stage
+    pub funded: Uint128,
+    /// Total tokens claimed from this stage
+    pub claimed: Uint128,
+}
+
+#[cw_serde]
+pub struct LatestStageResponse {
+    pub latest_stage: u8,
+}
+
+#[cw_serde]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
@@ -0,0 +1,39 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct Config {
+    /// Owner can register new stages and burn or withdraw unclaimed tokens.
+    /// If None, the airdrop is fixed forever.
+    pub owner: Option<Addr>,
+    /// The cw20 token being airdropped
+    pub cw20_token_address: Addr,
+}
+
+#[cw_serde]
+pub struct Stage {
+    /// sha256 hash, the root of the merkle tree of all `(address, amount)` leaves
+    pub merkle_root: [u8; 32],
+    /// After expiration, claims are no longer possible and the owner can take back the
+    /// unclaimed tokens. Never expires if not set.
+    pub expiration: Expiration,
+    /// Tokens deposited for this stage
+    pub funded: Uint128,
+    /// Tokens claimed from this stage
+    pub claimed: Uint128,
+}
+
+impl Stage {
+    /// Tokens that are deposited and not claimed yet
+    pub fn available(&self) -> Uint128 {
+        self.funded - self.claimed
+    }
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const LATEST_STAGE: Item<u8> = Item::new("latest_stage");
+pub const STAGES: Map<u8, Stage> = Map::new("stages");
+/// Marks addresses which have claimed their tokens, by stage
+pub const CLAIMED: Map<(u8, &Addr), bool> = Map::new("claimed");
@@ -0,0 +1,827 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal256, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Storage, Uint128, Uint256, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, UncheckedDenom};
+use cw20_base::allowances::{
+    execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
+    execute_transfer_from, query_allowance,
+};
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_remove_metadata, execute_send, execute_set_metadata,
+    execute_transfer, execute_update_marketing, execute_update_minter, execute_upload_logo,
+    query_balance, query_download_logo, query_marketing_info, query_metadata, query_minter,
+    query_token_info,
+};
+use cw20_base::enumerable::{
+    query_all_accounts, query_all_metadata, query_owner_allowances, query_spender_allowances,
+};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use cw_utils::PaymentError;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, PendingRewardsResponse, QueryMsg, ReceiveMsg,
+    RewardInfo,
+};
+use crate::state::{denom_key, Config, HolderRewards, CONFIG, HOLDER_REWARDS, REWARD_INDEX};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-rewards";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Every balance change touches all reward denoms, so keep them bounded
+const MAX_REWARD_DENOMS: usize = 10;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.reward_denoms.is_empty() || msg.reward_denoms.len() > MAX_REWARD_DENOMS {
+        return Err(ContractError::InvalidRewardDenoms {
+            max: MAX_REWARD_DENOMS,
+        });
+    }
+    let mut reward_denoms: Vec<Denom> = Vec::with_capacity(msg.reward_denoms.len());
+    for denom in msg.reward_denoms {
+        // cannot query ourselves during instantiation, and it would not make sense anyway
+        if matches!(&denom, UncheckedDenom::Cw20(addr) if addr == env.contract.address.as_str()) {
+            return Err(ContractError::SelfRewardDenom {});
+        }
+        let denom = denom.into_checked(deps.as_ref())?;
+        if reward_denoms.contains(&denom) {
+            return Err(ContractError::DuplicateRewardDenom {});
+        }
+        reward_denoms.push(denom);
+    }
+
+    let base_msg = cw20_base::msg::InstantiateMsg {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        initial_balances: msg.initial_balances,
+        mint: msg.mint,
+        marketing: msg.marketing,
+    };
+    cw20_base::contract::instantiate(deps.branch(), env, info, base_msg)?;
+
+    // overwrite the version set by cw20-base
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(deps.storage, &Config { reward_denoms })?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    match msg {
+        ExecuteMsg::DistributeRewards {} => execute_distribute_rewards(deps, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, info, msg),
+        ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, info),
+
+        // these all come from cw20-base, but every account whose balance is about to change
+        // needs to have its rewards updated first
+        ExecuteMsg::Transfer { recipient, amount } => {
+            let recipient_addr = api.addr_validate(&recipient)?;
+            update_rewards(deps.storage, &[&info.sender, &recipient_addr])?;
+            Ok(execute_transfer(deps, env, info, recipient, amount)?)
+        }
+        ExecuteMsg::Burn { amount } => {
+            update_rewards(deps.storage, &[&info.sender])?;
+            Ok(execute_burn(deps, env, info, amount)?)
+        }
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            let contract_addr = api.addr_validate(&contract)?;
+            update_rewards(deps.storage, &[&info.sender, &contract_addr])?;
+            Ok(execute_send(deps, env, info, contract, amount, msg)?)
+        }
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => {
+            let owner_addr = api.addr_validate(&owner)?;
+            let recipient_addr = api.addr_validate(&recipient)?;
+            update_rewards(deps.storage, &[&owner_addr, &recipient_addr])?;
+            Ok(execute_transfer_from(
+                deps, env, info, owner, recipient, amount,
+            )?)
+        }
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => {
+            let owner_addr = api.addr_validate(&owner)?;
+            let contract_addr = api.addr_validate(&contract)?;
+            update_rewards(deps.storage, &[&owner_addr, &contract_addr])?;
+            Ok(execute_send_from(
+                deps, env, info, owner, contract, amount, msg,
+            )?)
+        }
+        ExecuteMsg::BurnFrom { owner, amount } => {
+            let owner_addr = api.addr_validate(&owner)?;
+            update_rewards(deps.storage, &[&owner_addr])?;
+            Ok(execute_burn_from(deps, env, info, owner, amount)?)
+        }
+        ExecuteMsg::Mint { recipient, amount } => {
+            let recipient_addr = api.addr_validate(&recipient)?;
+            update_rewards(deps.storage, &[&recipient_addr])?;
+            Ok(execute_mint(deps, env, info, recipient, amount)?)
+        }
+        ExecuteMsg::UpdateMinter { new_minter } => {
+            Ok(execute_update_minter(deps, env, info, new_minter)?)
+        }
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => Ok(execute_update_marketing(
+            deps,
+            env,
+            info,
+            project,
+            description,
+            marketing,
+        )?),
+        ExecuteMsg::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
+        ExecuteMsg::SetMetadata { key, value } => {
+            Ok(execute_set_metadata(deps, env, info, key, value)?)
+        }
+        ExecuteMsg::RemoveMetadata { key } => Ok(execute_remove_metadata(deps, env, info, key)?),
+    }
+}
+
+/// Returns the rewards of `holder` in the denom stored under `key`, including everything
+/// accrued since the holder was last updated (up to the given global `index`)
+fn accrued_rewards(
+    storage: &dyn Storage,
+    holder: &Addr,
+    key: &str,
+    index: Decimal256,
+) -> StdResult<HolderRewards> {
+    let mut rewards = HOLDER_REWARDS
+        .may_load(storage, (holder, key))?
+        .unwrap_or_default();
+    let balance = BALANCES.may_load(storage, holder)?.unwrap_or_default();
+    let earned = Uint256::from(balance) * (index - rewards.index);
+    rewards.pending += Uint128::try_from(earned)?;
+    rewards.index = index;
+    Ok(rewards)
+}
+
+/// Brings the rewards of all `holders` up to date. This must be called before changing
+/// their balances, so that everything accrued with the old balance is accounted for.
+pub fn update_rewards(storage: &mut dyn Storage, holders: &[&Addr]) -> StdResult<()> {
+    let cfg = CONFIG.load(storage)?;
+    for denom in cfg.reward_denoms.iter() {
+        let key = denom_key(denom);
+        let index = REWARD_INDEX.may_load(storage, &key)?.unwrap_or_default();
+        for holder in holders {
+            let rewards = accrued_rewards(storage, holder, &key, index)?;
+            HOLDER_REWARDS.save(storage, (holder, &key), &rewards)?;
+        }
+    }
+    Ok(())
+}
+
+/// Increases the global reward index of `denom` so that `amount` is split among all tokens
+fn distribute(storage: &mut dyn Storage, denom: &Denom, amount: Uint128) -> StdResult<()> {
+    let total_supply = TOKEN_INFO.load(storage)?.total_supply;
+    let key = denom_key(denom);
+    REWARD_INDEX.update(storage, &key, |index| -> StdResult<_> {
+        Ok(index.unwrap_or_default() + Decimal256::from_ratio(amount, total_supply))
+    })?;
+    Ok(())
+}
+
+/// Fails if rewards in `denom` cannot be distributed right now
+fn ensure_can_distribute(storage: &dyn Storage, denom: &Denom) -> Result<(), ContractError> {
+    let cfg = CONFIG.load(storage)?;
+    if !cfg.reward_denoms.contains(denom) {
+        return Err(ContractError::UnsupportedDenom {});
+    }
+    if TOKEN_INFO.load(storage)?.total_supply.is_zero() {
+        return Err(ContractError::NoSupply {});
+    }
+    Ok(())
+}
+
+pub fn execute_distribute_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "distribute_rewards")
+        .add_attribute("sender", &info.sender);
+    // check everything first, so we never leave a partial distribution behind
+    let rewards: Vec<(Denom, Uint128)> = info
+        .funds
+        .into_iter()
+        .map(|coin| (Denom::Native(coin.denom), coin.amount))
+        .collect();
+    for (denom, _) in rewards.iter() {
+        ensure_can_distribute(deps.storage, denom)?;
+    }
+    for (denom, amount) in rewards {
+        distribute(deps.storage, &denom, amount)?;
+        res = res.add_attribute("reward", format!("{}{}", amount, denom_key(&denom)));
+    }
+    Ok(res)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    match msg {
+        ReceiveMsg::DistributeRewards {} => {
+            let denom = Denom::Cw20(info.sender);
+            ensure_can_distribute(deps.storage, &denom)?;
+            distribute(deps.storage, &denom, wrapper.amount)?;
+
+            let res = Response::new()
+                .add_attribute("action", "distribute_rewards")
+                .add_attribute("sender", wrapper.sender)
+                .add_attribute("reward", format!("{}{}", wrapper.amount, denom_key(&denom)));
+            Ok(res)
+        }
+    }
+}
+
+pub fn execute_claim_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    update_rewards(deps.storage, &[&info.sender])?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for denom in cfg.reward_denoms {
+        let key = denom_key(&denom);
+        let mut rewards = HOLDER_REWARDS.load(deps.storage, (&info.sender, &key))?;
+        if rewards.pending.is_zero() {
+            continue;
+        }
+        messages.push(send_rewards(&info.sender, denom, rewards.pending)?);
+        rewards.pending = Uint128::zero();
+        HOLDER_REWARDS.save(deps.storage, (&info.sender, &key), &rewards)?;
+    }
+
+    if messages.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let res = Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("sender", info.sender);
+    Ok(res)
+}
+
+fn send_rewards(recipient: &Addr, denom: Denom, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.into(),
+            amount: vec![Coin { denom, amount }],
+        }
+        .into(),
+        Denom::Cw20(addr) => WasmMsg::Execute {
+            contract_addr: addr.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingRewards { address } => to_binary(&query_pending_rewards(deps, address)?),
+
+        // inherited from cw20-base
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::Metadata { key } => to_binary(&query_metadata(deps, key)?),
+        QueryMsg::AllMetadata { start_after, limit } => {
+            to_binary(&query_all_metadata(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        reward_denoms: cfg.reward_denoms,
+    })
+}
+
+pub fn query_pending_rewards(deps: Deps, address: String) -> StdResult<PendingRewardsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let rewards = cfg
+        .reward_denoms
+        .into_iter()
+        .map(|denom| {
+            let key = denom_key(&denom);
+            let index = REWARD_INDEX
+                .may_load(deps.storage, &key)?
+                .unwrap_or_default();
+            let rewards = accrued_rewards(deps.storage, &address, &key, index)?;
+            Ok(RewardInfo {
+                denom,
+                amount: rewards.pending,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PendingRewardsResponse { rewards })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, Empty, SubMsg};
+    use cw20::{BalanceResponse, Cw20Coin};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+    use super::*;
+
+    const REWARD: &str = "ureward";
+
+    fn do_instantiate(deps: DepsMut, balances: &[(&str, u128)]) {
+        let msg = InstantiateMsg {
+            name: "Revenue Share".to_string(),
+            symbol: "SHARE".to_string(),
+            decimals: 6,
+            initial_balances: balances
+                .iter()
+                .map(|(address, amount)| Cw20Coin {
+                    address: address.to_string(),
+                    amount: Uint128::new(*amount),
+                })
+                .collect(),
+            mint: Some(cw20::MinterResponse {
+                minter: "minter".to_string(),
+                cap: None,
+            }),
+            marketing: None,
+            reward_denoms: vec![UncheckedDenom::Native(REWARD.to_string())],
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn pending(deps: Deps, address: &str) -> u128 {
+        query_pending_rewards(deps, address.to_string())
+            .unwrap()
+            .rewards[0]
+            .amount
+            .u128()
+    }
+
+    fn distribute_native(deps: DepsMut, amount: u128) {
+        let info = mock_info("payer", &coins(amount, REWARD));
+        execute(deps, mock_env(), info, ExecuteMsg::DistributeRewards {}).unwrap();
+    }
+
+    #[test]
+    fn instantiate_validates_denoms() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name: "Revenue Share".to_string(),
+            symbol: "SHARE".to_string(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: None,
+            marketing: None,
+            reward_denoms: vec![],
+        };
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRewardDenoms {
+                max: MAX_REWARD_DENOMS
+            }
+        );
+
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InstantiateMsg {
+                reward_denoms: vec![
+                    UncheckedDenom::Native(REWARD.to_string()),
+                    UncheckedDenom::Native(REWARD.to_string()),
+                ],
+                ..msg.clone()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::DuplicateRewardDenom {});
+
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InstantiateMsg {
+                reward_denoms: vec![UncheckedDenom::Cw20(
+                    mock_env().contract.address.to_string(),
+                )],
+                ..msg
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SelfRewardDenom {});
+    }
+
+    #[test]
+    fn rewards_split_by_balance() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), &[("alice", 300), ("bob", 100)]);
+
+        distribute_native(deps.as_mut(), 1000);
+        assert_eq!(pending(deps.as_ref(), "alice"), 750);
+        assert_eq!(pending(deps.as_ref(), "bob"), 250);
+        assert_eq!(pending(deps.as_ref(), "carl"), 0);
+
+        // transfers only affect future rewards
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::Transfer {
+            recipient: "carl".to_string(),
+            amount: Uint128::new(200),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(pending(deps.as_ref(), "alice"), 750);
+        assert_eq!(pending(deps.as_ref(), "carl"), 0);
+
+        distribute_native(deps.as_mut(), 400);
+        assert_eq!(pending(deps.as_ref(), "alice"), 850);
+        assert_eq!(pending(deps.as_ref(), "bob"), 350);
+        assert_eq!(pending(deps.as_ref(), "carl"), 200);
+
+        // minting and burning as well
+        let info = mock_info("minter", &[]);
+        let msg = ExecuteMsg::Mint {
+            recipient: "bob".to_string(),
+            amount: Uint128::new(300),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("carl", &[]);
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(200),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // alice 100, bob 400, carl 0
+        distribute_native(deps.as_mut(), 50);
+        assert_eq!(pending(deps.as_ref(), "alice"), 860);
+        assert_eq!(pending(deps.as_ref(), "bob"), 390);
+        assert_eq!(pending(deps.as_ref(), "carl"), 200);
+    }
+
+    #[test]
+    fn allowance_moves_update_owner() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), &[("alice", 100)]);
+
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: "spender".to_string(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        distribute_native(deps.as_mut(), 100);
+
+        let info = mock_info("spender", &[]);
+        let msg = ExecuteMsg::TransferFrom {
+            owner: "alice".to_string(),
+            recipient: "bob".to_string(),
+            amount: Uint128::new(50),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::BurnFrom {
+            owner: "alice".to_string(),
+            amount: Uint128::new(25),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // alice 25, bob 50
+        distribute_native(deps.as_mut(), 75);
+        assert_eq!(pending(deps.as_ref(), "alice"), 125);
+        assert_eq!(pending(deps.as_ref(), "bob"), 50);
+    }
+
+    #[test]
+    fn distribute_errors() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), &[]);
+
+        // nothing to distribute to yet
+        let info = mock_info("payer", &coins(100, REWARD));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DistributeRewards {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoSupply {});
+
+        let info = mock_info("minter", &[]);
+        let msg = ExecuteMsg::Mint {
+            recipient: "alice".to_string(),
+            amount: Uint128::new(10),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("payer", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DistributeRewards {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+        let info = mock_info("payer", &[coin(100, REWARD), coin(100, "uother")]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DistributeRewards {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedDenom {});
+
+        // unknown cw20 token
+        let info = mock_info("some-token", &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "payer".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::DistributeRewards {}).unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedDenom {});
+
+        assert_eq!(pending(deps.as_ref(), "alice"), 0);
+    }
+
+    #[test]
+    fn claim_native_rewards() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), &[("alice", 3), ("bob", 1)]);
+
+        let info = mock_info("alice", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        distribute_native(deps.as_mut(), 10);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        // rounded down
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(7, REWARD),
+            })]
+        );
+        assert_eq!(pending(deps.as_ref(), "alice"), 0);
+        assert_eq!(pending(deps.as_ref(), "bob"), 2);
+
+        let err =
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRewards {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+    }
+
+    fn contract_rewards() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn cw20_and_native_rewards() {
+        let owner = Addr::unchecked("owner");
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &owner, coins(1000, REWARD))
+                .unwrap();
+        });
+
+        let cw20_id = app.store_code(contract_cw20());
+        let usd = app
+            .instantiate_contract(
+                cw20_id,
+                owner.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Stable".to_string(),
+                    symbol: "USD".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: owner.to_string(),
+                        amount: Uint128::new(1000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "usd",
+                None,
+            )
+            .unwrap();
+
+        let rewards_id = app.store_code(contract_rewards());
+        let share = app
+            .instantiate_contract(
+                rewards_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    name: "Revenue Share".to_string(),
+                    symbol: "SHARE".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![
+                        Cw20Coin {
+                            address: alice.to_string(),
+                            amount: Uint128::new(60),
+                        },
+                        Cw20Coin {
+                            address: bob.to_string(),
+                            amount: Uint128::new(40),
+                        },
+                    ],
+                    mint: None,
+                    marketing: None,
+                    reward_denoms: vec![
+                        UncheckedDenom::Native(REWARD.to_string()),
+                        UncheckedDenom::Cw20(usd.to_string()),
+                    ],
+                },
+                &[],
+                "share",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            owner.clone(),
+            share.clone(),
+            &ExecuteMsg::DistributeRewards {},
+            &coins(500, REWARD),
+        )
+        .unwrap();
+        app.execute_contract(
+            owner,
+            usd.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: share.to_string(),
+                amount: Uint128::new(200),
+                msg: to_binary(&ReceiveMsg::DistributeRewards {}).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let pending: PendingRewardsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &share,
+                &QueryMsg::PendingRewards {
+                    address: bob.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            pending.rewards,
+            vec![
+                RewardInfo {
+                    denom: Denom::Native(REWARD.to_string()),
+                    amount: Uint128::new(200),
+                },
+                RewardInfo {
+                    denom: Denom::Cw20(usd.clone()),
+                    amount: Uint128::new(80),
+                },
+            ]
+        );
+
+        app.execute_contract(
+            alice.clone(),
+            share.clone(),
+            &ExecuteMsg::ClaimRewards {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            app.wrap().query_balance(&alice, REWARD).unwrap(),
+            coin(300, REWARD)
+        );
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &usd,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: alice.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(120));
+    }
+}
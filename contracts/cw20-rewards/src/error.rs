@@ -0,0 +1,33 @@
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw20_base::ContractError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("At least one and at most {max} reward denoms must be configured")]
+    InvalidRewardDenoms { max: usize },
+
+    #[error("Duplicate reward denom")]
+    DuplicateRewardDenom {},
+
+    #[error("Token itself cannot be used as reward denom")]
+    SelfRewardDenom {},
+
+    #[error("Denom is not accepted as reward")]
+    UnsupportedDenom {},
+
+    #[error("Cannot distribute rewards when there are no tokens")]
+    NoSupply {},
+
+    #[error("No rewards to claim")]
+    NothingToClaim {},
+}
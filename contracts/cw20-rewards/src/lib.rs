@@ -0,0 +1,22 @@
+/*!
+This is a cw20 token which distributes rewards (dividends) to its holders, pro rata to their
+balances. Anyone can distribute rewards in one of the configured native or cw20 denoms, and each
+holder can claim their share at any time.
+
+It uses a reward-per-token accumulator: distributing rewards only increases a global index,
+and each holder's accrued rewards are updated lazily, right before their balance changes.
+This keeps every operation O(1) in the number of holders.
+
+All the cw20 logic is imported from
+[cw20-base](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-base/README.md).
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-rewards/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,178 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Uint128};
+use cw20::{Cw20Coin, Cw20ReceiveMsg, Denom, Expiration, Logo, MinterResponse, UncheckedDenom};
+
+pub use cw20_base::msg::InstantiateMarketingInfo;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// Native and cw20 denoms which can be distributed as rewards
+    pub reward_denoms: Vec<UncheckedDenom>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Distributes all native tokens sent along with this message to the current holders,
+    /// pro rata to their balances. Only configured reward denoms are accepted.
+    DistributeRewards {},
+    /// Distributes cw20 rewards. The only valid message is `ReceiveMsg::DistributeRewards {}`.
+    Receive(Cw20ReceiveMsg),
+    /// Sends all the rewards accrued by the sender (in all reward denoms) to the sender
+    ClaimRewards {},
+
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Destroys tokens forever if `env.sender` has sufficient pre-approval.
+    BurnFrom { owner: String, amount: Uint128 },
+    /// If authorized, creates amount new tokens and adds to the recipient balance.
+    Mint { recipient: String, amount: Uint128 },
+    /// The current minter may set a new minter. Setting the minter to None will remove the
+    /// token's minter forever.
+    UpdateMinter { new_minter: Option<String> },
+    /// If authorized, updates marketing metadata.
+    /// Setting None/null for any of these will leave it unchanged.
+    /// Setting Some("") will clear this field on the contract storage
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, PNG, WebP or JPEG
+    /// for the token
+    UploadLogo(Logo),
+    /// If authorized, sets an arbitrary metadata entry
+    SetMetadata { key: String, value: String },
+    /// If authorized, removes the metadata entry stored under this key
+    RemoveMetadata { key: String },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Distributes the received cw20 tokens to the current holders
+    DistributeRewards {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the denoms which can be distributed as rewards
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the rewards accrued by the given address and not claimed yet, in all reward denoms
+    #[returns(PendingRewardsResponse)]
+    PendingRewards { address: String },
+
+    /// Returns the current balance of the given address, 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    /// Returns who can mint and the hard cap on maximum tokens after minting.
+    #[returns(Option<cw20::MinterResponse>)]
+    Minter {},
+    /// Returns how much spender can use from owner account, 0 if unset.
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns more metadata on the contract to display in the client:
+    /// - description, logo, project url, etc.
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for
+    /// this contract.
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    #[returns(cw20::MetadataResponse)]
+    Metadata { key: String },
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    #[returns(cw20::AllMetadataResponse)]
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub reward_denoms: Vec<Denom>,
+}
+
+#[cw_serde]
+pub struct RewardInfo {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    /// One entry per reward denom, in the configured order
+    pub rewards: Vec<RewardInfo>,
+}
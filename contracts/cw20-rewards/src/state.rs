@@ -0,0 +1,34 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal256, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct Config {
+    /// All denoms which can be distributed as rewards
+    pub reward_denoms: Vec<Denom>,
+}
+
+/// Rewards accrued by a single holder in a single denom
+#[cw_serde]
+#[derive(Default)]
+pub struct HolderRewards {
+    /// Value of the global reward index when the holder was last updated
+    pub index: Decimal256,
+    /// Rewards accrued until then, not yet claimed
+    pub pending: Uint128,
+}
+
+pub const CONFIG: Item<Config> = Item::new("rewards_config");
+/// Total rewards distributed per token, by denom key
+pub const REWARD_INDEX: Map<&str, Decimal256> = Map::new("reward_index");
+/// Rewards accrued per holder, by holder and denom key
+pub const HOLDER_REWARDS: Map<(&Addr, &str), HolderRewards> = Map::new("holder_rewards");
+
+/// Key under which rewards of this denom are stored
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => format!("native:{}", denom),
+        Denom::Cw20(addr) => format!("cw20:{}", addr),
+    }
+}
@@ -0,0 +1,960 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Order, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, MinterResponse};
+use cw20_base::allowances::{
+    execute_decrease_allowance, execute_increase_allowance, execute_send_from,
+    execute_transfer_from, query_allowance,
+};
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_remove_metadata, execute_send, execute_set_metadata,
+    execute_transfer, execute_update_marketing, execute_upload_logo, query_balance,
+    query_download_logo, query_marketing_info, query_metadata, query_token_info,
+};
+use cw20_base::enumerable::{
+    query_all_accounts, query_all_metadata, query_owner_allowances, query_spender_allowances,
+};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use cw4::{
+    Member, MemberChangedHookMsg, MemberDiff, MemberListResponse, MemberResponse,
+    TotalWeightResponse,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{maybe_addr, must_pay, nonpayable};
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, StakingInfoResponse};
+use crate::state::{Config, ADMIN, BONDED, CLAIMS, CONFIG, HOOKS, MEMBERS, TOTAL};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-staking";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Shares of the first bond which are kept by the contract itself and can never be unbonded.
+/// Otherwise the first bonder could raise the exchange rate of a tiny bond with `AddRewards`,
+/// so later bonds lose most of their value to rounding.
+pub const LOCKED_SHARES: Uint128 = Uint128::new(1000);
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.tokens_per_weight.is_zero() {
+        return Err(ContractError::ZeroTokensPerWeight {});
+    }
+    let config = Config {
+        denom: msg.denom.into_checked(deps.as_ref())?,
+        tokens_per_weight: msg.tokens_per_weight,
+        unbonding_period: msg.unbonding_period,
+    };
+
+    // the contract is the only minter, issuing shares only when receiving bonded tokens
+    let base_msg = cw20_base::msg::InstantiateMsg {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: env.contract.address.to_string(),
+            cap: None,
+        }),
+        marketing: msg.marketing,
+    };
+    let height = env.block.height;
+    cw20_base::contract::instantiate(deps.branch(), env, info, base_msg)?;
+
+    // overwrite the version set by cw20-base
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let api = deps.api;
+    ADMIN.set(deps.branch(), maybe_addr(api, msg.admin)?)?;
+    CONFIG.save(deps.storage, &config)?;
+    BONDED.save(deps.storage, &Uint128::zero())?;
+    TOTAL.save(deps.storage, &0, height)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    let height = env.block.height;
+    let self_addr = env.contract.address.clone();
+    match msg {
+        ExecuteMsg::Bond {} => {
+            let amount = native_payment(deps.storage, &info)?;
+            execute_bond(deps, env, info.sender, amount)
+        }
+        ExecuteMsg::Unbond { amount } => execute_unbond(deps, env, info, amount),
+        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::AddRewards {} => {
+            let amount = native_payment(deps.storage, &info)?;
+            execute_add_rewards(deps, info.sender, amount)
+        }
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+
+        ExecuteMsg::UpdateAdmin { admin } => {
+            Ok(ADMIN.execute_update_admin(deps, info, maybe_addr(api, admin)?)?)
+        }
+        ExecuteMsg::AddHook { addr } => {
+            Ok(HOOKS.execute_add_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
+        }
+        ExecuteMsg::RemoveHook { addr } => {
+            Ok(HOOKS.execute_remove_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
+        }
+
+        // these all come from cw20-base, but every account whose share balance changed
+        // needs to have its group weight updated afterwards
+        ExecuteMsg::Transfer { recipient, amount } => {
+            let recipient_addr = api.addr_validate(&recipient)?;
+            let sender = info.sender.clone();
+            let res = execute_transfer(deps.branch(), env, info, recipient, amount)?;
+            let hooks = update_membership(
+                deps.storage,
+                &self_addr,
+                &[&sender, &recipient_addr],
+                height,
+            )?;
+            Ok(res.add_submessages(hooks))
+        }
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            let contract_addr = api.addr_validate(&contract)?;
+            let sender = info.sender.clone();
+            let res = execute_send(deps.branch(), env, info, contract, amount, msg)?;
+            let hooks =
+                update_membership(deps.storage, &self_addr, &[&sender, &contract_addr], height)?;
+            Ok(res.add_submessages(hooks))
+        }
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => {
+            let owner_addr = api.addr_validate(&owner)?;
+            let recipient_addr = api.addr_validate(&recipient)?;
+            let res = execute_transfer_from(deps.branch(), env, info, owner, recipient, amount)?;
+            let hooks = update_membership(
+                deps.storage,
+                &self_addr,
+                &[&owner_addr, &recipient_addr],
+                height,
+            )?;
+            Ok(res.add_submessages(hooks))
+        }
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => {
+            let owner_addr = api.addr_validate(&owner)?;
+            let contract_addr = api.addr_validate(&contract)?;
+            let res = execute_send_from(deps.branch(), env, info, owner, contract, amount, msg)?;
+            let hooks = update_membership(
+                deps.storage,
+                &self_addr,
+                &[&owner_addr, &contract_addr],
+                height,
+            )?;
+            Ok(res.add_submessages(hooks))
+        }
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => Ok(execute_update_marketing(
+            deps,
+            env,
+            info,
+            project,
+            description,
+            marketing,
+        )?),
+        ExecuteMsg::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
+        ExecuteMsg::SetMetadata { key, value } => {
+            Ok(execute_set_metadata(deps, env, info, key, value)?)
+        }
+        ExecuteMsg::RemoveMetadata { key } => Ok(execute_remove_metadata(deps, env, info, key)?),
+    }
+}
+
+/// Returns the amount of native staking tokens sent, error if the bonded token is a cw20
+fn native_payment(storage: &dyn Storage, info: &MessageInfo) -> Result<Uint128, ContractError> {
+    match CONFIG.load(storage)?.denom {
+        Denom::Native(denom) => Ok(must_pay(info, &denom)?),
+        Denom::Cw20(_) => Err(ContractError::InvalidDenom {}),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    // This cannot be fully trusted (the cw20 contract can fake it), so only use it for actions
+    // in the address's favor (like paying/bonding tokens, not withdrawls)
+    match CONFIG.load(deps.storage)?.denom {
+        Denom::Cw20(addr) if addr == info.sender => {}
+        _ => return Err(ContractError::InvalidDenom {}),
+    }
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match msg {
+        ReceiveMsg::Bond {} => execute_bond(deps, env, sender, wrapper.amount),
+        ReceiveMsg::AddRewards {} => execute_add_rewards(deps, sender, wrapper.amount),
+    }
+}
+
+pub fn execute_bond(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // issue shares at the current exchange rate, rounding down in favor of existing holders
+    let bonded = BONDED.load(deps.storage)?;
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let mut shares = if supply.is_zero() || bonded.is_zero() {
+        amount
+    } else {
+        amount.multiply_ratio(supply, bonded)
+    };
+    let locked = if supply.is_zero() {
+        shares.min(LOCKED_SHARES)
+    } else {
+        Uint128::zero()
+    };
+    shares -= locked;
+    if shares.is_zero() {
+        return Err(ContractError::BondTooSmall {});
+    }
+    BONDED.save(deps.storage, &(bonded + amount))?;
+
+    // call into cw20-base to mint the shares, call as self as no one else is allowed
+    let height = env.block.height;
+    let self_addr = env.contract.address.clone();
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    if !locked.is_zero() {
+        let contract = env.contract.address.to_string();
+        execute_mint(
+            deps.branch(),
+            env.clone(),
+            sub_info.clone(),
+            contract,
+            locked,
+        )?;
+    }
+    execute_mint(deps.branch(), env, sub_info, sender.to_string(), shares)?;
+    let hooks = update_membership(deps.storage, &self_addr, &[&sender], height)?;
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "bond")
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares)
+        .add_attribute("sender", sender))
+}
+
+pub fn execute_unbond(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+
+    // calculate the tokens released, rounding down in favor of the remaining holders
+    let bonded = BONDED.load(deps.storage)?;
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let tokens = if supply.is_zero() {
+        Uint128::zero()
+    } else {
+        amount.multiply_ratio(bonded, supply)
+    };
+    if tokens.is_zero() {
+        return Err(ContractError::UnbondTooSmall {});
+    }
+
+    // burn from the caller, this ensures there are shares to cover this
+    let height = env.block.height;
+    let self_addr = env.contract.address.clone();
+    execute_burn(deps.branch(), env.clone(), info.clone(), amount)?;
+    BONDED.save(deps.storage, &(bonded - tokens))?;
+
+    // provide them a claim
+    let cfg = CONFIG.load(deps.storage)?;
+    CLAIMS.create_claim(
+        deps.storage,
+        &info.sender,
+        tokens,
+        cfg.unbonding_period.after(&env.block),
+    )?;
+    let hooks = update_membership(deps.storage, &self_addr, &[&info.sender], height)?;
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "unbond")
+        .add_attribute("shares", amount)
+        .add_attribute("amount", tokens)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_add_rewards(
+    deps: DepsMut,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // with no shares, the rewards would go to whoever bonds next
+    if TOKEN_INFO.load(deps.storage)?.total_supply.is_zero() {
+        return Err(ContractError::NoShares {});
+    }
+    BONDED.update(deps.storage, |bonded| -> StdResult<_> {
+        Ok(bonded + amount)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_rewards")
+        .add_attribute("amount", amount)
+        .add_attribute("sender", sender))
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
+    if release.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let message: SubMsg = match CONFIG.load(deps.storage)?.denom {
+        Denom::Native(denom) => SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin {
+                denom,
+                amount: release,
+            }],
+        }),
+        Denom::Cw20(addr) => SubMsg::new(WasmMsg::Execute {
+            contract_addr: addr.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: release,
+            })?,
+            funds: vec![],
+        }),
+    };
+
+    Ok(Response::new()
+        .add_submessage(message)
+        .add_attribute("action", "claim")
+        .add_attribute("amount", release)
+        .add_attribute("sender", info.sender))
+}
+
+/// Updates the group weight of the given accounts to match their share balance
+/// and returns the messages to alert the hooks of all changes.
+/// The contract itself is never a member, it only holds the locked shares (and any sent to it)
+fn update_membership(
+    storage: &mut dyn Storage,
+    self_addr: &Addr,
+    accounts: &[&Addr],
+    height: u64,
+) -> StdResult<Vec<SubMsg>> {
+    let cfg = CONFIG.load(storage)?;
+    let mut total = TOTAL.load(storage)?;
+    let mut diffs: Vec<MemberDiff> = vec![];
+    for addr in accounts.iter().filter(|addr| **addr != self_addr) {
+        let shares = BALANCES.may_load(storage, addr)?.unwrap_or_default();
+        let new = calc_weight(shares, &cfg);
+        let old = MEMBERS.may_load(storage, addr)?;
+        // this also skips an account listed twice, e.g. a transfer to self
+        if new == old {
+            continue;
+        }
+        match new {
+            Some(weight) => MEMBERS.save(storage, addr, &weight, height),
+            None => MEMBERS.remove(storage, addr, height),
+        }?;
+        total = total + new.unwrap_or_default() - old.unwrap_or_default();
+        diffs.push(MemberDiff::new(addr.as_str(), old, new));
+    }
+    if diffs.is_empty() {
+        return Ok(vec![]);
+    }
+    TOTAL.save(storage, &total, height)?;
+
+    // alert the hooks
+    let msg = MemberChangedHookMsg::new(diffs);
+    HOOKS.prepare_hooks(storage, |h| msg.clone().into_cosmos_msg(h).map(SubMsg::new))
+}
+
+/// The weight is based on shares rather than on the bonded tokens they are worth. All shares
+/// have the same value, so this gives the same relative voting power, while adding rewards
+/// does not need to update every member.
+fn calc_weight(shares: Uint128, cfg: &Config) -> Option<u64> {
+    if shares.is_zero() {
+        None
+    } else {
+        let w = shares.u128() / cfg.tokens_per_weight.u128();
+        Some(w as u64)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        // custom queries
+        QueryMsg::StakingInfo {} => to_binary(&query_staking_info(deps)?),
+        QueryMsg::Claims { address } => {
+            to_binary(&CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)?)
+        }
+        // cw4 group
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::TotalWeight { at_height } => to_binary(&query_total_weight(deps, at_height)?),
+        QueryMsg::ListMembers { start_after, limit } => {
+            to_binary(&query_list_members(deps, start_after, limit)?)
+        }
+        QueryMsg::Member { addr, at_height } => to_binary(&query_member(deps, addr, at_height)?),
+        QueryMsg::Hooks {} => to_binary(&HOOKS.query_hooks(deps)?),
+        // inherited from cw20-base
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::Metadata { key } => to_binary(&query_metadata(deps, key)?),
+        QueryMsg::AllMetadata { start_after, limit } => {
+            to_binary(&query_all_metadata(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_staking_info(deps: Deps) -> StdResult<StakingInfoResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let bonded = BONDED.load(deps.storage)?;
+    let shares = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let exchange_rate = if shares.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(bonded, shares)
+    };
+    Ok(StakingInfoResponse {
+        denom: cfg.denom,
+        bonded,
+        shares,
+        exchange_rate,
+        tokens_per_weight: cfg.tokens_per_weight,
+        unbonding_period: cfg.unbonding_period,
+    })
+}
+
+pub fn query_total_weight(deps: Deps, height: Option<u64>) -> StdResult<TotalWeightResponse> {
+    let weight = match height {
+        Some(h) => TOTAL.may_load_at_height(deps.storage, h),
+        None => TOTAL.may_load(deps.storage),
+    }?
+    .unwrap_or_default();
+    Ok(TotalWeightResponse { weight })
+}
+
+pub fn query_member(deps: Deps, addr: String, height: Option<u64>) -> StdResult<MemberResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let weight = match height {
+        Some(h) => MEMBERS.may_load_at_height(deps.storage, &addr, h),
+        None => MEMBERS.may_load(deps.storage, &addr),
+    }?;
+    Ok(MemberResponse { weight })
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_list_members(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MemberListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let start = addr.as_ref().map(Bound::exclusive);
+
+    let members = MEMBERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, weight)| Member {
+                addr: addr.into(),
+                weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(MemberListResponse { members })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, Empty};
+    use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg, UncheckedDenom};
+    use cw_controllers::Claim;
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_utils::{Duration, Expiration, PaymentError};
+
+    const ADMIN_ADDR: &str = "admin";
+    const ALICE: &str = "alice";
+    const BOB: &str = "bob";
+    const DENOM: &str = "ustake";
+    const UNBONDING_BLOCKS: u64 = 100;
+
+    fn instantiate_msg(denom: UncheckedDenom) -> InstantiateMsg {
+        InstantiateMsg {
+            name: "Staked Token".to_string(),
+            symbol: "STAKED".to_string(),
+            decimals: 6,
+            denom,
+            tokens_per_weight: Uint128::new(100),
+            unbonding_period: Duration::Height(UNBONDING_BLOCKS),
+            admin: Some(ADMIN_ADDR.to_string()),
+            marketing: None,
+        }
+    }
+
+    fn do_instantiate(deps: DepsMut) {
+        let msg = instantiate_msg(UncheckedDenom::Native(DENOM.to_string()));
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn bond(deps: DepsMut, sender: &str, amount: u128) -> Response {
+        let info = mock_info(sender, &coins(amount, DENOM));
+        execute(deps, mock_env(), info, ExecuteMsg::Bond {}).unwrap()
+    }
+
+    fn shares(deps: Deps, addr: &str) -> u128 {
+        query_balance(deps, addr.to_string())
+            .unwrap()
+            .balance
+            .u128()
+    }
+
+    fn weight(deps: Deps, addr: &str) -> Option<u64> {
+        query_member(deps, addr.to_string(), None).unwrap().weight
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+        let mut msg = instantiate_msg(UncheckedDenom::Native(DENOM.to_string()));
+        msg.tokens_per_weight = Uint128::zero();
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ZeroTokensPerWeight {});
+
+        do_instantiate(deps.as_mut());
+        let info = query_staking_info(deps.as_ref()).unwrap();
+        assert_eq!(
+            info,
+            StakingInfoResponse {
+                denom: Denom::Native(DENOM.to_string()),
+                bonded: Uint128::zero(),
+                shares: Uint128::zero(),
+                exchange_rate: Decimal::one(),
+                tokens_per_weight: Uint128::new(100),
+                unbonding_period: Duration::Height(UNBONDING_BLOCKS),
+            }
+        );
+        assert_eq!(query_total_weight(deps.as_ref(), None).unwrap().weight, 0);
+        let minter = cw20_base::contract::query_minter(deps.as_ref())
+            .unwrap()
+            .unwrap();
+        assert_eq!(minter.minter, mock_env().contract.address.to_string());
+    }
+
+    #[test]
+    fn bond_follows_exchange_rate() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        // wrong denom
+        let info = mock_info(ALICE, &coins(1000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(PaymentError::MissingDenom(DENOM.to_string()))
+        );
+        // no rewards without shares
+        let info = mock_info(ALICE, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddRewards {}).unwrap_err();
+        assert_eq!(err, ContractError::NoShares {});
+
+        // the first bond must be larger than the locked shares
+        let info = mock_info(ALICE, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap_err();
+        assert_eq!(err, ContractError::BondTooSmall {});
+
+        // first bond is 1:1, minus the locked shares
+        bond(deps.as_mut(), ALICE, 2000);
+        assert_eq!(shares(deps.as_ref(), ALICE), 1000);
+        let contract = mock_env().contract.address;
+        assert_eq!(shares(deps.as_ref(), contract.as_str()), 1000);
+        assert_eq!(weight(deps.as_ref(), contract.as_str()), None);
+
+        // rewards raise the exchange rate to 1.5
+        let info = mock_info(BOB, &coins(1000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddRewards {}).unwrap();
+        let info = query_staking_info(deps.as_ref()).unwrap();
+        assert_eq!(info.exchange_rate, Decimal::percent(150));
+
+        // so bob gets fewer shares for the same tokens
+        bond(deps.as_mut(), BOB, 300);
+        assert_eq!(shares(deps.as_ref(), BOB), 200);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(BOB, &coins(1, DENOM)),
+            ExecuteMsg::Bond {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BondTooSmall {});
+
+        // weights are based on shares
+        assert_eq!(weight(deps.as_ref(), ALICE), Some(10));
+        assert_eq!(weight(deps.as_ref(), BOB), Some(2));
+        assert_eq!(query_total_weight(deps.as_ref(), None).unwrap().weight, 12);
+    }
+
+    #[test]
+    fn locked_shares_prevent_inflation() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        // the first bonder keeps a single share and tries to inflate its value
+        bond(deps.as_mut(), ALICE, 1001);
+        assert_eq!(shares(deps.as_ref(), ALICE), 1);
+        let info = mock_info(ALICE, &coins(10_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddRewards {}).unwrap();
+
+        // bob still gets shares worth almost all of the bonded tokens
+        bond(deps.as_mut(), BOB, 5000);
+        assert_eq!(shares(deps.as_ref(), BOB), 454);
+        let info = query_staking_info(deps.as_ref()).unwrap();
+        let bob_tokens = Uint128::new(454).multiply_ratio(info.bonded, info.shares);
+        assert_eq!(bob_tokens, Uint128::new(4992));
+
+        // and alice lost most of her rewards to the locked shares
+        let alice_tokens = Uint128::new(1).multiply_ratio(info.bonded, info.shares);
+        assert_eq!(alice_tokens, Uint128::new(10));
+    }
+
+    #[test]
+    fn unbond_and_claim() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        bond(deps.as_mut(), ALICE, 2000);
+        let info = mock_info(BOB, &coins(2000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddRewards {}).unwrap();
+
+        // 400 shares are worth 800 tokens now
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(400),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ALICE, &[]), msg).unwrap();
+        assert_eq!(shares(deps.as_ref(), ALICE), 600);
+        assert_eq!(weight(deps.as_ref(), ALICE), Some(6));
+        let info = query_staking_info(deps.as_ref()).unwrap();
+        assert_eq!(info.bonded, Uint128::new(3200));
+        assert_eq!(info.exchange_rate, Decimal::percent(200));
+
+        let claims = CLAIMS
+            .query_claims(deps.as_ref(), &Addr::unchecked(ALICE))
+            .unwrap()
+            .claims;
+        let release_at = mock_env().block.height + UNBONDING_BLOCKS;
+        assert_eq!(
+            claims,
+            vec![Claim::new(800, Expiration::AtHeight(release_at))]
+        );
+
+        // cannot unbond more shares than owned
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(601),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ALICE, &[]), msg).unwrap_err();
+
+        // nothing to claim before the unbonding period
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ALICE, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        let mut env = mock_env();
+        env.block.height = release_at;
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info(ALICE, &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(800, DENOM),
+            })]
+        );
+
+        // unbonding everything removes the membership, only the locked shares stay bonded
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(600),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ALICE, &[]), msg).unwrap();
+        assert_eq!(weight(deps.as_ref(), ALICE), None);
+        assert_eq!(query_total_weight(deps.as_ref(), None).unwrap().weight, 0);
+        let info = query_staking_info(deps.as_ref()).unwrap();
+        assert_eq!(info.bonded, Uint128::new(2000));
+        assert_eq!(info.shares, LOCKED_SHARES);
+    }
+
+    #[test]
+    fn transfers_update_membership_and_hooks() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let hook = "hook";
+        let msg = ExecuteMsg::AddHook {
+            addr: hook.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+
+        let res = bond(deps.as_mut(), ALICE, 2000);
+        let diff = MemberDiff::new(ALICE, None, Some(10));
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                MemberChangedHookMsg::one(diff)
+                    .into_cosmos_msg(hook)
+                    .unwrap()
+            )]
+        );
+
+        let mut env = mock_env();
+        env.block.height += 1;
+        let msg = ExecuteMsg::Transfer {
+            recipient: BOB.to_string(),
+            amount: Uint128::new(1000),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(ALICE, &[]), msg).unwrap();
+        let diffs = vec![
+            MemberDiff::new(ALICE, Some(10), None),
+            MemberDiff::new(BOB, None, Some(10)),
+        ];
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                MemberChangedHookMsg::new(diffs)
+                    .into_cosmos_msg(hook)
+                    .unwrap()
+            )]
+        );
+        assert_eq!(weight(deps.as_ref(), ALICE), None);
+        assert_eq!(weight(deps.as_ref(), BOB), Some(10));
+        assert_eq!(query_total_weight(deps.as_ref(), None).unwrap().weight, 10);
+
+        // the old weights are kept as snapshots
+        let height = Some(mock_env().block.height + 1);
+        let old = query_member(deps.as_ref(), ALICE.to_string(), height).unwrap();
+        assert_eq!(old.weight, Some(10));
+
+        // small transfers below a unit of weight change nothing
+        let msg = ExecuteMsg::Transfer {
+            recipient: ALICE.to_string(),
+            amount: Uint128::new(5),
+        };
+        let res = execute(deps.as_mut(), env, mock_info(BOB, &[]), msg).unwrap();
+        assert_eq!(weight(deps.as_ref(), ALICE), Some(0));
+        assert_eq!(weight(deps.as_ref(), BOB), Some(9));
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn shares_sent_to_the_contract_are_no_member() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        bond(deps.as_mut(), ALICE, 2000);
+
+        let contract = mock_env().contract.address;
+        let msg = ExecuteMsg::Transfer {
+            recipient: contract.to_string(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ALICE, &[]), msg).unwrap();
+        assert_eq!(shares(deps.as_ref(), contract.as_str()), 2000);
+        assert_eq!(weight(deps.as_ref(), contract.as_str()), None);
+        assert_eq!(weight(deps.as_ref(), ALICE), None);
+        assert_eq!(query_total_weight(deps.as_ref(), None).unwrap().weight, 0);
+    }
+
+    fn contract_staking() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn stake_cw20_tokens() {
+        let alice = Addr::unchecked(ALICE);
+        let mut app = App::default();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let token = app
+            .instantiate_contract(
+                cw20_id,
+                alice.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Gov Token".to_string(),
+                    symbol: "GOV".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: alice.to_string(),
+                        amount: Uint128::new(2000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "GOV",
+                None,
+            )
+            .unwrap();
+        let staking_id = app.store_code(contract_staking());
+        let staking = app
+            .instantiate_contract(
+                staking_id,
+                alice.clone(),
+                &instantiate_msg(UncheckedDenom::Cw20(token.to_string())),
+                &[],
+                "staking",
+                None,
+            )
+            .unwrap();
+
+        // native bonds are rejected
+        let err = app
+            .execute_contract(alice.clone(), staking.clone(), &ExecuteMsg::Bond {}, &[])
+            .unwrap_err();
+        assert_eq!(ContractError::InvalidDenom {}, err.downcast().unwrap());
+
+        let msg = Cw20ExecuteMsg::Send {
+            contract: staking.to_string(),
+            amount: Uint128::new(2000),
+            msg: to_binary(&ReceiveMsg::Bond {}).unwrap(),
+        };
+        app.execute_contract(alice.clone(), token.clone(), &msg, &[])
+            .unwrap();
+
+        let balance = |app: &App, contract: &Addr| {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract,
+                    &Cw20QueryMsg::Balance {
+                        address: alice.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance.u128()
+        };
+        assert_eq!(balance(&app, &token), 0);
+        assert_eq!(balance(&app, &staking), 1000);
+
+        // the contract works as a cw4 group
+        let group = cw4::Cw4Contract(staking.clone());
+        let weight = group.is_member(&app.wrap(), &alice, None).unwrap();
+        assert_eq!(weight, Some(10));
+        assert_eq!(group.total_weight(&app.wrap()).unwrap(), 10);
+
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(1000),
+        };
+        app.execute_contract(alice.clone(), staking.clone(), &msg, &[])
+            .unwrap();
+        app.update_block(|block| block.height += UNBONDING_BLOCKS);
+        app.execute_contract(alice.clone(), staking.clone(), &ExecuteMsg::Claim {}, &[])
+            .unwrap();
+        assert_eq!(balance(&app, &token), 1000);
+        assert_eq!(balance(&app, &staking), 0);
+    }
+}
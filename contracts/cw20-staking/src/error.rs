@@ -0,0 +1,40 @@
+use cosmwasm_std::StdError;
+use cw_controllers::{AdminError, HookError};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw20_base::ContractError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
+    #[error("{0}")]
+    Hook(#[from] HookError),
+
+    #[error("tokens_per_weight must be positive")]
+    ZeroTokensPerWeight {},
+
+    #[error("This token cannot be bonded here")]
+    InvalidDenom {},
+
+    #[error("Bond is too small to issue a single share")]
+    BondTooSmall {},
+
+    #[error("Unbond is too small to release a single token")]
+    UnbondTooSmall {},
+
+    #[error("Cannot add rewards when there are no shares")]
+    NoShares {},
+
+    #[error("No claims that can be released currently")]
+    NothingToClaim {},
+}
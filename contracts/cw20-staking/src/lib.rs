@@ -0,0 +1,23 @@
+/*!
+This is a liquid staking contract. Tokens bonded here (native or cw20) are represented
+by a cw20 share token, which can be transferred and used like any other cw20 token while
+the underlying tokens stay bonded. It uses `cw20-base` as a library for the share token.
+
+Shares are issued at the current exchange rate between shares and bonded tokens. The rate
+starts at 1:1 and grows when rewards are added to the bonded tokens. Unbonding burns shares
+and releases the matching tokens after the unbonding period, like in `cw4-stake`.
+
+The contract also implements the cw4 group interface, with the weight of every member based on
+their share balance. This way the bonded tokens still count for governance, e.g. when using this
+contract as the group of a `cw3-flex-multisig`.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-staking/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,210 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Decimal, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom, Expiration, Logo, UncheckedDenom};
+use cw_utils::Duration;
+
+pub use cw20_base::msg::InstantiateMarketingInfo;
+pub use cw_controllers::ClaimsResponse;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// name of the share token
+    pub name: String,
+    /// symbol / ticker of the share token
+    pub symbol: String,
+    /// number of decimal places of the share token, usually the same as the bonded token
+    pub decimals: u8,
+
+    /// denom of the token to bond
+    pub denom: UncheckedDenom,
+    /// number of shares per unit of group weight
+    pub tokens_per_weight: Uint128,
+    /// how long unbonded tokens are locked before they can be claimed
+    pub unbonding_period: Duration,
+
+    /// admin can only add/remove hooks, not change other parameters
+    pub admin: Option<String>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+}
+
+/// All the cw20 messages, besides the ones that would change the total supply without
+/// touching the bonded tokens (`Burn`, `BurnFrom`, `Mint` and `UpdateMinter`)
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Bond will bond all native staking tokens sent with the message and issue shares
+    /// to the sender at the current exchange rate. The first bond keeps `LOCKED_SHARES`
+    /// of them in the contract.
+    Bond {},
+    /// Unbond burns the given number of shares of the sender and starts unbonding the tokens
+    /// they are worth. These can be claimed back after `unbonding_period`
+    Unbond { amount: Uint128 },
+    /// Claim is used to claim your tokens that you previously "unbonded"
+    /// after the contract-defined waiting period
+    Claim {},
+    /// Adds all native staking tokens sent with the message to the bonded tokens without
+    /// issuing shares, raising the exchange rate for all current share holders
+    AddRewards {},
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+
+    /// Change the admin
+    UpdateAdmin { admin: Option<String> },
+    /// Add a new hook to be informed of all membership changes. Must be called by Admin
+    AddHook { addr: String },
+    /// Remove a hook. Must be called by Admin
+    RemoveHook { addr: String },
+
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// If authorized, updates marketing metadata.
+    /// Setting None/null for any of these will leave it unchanged.
+    /// Setting Some("") will clear this field on the contract storage
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, PNG, WebP or JPEG
+    /// for the token
+    UploadLogo(Logo),
+    /// If authorized, sets an arbitrary metadata entry
+    SetMetadata { key: String, value: String },
+    /// If authorized, removes the metadata entry stored under this key
+    RemoveMetadata { key: String },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Bonds the received cw20 tokens, issuing shares to the sender
+    Bond {},
+    /// Adds the received cw20 tokens to the bonded tokens without issuing shares
+    AddRewards {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the bonded token, the total bonded tokens and shares, and the exchange rate
+    #[returns(StakingInfoResponse)]
+    StakingInfo {},
+    /// Claims shows the tokens in process of unbonding for this address
+    #[returns(cw_controllers::ClaimsResponse)]
+    Claims { address: String },
+
+    #[returns(cw_controllers::AdminResponse)]
+    Admin {},
+    #[returns(cw4::TotalWeightResponse)]
+    TotalWeight { at_height: Option<u64> },
+    #[returns(cw4::MemberListResponse)]
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(cw4::MemberResponse)]
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    /// Shows all registered hooks.
+    #[returns(cw_controllers::HooksResponse)]
+    Hooks {},
+
+    /// Returns the current balance of the given address, 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    /// Returns how much spender can use from owner account, 0 if unset.
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns more metadata on the contract to display in the client:
+    /// - description, logo, project url, etc.
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for
+    /// this contract.
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    #[returns(cw20::MetadataResponse)]
+    Metadata { key: String },
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    #[returns(cw20::AllMetadataResponse)]
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct StakingInfoResponse {
+    pub denom: Denom,
+    /// tokens backing the shares, not counting the ones being unbonded
+    pub bonded: Uint128,
+    /// total supply of the share token
+    pub shares: Uint128,
+    /// bonded tokens per share
+    pub exchange_rate: Decimal,
+    pub tokens_per_weight: Uint128,
+    pub unbonding_period: Duration,
+}
@@ -0,0 +1,42 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use cw20::Denom;
+use cw4::{
+    MEMBERS_CHANGELOG, MEMBERS_CHECKPOINTS, MEMBERS_KEY, TOTAL_KEY, TOTAL_KEY_CHANGELOG,
+    TOTAL_KEY_CHECKPOINTS,
+};
+use cw_controllers::{Admin, Claims, Hooks};
+use cw_storage_plus::{Item, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::Duration;
+
+#[cw_serde]
+pub struct Config {
+    /// denom of the token to bond
+    pub denom: Denom,
+    /// number of shares per unit of group weight
+    pub tokens_per_weight: Uint128,
+    pub unbonding_period: Duration,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Tokens backing the shares. This includes added rewards, but not the unbonding tokens.
+pub const BONDED: Item<Uint128> = Item::new("bonded");
+pub const CLAIMS: Claims = Claims::new("claims");
+
+// admin can only add/remove hooks
+pub const ADMIN: Admin = Admin::new("admin");
+pub const HOOKS: Hooks = Hooks::new("cw4-hooks");
+
+pub const TOTAL: SnapshotItem<u64> = SnapshotItem::new(
+    TOTAL_KEY,
+    TOTAL_KEY_CHECKPOINTS,
+    TOTAL_KEY_CHANGELOG,
+    Strategy::EveryBlock,
+);
+
+pub const MEMBERS: SnapshotMap<&Addr, u64> = SnapshotMap::new(
+    MEMBERS_KEY,
+    MEMBERS_CHECKPOINTS,
+    MEMBERS_CHANGELOG,
+    Strategy::EveryBlock,
+);
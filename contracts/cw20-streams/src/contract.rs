@@ -0,0 +1,587 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Response, StdResult, Timestamp, Uint128, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use cw_storage_plus::{Bound, Map};
+use cw_utils::{one_coin, PaymentError};
+
+use crate::error::ContractError;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, StreamResponse, StreamsResponse,
+};
+use crate::state::{
+    remove_stream, save_new_stream, Stream, STREAMS, STREAMS_BY_RECIPIENT, STREAMS_BY_SENDER,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-streams";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    // no setup
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateStream {
+            recipient,
+            start_time,
+            end_time,
+        } => {
+            let coin = one_coin(&info)?;
+            execute_create_stream(
+                deps,
+                env,
+                info.sender,
+                recipient,
+                Denom::Native(coin.denom),
+                coin.amount,
+                start_time,
+                end_time,
+            )
+        }
+        ExecuteMsg::Withdraw { id } => execute_withdraw(deps, env, info, id),
+        ExecuteMsg::Cancel { id } => execute_cancel(deps, env, info, id),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match msg {
+        ReceiveMsg::CreateStream {
+            recipient,
+            start_time,
+            end_time,
+        } => execute_create_stream(
+            deps,
+            env,
+            sender,
+            recipient,
+            Denom::Cw20(info.sender),
+            wrapper.amount,
+            start_time,
+            end_time,
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_stream(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    recipient: String,
+    denom: Denom,
+    deposit: Uint128,
+    start_time: Timestamp,
+    end_time: Timestamp,
+) -> Result<Response, ContractError> {
+    if deposit.is_zero() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+    if end_time <= start_time || end_time <= env.block.time {
+        return Err(ContractError::InvalidTimes {});
+    }
+
+    let stream = Stream {
+        sender,
+        recipient: deps.api.addr_validate(&recipient)?,
+        denom,
+        deposit,
+        withdrawn: Uint128::zero(),
+        start_time,
+        end_time,
+    };
+    let id = save_new_stream(deps.storage, &stream)?;
+
+    let res = Response::new()
+        .add_attribute("action", "create_stream")
+        .add_attribute("id", id.to_string())
+        .add_attribute("sender", stream.sender)
+        .add_attribute("recipient", stream.recipient)
+        .add_attribute("deposit", deposit);
+    Ok(res)
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut stream = STREAMS.load(deps.storage, id)?;
+    if info.sender != stream.recipient {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amount = stream.withdrawable(env.block.time);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+    stream.withdrawn += amount;
+    if stream.withdrawn == stream.deposit {
+        remove_stream(deps.storage, id, &stream);
+    } else {
+        STREAMS.save(deps.storage, id, &stream)?;
+    }
+
+    let res = Response::new()
+        .add_message(send_tokens(&stream.recipient, stream.denom, amount)?)
+        .add_attribute("action", "withdraw")
+        .add_attribute("id", id.to_string())
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let stream = STREAMS.load(deps.storage, id)?;
+    if info.sender != stream.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.time >= stream.end_time {
+        return Err(ContractError::StreamEnded {});
+    }
+    remove_stream(deps.storage, id, &stream);
+
+    let streamed = stream.streamed(env.block.time);
+    let to_recipient = streamed - stream.withdrawn;
+    let refund = stream.deposit - streamed;
+
+    let mut res = Response::new()
+        .add_attribute("action", "cancel")
+        .add_attribute("id", id.to_string())
+        .add_attribute("refund", refund);
+    if !to_recipient.is_zero() {
+        res = res.add_message(send_tokens(
+            &stream.recipient,
+            stream.denom.clone(),
+            to_recipient,
+        )?);
+    }
+    // the stream did not end yet, so there is always something to refund
+    res = res.add_message(send_tokens(&stream.sender, stream.denom, refund)?);
+    Ok(res)
+}
+
+fn send_tokens(recipient: &Addr, denom: Denom, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.into(),
+            amount: vec![Coin { denom, amount }],
+        }
+        .into(),
+        Denom::Cw20(addr) => WasmMsg::Execute {
+            contract_addr: addr.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Stream { id } => to_binary(&query_stream(deps, env, id)?),
+        QueryMsg::StreamsBySender {
+            sender,
+            start_after,
+            limit,
+        } => to_binary(&query_streams_by(
+            deps,
+            env,
+            STREAMS_BY_SENDER,
+            sender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::StreamsByRecipient {
+            recipient,
+            start_after,
+            limit,
+        } => to_binary(&query_streams_by(
+            deps,
+            env,
+            STREAMS_BY_RECIPIENT,
+            recipient,
+            start_after,
+            limit,
+        )?),
+    }
+}
+
+fn to_response(id: u64, stream: Stream, env: &Env) -> StreamResponse {
+    StreamResponse {
+        id,
+        withdrawable: stream.withdrawable(env.block.time),
+        sender: stream.sender.into(),
+        recipient: stream.recipient.into(),
+        denom: stream.denom,
+        deposit: stream.deposit,
+        withdrawn: stream.withdrawn,
+        start_time: stream.start_time,
+        end_time: stream.end_time,
+    }
+}
+
+pub fn query_stream(deps: Deps, env: Env, id: u64) -> StdResult<StreamResponse> {
+    let stream = STREAMS.load(deps.storage, id)?;
+    Ok(to_response(id, stream, &env))
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_streams_by(
+    deps: Deps,
+    env: Env,
+    index: Map<(&Addr, u64), Empty>,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<StreamsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let streams = index
+        .prefix(&address)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| {
+            let id = id?;
+            let stream = STREAMS.load(deps.storage, id)?;
+            Ok(to_response(id, stream, &env))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(StreamsResponse { streams })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, StdError, SubMsg};
+    use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+    const FUNDER: &str = "funder";
+    const ALICE: &str = "alice";
+    const BOB: &str = "bob";
+    const DENOM: &str = "ujuno";
+
+    fn env_at(seconds: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    }
+
+    fn create_stream(deps: DepsMut, recipient: &str, amount: u128, start: u64, end: u64) -> u64 {
+        let msg = ExecuteMsg::CreateStream {
+            recipient: recipient.to_string(),
+            start_time: Timestamp::from_seconds(start),
+            end_time: Timestamp::from_seconds(end),
+        };
+        let info = mock_info(FUNDER, &coins(amount, DENOM));
+        let res = execute(deps, env_at(0), info, msg).unwrap();
+        let id = res.attributes.iter().find(|a| a.key == "id").unwrap();
+        id.value.parse().unwrap()
+    }
+
+    fn withdraw(deps: DepsMut, at: u64, id: u64) -> Result<Response, ContractError> {
+        execute(
+            deps,
+            env_at(at),
+            mock_info(ALICE, &[]),
+            ExecuteMsg::Withdraw { id },
+        )
+    }
+
+    fn bank_send(to: &str, amount: u128) -> SubMsg {
+        SubMsg::new(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount, DENOM),
+        })
+    }
+
+    #[test]
+    fn create_validates() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(FUNDER, &[]),
+            InstantiateMsg {},
+        )
+        .unwrap();
+
+        let msg = |start: u64, end: u64| ExecuteMsg::CreateStream {
+            recipient: ALICE.to_string(),
+            start_time: Timestamp::from_seconds(start),
+            end_time: Timestamp::from_seconds(end),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env_at(0),
+            mock_info(FUNDER, &[]),
+            msg(100, 200),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+        let info = mock_info(FUNDER, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), env_at(0), info.clone(), msg(200, 200)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimes {});
+        let err = execute(deps.as_mut(), env_at(300), info, msg(100, 200)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimes {});
+
+        assert_eq!(create_stream(deps.as_mut(), ALICE, 1000, 100, 200), 1);
+        assert_eq!(create_stream(deps.as_mut(), ALICE, 1000, 100, 200), 2);
+    }
+
+    #[test]
+    fn withdraw_streamed() {
+        let mut deps = mock_dependencies();
+        let id = create_stream(deps.as_mut(), ALICE, 1000, 100, 200);
+
+        let err = withdraw(deps.as_mut(), 100, id).unwrap_err();
+        assert_eq!(err, ContractError::NothingToWithdraw {});
+        let err = execute(
+            deps.as_mut(),
+            env_at(150),
+            mock_info(BOB, &[]),
+            ExecuteMsg::Withdraw { id },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = withdraw(deps.as_mut(), 125, id).unwrap();
+        assert_eq!(res.messages, vec![bank_send(ALICE, 250)]);
+        let stream = query_stream(deps.as_ref(), env_at(150), id).unwrap();
+        assert_eq!(stream.withdrawn, Uint128::new(250));
+        assert_eq!(stream.withdrawable, Uint128::new(250));
+
+        // the last withdrawal closes the stream
+        let res = withdraw(deps.as_mut(), 500, id).unwrap();
+        assert_eq!(res.messages, vec![bank_send(ALICE, 750)]);
+        let err = query_stream(deps.as_ref(), env_at(500), id).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+    }
+
+    #[test]
+    fn cancel_splits_deposit() {
+        let mut deps = mock_dependencies();
+        let id = create_stream(deps.as_mut(), ALICE, 1000, 100, 200);
+        withdraw(deps.as_mut(), 120, id).unwrap();
+
+        let cancel = ExecuteMsg::Cancel { id };
+        let err = execute(
+            deps.as_mut(),
+            env_at(150),
+            mock_info(ALICE, &[]),
+            cancel.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            env_at(150),
+            mock_info(FUNDER, &[]),
+            cancel.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![bank_send(ALICE, 300), bank_send(FUNDER, 500)]
+        );
+        STREAMS.load(&deps.storage, id).unwrap_err();
+
+        // cannot cancel once everything is streamed
+        let id = create_stream(deps.as_mut(), ALICE, 1000, 100, 200);
+        let err = execute(
+            deps.as_mut(),
+            env_at(200),
+            mock_info(FUNDER, &[]),
+            ExecuteMsg::Cancel { id },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::StreamEnded {});
+    }
+
+    #[test]
+    fn list_streams() {
+        let mut deps = mock_dependencies();
+        let first = create_stream(deps.as_mut(), ALICE, 1000, 100, 200);
+        let second = create_stream(deps.as_mut(), BOB, 1000, 100, 200);
+        let third = create_stream(deps.as_mut(), ALICE, 1000, 100, 200);
+
+        let ids = |res: StreamsResponse| res.streams.iter().map(|s| s.id).collect::<Vec<_>>();
+        let by_recipient = |recipient: &str, start_after| {
+            query_streams_by(
+                deps.as_ref(),
+                env_at(0),
+                STREAMS_BY_RECIPIENT,
+                recipient.to_string(),
+                start_after,
+                None,
+            )
+            .unwrap()
+        };
+        assert_eq!(ids(by_recipient(ALICE, None)), vec![first, third]);
+        assert_eq!(ids(by_recipient(ALICE, Some(first))), vec![third]);
+        assert_eq!(ids(by_recipient(BOB, None)), vec![second]);
+
+        let by_sender = query_streams_by(
+            deps.as_ref(),
+            env_at(0),
+            STREAMS_BY_SENDER,
+            FUNDER.to_string(),
+            None,
+            Some(2),
+        )
+        .unwrap();
+        assert_eq!(ids(by_sender), vec![first, second]);
+    }
+
+    fn contract_streams() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn stream_cw20_tokens() {
+        let funder = Addr::unchecked(FUNDER);
+        let alice = Addr::unchecked(ALICE);
+        let mut app = App::default();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let token = app
+            .instantiate_contract(
+                cw20_id,
+                funder.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Dao Token".to_string(),
+                    symbol: "DAO".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: funder.to_string(),
+                        amount: Uint128::new(1000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "DAO",
+                None,
+            )
+            .unwrap();
+        let streams_id = app.store_code(contract_streams());
+        let streams = app
+            .instantiate_contract(
+                streams_id,
+                funder.clone(),
+                &InstantiateMsg {},
+                &[],
+                "streams",
+                None,
+            )
+            .unwrap();
+
+        let start = app.block_info().time;
+        let msg = Cw20ExecuteMsg::Send {
+            contract: streams.to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::CreateStream {
+                recipient: alice.to_string(),
+                start_time: start,
+                end_time: start.plus_seconds(100),
+            })
+            .unwrap(),
+        };
+        app.execute_contract(funder.clone(), token.clone(), &msg, &[])
+            .unwrap();
+
+        let balance = |app: &App, addr: &Addr| {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &token,
+                    &Cw20QueryMsg::Balance {
+                        address: addr.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance.u128()
+        };
+
+        app.update_block(|block| block.time = block.time.plus_seconds(40));
+        app.execute_contract(
+            alice.clone(),
+            streams.clone(),
+            &ExecuteMsg::Withdraw { id: 1 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &alice), 400);
+
+        app.update_block(|block| block.time = block.time.plus_seconds(20));
+        app.execute_contract(
+            funder.clone(),
+            streams.clone(),
+            &ExecuteMsg::Cancel { id: 1 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &alice), 600);
+        assert_eq!(balance(&app, &funder), 400);
+        assert_eq!(balance(&app, &streams), 0);
+    }
+}
@@ -0,0 +1,24 @@
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Stream must end after it starts, and in the future")]
+    InvalidTimes {},
+
+    #[error("Nothing streamed to withdraw yet")]
+    NothingToWithdraw {},
+
+    #[error("Stream has already ended")]
+    StreamEnded {},
+}
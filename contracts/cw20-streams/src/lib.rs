@@ -0,0 +1,19 @@
+/*!
+This is a payment streaming contract, paying out native or cw20 tokens to a recipient
+continuously over time, e.g. for contributor salaries.
+
+A funder deposits the tokens and opens a stream with a start and an end time. The deposit
+is released linearly between the two, and the recipient can withdraw everything streamed
+so far at any time. The funder can cancel a stream before it ends, which pays out
+the streamed part to the recipient and returns the rest to the funder.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-streams/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,77 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Timestamp, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
+
+#[cw_serde]
+pub struct InstantiateMsg {}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Opens a stream to the recipient with the native tokens sent along with this message.
+    /// Exactly one denom must be sent.
+    CreateStream {
+        recipient: String,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    },
+    /// Sends everything streamed so far to the recipient. Only the recipient can do this.
+    Withdraw { id: u64 },
+    /// Closes the stream, sending everything streamed so far to the recipient and returning
+    /// the rest to its sender. Only the sender of the stream can do this, and only before it ends.
+    Cancel { id: u64 },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Opens a stream to the recipient with the received cw20 tokens
+    CreateStream {
+        recipient: String,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the details of the given stream, error if it does not exist (anymore)
+    #[returns(StreamResponse)]
+    Stream { id: u64 },
+    /// Lists all open streams funded by the given address. Supports pagination.
+    #[returns(StreamsResponse)]
+    StreamsBySender {
+        sender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Lists all open streams paying to the given address. Supports pagination.
+    #[returns(StreamsResponse)]
+    StreamsByRecipient {
+        recipient: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct StreamResponse {
+    pub id: u64,
+    pub sender: String,
+    pub recipient: String,
+    pub denom: Denom,
+    /// Total amount streamed from `start_time` to `end_time`
+    pub deposit: Uint128,
+    /// Amount already sent to the recipient
+    pub withdrawn: Uint128,
+    /// Amount the recipient can withdraw at the current block time
+    pub withdrawable: Uint128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct StreamsResponse {
+    pub streams: Vec<StreamResponse>,
+}
@@ -0,0 +1,59 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Empty, StdResult, Storage, Timestamp, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct Stream {
+    /// The funder of the stream, who can cancel it
+    pub sender: Addr,
+    pub recipient: Addr,
+    pub denom: Denom,
+    /// Total amount streamed from `start_time` to `end_time`
+    pub deposit: Uint128,
+    /// Amount already sent to the recipient
+    pub withdrawn: Uint128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+impl Stream {
+    /// Returns the amount streamed until block time `at`, including the withdrawn tokens
+    pub fn streamed(&self, at: Timestamp) -> Uint128 {
+        if at <= self.start_time {
+            Uint128::zero()
+        } else if at >= self.end_time {
+            self.deposit
+        } else {
+            let elapsed = at.seconds() - self.start_time.seconds();
+            let duration = self.end_time.seconds() - self.start_time.seconds();
+            self.deposit.multiply_ratio(elapsed, duration)
+        }
+    }
+
+    /// Returns the amount the recipient can withdraw at block time `at`
+    pub fn withdrawable(&self, at: Timestamp) -> Uint128 {
+        self.streamed(at) - self.withdrawn
+    }
+}
+
+pub const STREAM_SEQ: Item<u64> = Item::new("stream_seq");
+pub const STREAMS: Map<u64, Stream> = Map::new("streams");
+/// Secondary indexes of all open streams by sender and by recipient
+pub const STREAMS_BY_SENDER: Map<(&Addr, u64), Empty> = Map::new("streams_by_sender");
+pub const STREAMS_BY_RECIPIENT: Map<(&Addr, u64), Empty> = Map::new("streams_by_recipient");
+
+pub fn save_new_stream(storage: &mut dyn Storage, stream: &Stream) -> StdResult<u64> {
+    let id = STREAM_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    STREAM_SEQ.save(storage, &id)?;
+    STREAMS.save(storage, id, stream)?;
+    STREAMS_BY_SENDER.save(storage, (&stream.sender, id), &Empty {})?;
+    STREAMS_BY_RECIPIENT.save(storage, (&stream.recipient, id), &Empty {})?;
+    Ok(id)
+}
+
+pub fn remove_stream(storage: &mut dyn Storage, id: u64, stream: &Stream) {
+    STREAMS.remove(storage, id);
+    STREAMS_BY_SENDER.remove(storage, (&stream.sender, id));
+    STREAMS_BY_RECIPIENT.remove(storage, (&stream.recipient, id));
+}
@@ -0,0 +1,862 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    Order, Response, StdResult, Timestamp, Uint128, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use cw_storage_plus::Bound;
+use cw_utils::{maybe_addr, one_coin, PaymentError};
+
+use crate::error::ContractError;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, VestedResponse, VestedTotal,
+    VestedTotalsResponse, VestingScheduleResponse, VestingSchedulesResponse,
+};
+use crate::state::{Schedule, Vesting, ADMIN, VESTINGS, VESTING_SEQ};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-vesting";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let admin = maybe_addr(deps.api, msg.admin)?;
+    ADMIN.set(deps.branch(), admin)?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    match msg {
+        ExecuteMsg::Create {
+            beneficiary,
+            schedule,
+        } => {
+            let coin = one_coin(&info)?;
+            execute_create(
+                deps,
+                &info.sender,
+                beneficiary,
+                schedule,
+                Denom::Native(coin.denom),
+                coin.amount,
+            )
+        }
+        ExecuteMsg::Claim {} => execute_claim_all(deps, env, info),
+        ExecuteMsg::ClaimById { id } => execute_claim(deps, env, info, id),
+        ExecuteMsg::Revoke { address, id } => execute_revoke(deps, env, info, address, id),
+        ExecuteMsg::UpdateAdmin { admin } => Ok(ADMIN.execute_update_admin(
+            deps,
+            info,
+            admin.map(|admin| api.addr_validate(&admin)).transpose()?,
+        )?),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the address of the cw20 contract (that re-sent this message).
+    // wrapper.sender is the address of the user that requested the cw20 contract to send this.
+    let msg: ReceiveMsg = from_slice(&wrapper.msg)?;
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match msg {
+        ReceiveMsg::Create {
+            beneficiary,
+            schedule,
+        } => execute_create(
+            deps,
+            &sender,
+            beneficiary,
+            schedule,
+            Denom::Cw20(info.sender),
+            wrapper.amount,
+        ),
+    }
+}
+
+pub fn execute_create(
+    deps: DepsMut,
+    sender: &Addr,
+    beneficiary: String,
+    schedule: Schedule,
+    denom: Denom,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if ADMIN.get(deps.as_ref())?.is_some() {
+        ADMIN.assert_admin(deps.as_ref(), sender)?;
+    }
+    if amount.is_zero() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+    schedule.validate()?;
+
+    let beneficiary = deps.api.addr_validate(&beneficiary)?;
+    // every schedule gets its own id, so creating one never blocks another
+    let id = VESTING_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    VESTING_SEQ.save(deps.storage, &id)?;
+    let vesting = Vesting {
+        denom,
+        amount,
+        claimed: Uint128::zero(),
+        schedule,
+        revoked_at: None,
+    };
+    VESTINGS.save(deps.storage, (&beneficiary, id), &vesting)?;
+
+    let res = Response::new()
+        .add_attribute("action", "create")
+        .add_attribute("id", id.to_string())
+        .add_attribute("sender", sender)
+        .add_attribute("beneficiary", beneficiary)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut vesting = VESTINGS
+        .may_load(deps.storage, (&info.sender, id))?
+        .ok_or(ContractError::NoSchedule {})?;
+
+    let amount = vesting.claimable(env.block.time);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    vesting.claimed += amount;
+    VESTINGS.save(deps.storage, (&info.sender, id), &vesting)?;
+
+    let res = Response::new()
+        .add_message(send_tokens(&info.sender, vesting.denom, amount)?)
+        .add_attribute("action", "claim")
+        .add_attribute("id", id.to_string())
+        .add_attribute("beneficiary", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_claim_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let vestings = VESTINGS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if vestings.is_empty() {
+        return Err(ContractError::NoSchedule {});
+    }
+
+    // claim from every schedule, but send only one message per denom
+    let mut amounts: Vec<(Denom, Uint128)> = vec![];
+    for (id, mut vesting) in vestings {
+        let amount = vesting.claimable(env.block.time);
+        if amount.is_zero() {
+            continue;
+        }
+        vesting.claimed += amount;
+        VESTINGS.save(deps.storage, (&info.sender, id), &vesting)?;
+        match amounts
+            .iter_mut()
+            .find(|(denom, _)| denom == &vesting.denom)
+        {
+            Some((_, total)) => *total += amount,
+            None => amounts.push((vesting.denom, amount)),
+        }
+    }
+    if amounts.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let msgs = amounts
+        .into_iter()
+        .map(|(denom, amount)| send_tokens(&info.sender, denom, amount))
+        .collect::<StdResult<Vec<_>>>()?;
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "claim")
+        .add_attribute("beneficiary", info.sender);
+    Ok(res)
+}
+
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    id: u64,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let beneficiary = deps.api.addr_validate(&address)?;
+    let mut vesting = VESTINGS
+        .may_load(deps.storage, (&beneficiary, id))?
+        .ok_or(ContractError::NoSchedule {})?;
+    if vesting.revoked_at.is_some() {
+        return Err(ContractError::AlreadyRevoked {});
+    }
+
+    let unvested = vesting.amount - vesting.vested(env.block.time);
+    if unvested.is_zero() {
+        return Err(ContractError::NothingToRevoke {});
+    }
+    vesting.revoked_at = Some(env.block.time);
+    VESTINGS.save(deps.storage, (&beneficiary, id), &vesting)?;
+
+    let res = Response::new()
+        .add_message(send_tokens(&info.sender, vesting.denom, unvested)?)
+        .add_attribute("action", "revoke")
+        .add_attribute("id", id.to_string())
+        .add_attribute("beneficiary", beneficiary)
+        .add_attribute("amount", unvested);
+    Ok(res)
+}
+
+fn send_tokens(recipient: &Addr, denom: Denom, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.into(),
+            amount: vec![Coin { denom, amount }],
+        }
+        .into(),
+        Denom::Cw20(addr) => WasmMsg::Execute {
+            contract_addr: addr.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::VestingSchedule { address } => {
+            to_binary(&query_all_vesting_schedules(deps, address)?)
+        }
+        QueryMsg::VestingScheduleById { address, id } => {
+            to_binary(&query_vesting_schedule(deps, address, id)?)
+        }
+        QueryMsg::VestingSchedules {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_vesting_schedules(deps, address, start_after, limit)?),
+        QueryMsg::Vested { address, at_time } => {
+            to_binary(&query_vested_totals(deps, env, address, at_time)?)
+        }
+        QueryMsg::VestedById {
+            address,
+            id,
+            at_time,
+        } => to_binary(&query_vested(deps, env, address, id, at_time)?),
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+    }
+}
+
+fn to_response(id: u64, vesting: Vesting) -> VestingScheduleResponse {
+    VestingScheduleResponse {
+        id,
+        denom: vesting.denom,
+        amount: vesting.amount,
+        claimed: vesting.claimed,
+        schedule: vesting.schedule,
+        revoked_at: vesting.revoked_at,
+    }
+}
+
+pub fn query_vesting_schedule(
+    deps: Deps,
+    address: String,
+    id: u64,
+) -> StdResult<VestingScheduleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let vesting = VESTINGS.load(deps.storage, (&address, id))?;
+    Ok(to_response(id, vesting))
+}
+
+pub fn query_all_vesting_schedules(
+    deps: Deps,
+    address: String,
+) -> StdResult<VestingSchedulesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedules = VESTINGS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(id, vesting)| to_response(id, vesting)))
+        .collect::<StdResult<_>>()?;
+    Ok(VestingSchedulesResponse { schedules })
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_vesting_schedules(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VestingSchedulesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let schedules = VESTINGS
+        .prefix(&address)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, vesting)| to_response(id, vesting)))
+        .collect::<StdResult<_>>()?;
+    Ok(VestingSchedulesResponse { schedules })
+}
+
+pub fn query_vested(
+    deps: Deps,
+    env: Env,
+    address: String,
+    id: u64,
+    at_time: Option<Timestamp>,
+) -> StdResult<VestedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let vesting = VESTINGS.load(deps.storage, (&address, id))?;
+    let at = at_time.unwrap_or(env.block.time);
+    Ok(VestedResponse {
+        vested: vesting.vested(at),
+        claimable: vesting.claimable(at),
+    })
+}
+
+pub fn query_vested_totals(
+    deps: Deps,
+    env: Env,
+    address: String,
+    at_time: Option<Timestamp>,
+) -> StdResult<VestedTotalsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let at = at_time.unwrap_or(env.block.time);
+
+    let mut totals: Vec<VestedTotal> = vec![];
+    for item in VESTINGS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, vesting) = item?;
+        let (vested, claimable) = (vesting.vested(at), vesting.claimable(at));
+        match totals.iter_mut().find(|total| total.denom == vesting.denom) {
+            Some(total) => {
+                total.vested += vested;
+                total.claimable += claimable;
+            }
+            None => totals.push(VestedTotal {
+                denom: vesting.denom,
+                vested,
+                claimable,
+            }),
+        }
+    }
+    Ok(VestedTotalsResponse { totals })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, Empty, SubMsg};
+    use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg};
+    use cw_controllers::AdminError;
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+    const ADMIN_ADDR: &str = "admin";
+    const ALICE: &str = "alice";
+    const DENOM: &str = "ustake";
+
+    fn linear(start: u64, end: u64, cliff: Option<u64>) -> Schedule {
+        Schedule::Linear {
+            start_time: Timestamp::from_seconds(start),
+            end_time: Timestamp::from_seconds(end),
+            cliff_time: cliff.map(Timestamp::from_seconds),
+        }
+    }
+
+    fn env_at(seconds: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    }
+
+    fn do_instantiate(deps: DepsMut, admin: Option<&str>) {
+        let msg = InstantiateMsg {
+            admin: admin.map(String::from),
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn create_native(deps: DepsMut, sender: &str, amount: u128, schedule: Schedule) -> u64 {
+        let msg = ExecuteMsg::Create {
+            beneficiary: ALICE.to_string(),
+            schedule,
+        };
+        let info = mock_info(sender, &coins(amount, DENOM));
+        let res = execute(deps, env_at(0), info, msg).unwrap();
+        let id = res.attributes.iter().find(|attr| attr.key == "id").unwrap();
+        id.value.parse().unwrap()
+    }
+
+    fn claim(deps: DepsMut, seconds: u64, id: u64) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::ClaimById { id };
+        execute(deps, env_at(seconds), mock_info(ALICE, &[]), msg)
+    }
+
+    #[test]
+    fn create_checks_schedule_and_permissions() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(ADMIN_ADDR));
+
+        // only the admin can create schedules
+        let msg = ExecuteMsg::Create {
+            beneficiary: ALICE.to_string(),
+            schedule: linear(100, 200, None),
+        };
+        let info = mock_info("anyone", &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), env_at(0), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+
+        // funds are required
+        let info = mock_info(ADMIN_ADDR, &[]);
+        let err = execute(deps.as_mut(), env_at(0), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+        // bad schedule
+        let msg = ExecuteMsg::Create {
+            beneficiary: ALICE.to_string(),
+            schedule: linear(200, 100, None),
+        };
+        let info = mock_info(ADMIN_ADDR, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), env_at(0), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSchedule { .. }));
+
+        let id = create_native(deps.as_mut(), ADMIN_ADDR, 1000, linear(100, 200, None));
+        assert_eq!(id, 1);
+        let vesting = query_vesting_schedule(deps.as_ref(), ALICE.to_string(), id).unwrap();
+        assert_eq!(
+            vesting,
+            VestingScheduleResponse {
+                id,
+                denom: Denom::Native(DENOM.to_string()),
+                amount: Uint128::new(1000),
+                claimed: Uint128::zero(),
+                schedule: linear(100, 200, None),
+                revoked_at: None,
+            }
+        );
+
+        // a second one can be created while the first is still running
+        let id = create_native(
+            deps.as_mut(),
+            ADMIN_ADDR,
+            500,
+            Schedule::Cliff {
+                time: Timestamp::from_seconds(300),
+            },
+        );
+        assert_eq!(id, 2);
+        let schedules =
+            query_vesting_schedules(deps.as_ref(), ALICE.to_string(), None, None).unwrap();
+        let ids: Vec<_> = schedules.schedules.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        let schedules =
+            query_vesting_schedules(deps.as_ref(), ALICE.to_string(), Some(1), None).unwrap();
+        assert_eq!(schedules.schedules.len(), 1);
+        assert_eq!(schedules.schedules[0].amount, Uint128::new(500));
+
+        // without an admin, anyone can create schedules
+        let msg = ExecuteMsg::UpdateAdmin { admin: None };
+        execute(deps.as_mut(), env_at(0), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        let id = create_native(deps.as_mut(), "anyone", 1000, linear(100, 200, None));
+        assert_eq!(id, 3);
+    }
+
+    #[test]
+    fn schedules_of_others_do_not_block_beneficiary() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), None);
+
+        // someone locks up a worthless amount for alice first
+        let griefing = create_native(deps.as_mut(), "anyone", 1, linear(0, 1_000_000, None));
+        let id = create_native(deps.as_mut(), "employer", 1000, linear(100, 200, None));
+        assert_ne!(griefing, id);
+
+        // the real schedule still vests and is claimed on its own
+        let res = claim(deps.as_mut(), 200, id).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(1000, DENOM),
+            })]
+        );
+        let err = claim(deps.as_mut(), 200, griefing).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // without an admin, nothing can be revoked
+        let msg = ExecuteMsg::Revoke {
+            address: ALICE.to_string(),
+            id: griefing,
+        };
+        let err = execute(deps.as_mut(), env_at(200), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+    }
+
+    #[test]
+    fn claim_all_schedules() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(ADMIN_ADDR));
+
+        // nothing to claim without schedules
+        let claim_all = ExecuteMsg::Claim {};
+        let err = execute(
+            deps.as_mut(),
+            env_at(0),
+            mock_info(ALICE, &[]),
+            claim_all.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoSchedule {});
+
+        create_native(deps.as_mut(), ADMIN_ADDR, 1000, linear(100, 200, None));
+        create_native(deps.as_mut(), ADMIN_ADDR, 400, linear(100, 300, None));
+        let msg = ExecuteMsg::Create {
+            beneficiary: ALICE.to_string(),
+            schedule: Schedule::Cliff {
+                time: Timestamp::from_seconds(200),
+            },
+        };
+        let info = mock_info(ADMIN_ADDR, &coins(300, "uatom"));
+        execute(deps.as_mut(), env_at(0), info, msg).unwrap();
+
+        let schedules = query_all_vesting_schedules(deps.as_ref(), ALICE.to_string()).unwrap();
+        let ids: Vec<_> = schedules.schedules.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        // the vested amounts are summed up per denom
+        let vested =
+            query_vested_totals(deps.as_ref(), env_at(150), ALICE.to_string(), None).unwrap();
+        assert_eq!(
+            vested.totals,
+            vec![
+                VestedTotal {
+                    denom: Denom::Native(DENOM.to_string()),
+                    vested: Uint128::new(600),
+                    claimable: Uint128::new(600),
+                },
+                VestedTotal {
+                    denom: Denom::Native("uatom".to_string()),
+                    vested: Uint128::zero(),
+                    claimable: Uint128::zero(),
+                },
+            ]
+        );
+
+        // one message for all schedules of the same denom
+        let info = mock_info(ALICE, &[]);
+        let res = execute(deps.as_mut(), env_at(150), info.clone(), claim_all.clone()).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(600, DENOM),
+            })]
+        );
+        let err = execute(deps.as_mut(), env_at(150), info.clone(), claim_all.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // and one per denom
+        let res = execute(deps.as_mut(), env_at(200), info, claim_all).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: ALICE.to_string(),
+                    amount: coins(600, DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: ALICE.to_string(),
+                    amount: coins(300, "uatom"),
+                }),
+            ]
+        );
+        let vested =
+            query_vested_totals(deps.as_ref(), env_at(200), ALICE.to_string(), None).unwrap();
+        let claimable: Vec<_> = vested.totals.iter().map(|t| t.claimable).collect();
+        assert_eq!(claimable, vec![Uint128::zero(), Uint128::zero()]);
+    }
+
+    #[test]
+    fn claim_linear_with_cliff() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(ADMIN_ADDR));
+        let id = create_native(deps.as_mut(), ADMIN_ADDR, 1000, linear(100, 200, Some(150)));
+
+        // nothing before the cliff
+        let vested = query_vested(deps.as_ref(), env_at(149), ALICE.to_string(), id, None).unwrap();
+        assert_eq!(vested.vested, Uint128::zero());
+        let err = claim(deps.as_mut(), 149, id).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // half at the cliff
+        let res = claim(deps.as_mut(), 150, id).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(500, DENOM),
+            })]
+        );
+
+        // the rest accrues linearly
+        let at = Some(Timestamp::from_seconds(180));
+        let vested = query_vested(deps.as_ref(), env_at(150), ALICE.to_string(), id, at).unwrap();
+        assert_eq!(
+            vested,
+            VestedResponse {
+                vested: Uint128::new(800),
+                claimable: Uint128::new(300),
+            }
+        );
+
+        // and everything can be claimed after the end, which finishes the schedule
+        let res = claim(deps.as_mut(), 500, id).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(500, DENOM),
+            })]
+        );
+        let err = claim(deps.as_mut(), 600, id).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // no schedule at all
+        let err = execute(
+            deps.as_mut(),
+            env_at(500),
+            mock_info("bob", &[]),
+            ExecuteMsg::ClaimById { id },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoSchedule {});
+    }
+
+    #[test]
+    fn revoke_unvested() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(ADMIN_ADDR));
+        let schedule = Schedule::Step {
+            start_time: Timestamp::from_seconds(100),
+            interval: 10,
+            steps: 4,
+        };
+        let id = create_native(deps.as_mut(), ADMIN_ADDR, 1000, schedule);
+
+        // only the admin can revoke
+        let msg = ExecuteMsg::Revoke {
+            address: ALICE.to_string(),
+            id,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env_at(125),
+            mock_info(ALICE, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+
+        // two steps are vested, the other two go back to the admin
+        let res = execute(
+            deps.as_mut(),
+            env_at(125),
+            mock_info(ADMIN_ADDR, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADMIN_ADDR.to_string(),
+                amount: coins(500, DENOM),
+            })]
+        );
+        let err = execute(deps.as_mut(), env_at(126), mock_info(ADMIN_ADDR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyRevoked {});
+
+        // the vested part can still be claimed, but nothing more vests
+        let vested =
+            query_vested(deps.as_ref(), env_at(1000), ALICE.to_string(), id, None).unwrap();
+        assert_eq!(vested.claimable, Uint128::new(500));
+        let res = claim(deps.as_mut(), 1000, id).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ALICE.to_string(),
+                amount: coins(500, DENOM),
+            })]
+        );
+    }
+
+    #[test]
+    fn revoke_fully_vested() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(ADMIN_ADDR));
+        let schedule = Schedule::Cliff {
+            time: Timestamp::from_seconds(100),
+        };
+        let id = create_native(deps.as_mut(), ADMIN_ADDR, 1000, schedule);
+
+        let msg = ExecuteMsg::Revoke {
+            address: ALICE.to_string(),
+            id,
+        };
+        let err = execute(deps.as_mut(), env_at(100), mock_info(ADMIN_ADDR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToRevoke {});
+    }
+
+    fn contract_vesting() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    fn contract_cw20() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn vest_cw20_tokens() {
+        let admin = Addr::unchecked(ADMIN_ADDR);
+        let alice = Addr::unchecked(ALICE);
+        let mut app = App::default();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let token = app
+            .instantiate_contract(
+                cw20_id,
+                admin.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Team Token".to_string(),
+                    symbol: "TEAM".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: admin.to_string(),
+                        amount: Uint128::new(1000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "TEAM",
+                None,
+            )
+            .unwrap();
+
+        let vesting_id = app.store_code(contract_vesting());
+        let vesting = app
+            .instantiate_contract(
+                vesting_id,
+                admin.clone(),
+                &InstantiateMsg {
+                    admin: Some(admin.to_string()),
+                },
+                &[],
+                "vesting",
+                None,
+            )
+            .unwrap();
+
+        let start = app.block_info().time;
+        let schedule = Schedule::Linear {
+            start_time: start,
+            end_time: start.plus_seconds(1000),
+            cliff_time: None,
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: vesting.to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::Create {
+                beneficiary: alice.to_string(),
+                schedule,
+            })
+            .unwrap(),
+        };
+        app.execute_contract(admin.clone(), token.clone(), &msg, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(250));
+        let claim = ExecuteMsg::Claim {};
+        app.execute_contract(alice.clone(), vesting.clone(), &claim, &[])
+            .unwrap();
+
+        let balance = |app: &App, addr: &Addr| {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &token,
+                    &Cw20QueryMsg::Balance {
+                        address: addr.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance.u128()
+        };
+        assert_eq!(balance(&app, &alice), 250);
+        assert_eq!(balance(&app, &vesting), 750);
+
+        // revoking returns the unvested tokens to the admin
+        app.update_block(|block| block.time = block.time.plus_seconds(250));
+        let msg = ExecuteMsg::Revoke {
+            address: alice.to_string(),
+            id: 1,
+        };
+        app.execute_contract(admin.clone(), vesting.clone(), &msg, &[])
+            .unwrap();
+        assert_eq!(balance(&app, &admin), 500);
+        assert_eq!(balance(&app, &vesting), 250);
+
+        app.execute_contract(alice.clone(), vesting.clone(), &claim, &[])
+            .unwrap();
+        assert_eq!(balance(&app, &alice), 500);
+        assert_eq!(balance(&app, &vesting), 0);
+    }
+}
@@ -0,0 +1,31 @@
+use cosmwasm_std::StdError;
+use cw_controllers::AdminError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Invalid vesting schedule: {reason}")]
+    InvalidSchedule { reason: String },
+
+    #[error("No vesting schedule for this address")]
+    NoSchedule {},
+
+    #[error("Nothing to claim yet")]
+    NothingToClaim {},
+
+    #[error("Vesting schedule is already revoked")]
+    AlreadyRevoked {},
+
+    #[error("Nothing left to revoke, everything is already vested")]
+    NothingToRevoke {},
+}
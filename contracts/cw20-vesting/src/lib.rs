@@ -0,0 +1,19 @@
+/*!
+This is a vesting contract locking up native or cw20 tokens for a number of beneficiaries,
+e.g. team and investor allocations. Each beneficiary has one vesting schedule, releasing
+tokens over block time with a cliff, linearly or in equal steps.
+
+Schedules are funded on creation, either with native tokens sent along with the message or
+by sending cw20 tokens to this contract. The beneficiary can claim vested tokens at any time.
+An optional admin can revoke the unvested part of a schedule, which is then returned to them.
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-vesting/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,121 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Timestamp, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
+
+use crate::state::Schedule;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// The admin can revoke unvested tokens. If set, only the admin can create schedules,
+    /// otherwise anyone can.
+    pub admin: Option<String>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Creates a new vesting schedule for the beneficiary, locking up the native tokens sent
+    /// along with this message. Exactly one denom must be sent. If there is an admin, only
+    /// the admin can do this.
+    Create {
+        beneficiary: String,
+        schedule: Schedule,
+    },
+    /// Sends all vested, but not yet claimed tokens of all the sender's schedules to the sender
+    Claim {},
+    /// Like `Claim`, but only for the sender's schedule `id`. Use this if the sender has too
+    /// many schedules to claim them all at once.
+    ClaimById { id: u64 },
+    /// Stops the vesting schedule `id` of the given beneficiary and returns all unvested tokens
+    /// to the admin. Already vested tokens can still be claimed. Only the admin can do this.
+    Revoke { address: String, id: u64 },
+    /// Change the admin
+    UpdateAdmin { admin: Option<String> },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Creates a new vesting schedule for the beneficiary, locking up the received cw20 tokens.
+    /// If there is an admin, only the admin can do this.
+    Create {
+        beneficiary: String,
+        schedule: Schedule,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns all vesting schedules of the given beneficiary, ordered by id
+    #[returns(VestingSchedulesResponse)]
+    VestingSchedule { address: String },
+    /// Returns the vesting schedule `id` of the given beneficiary, error if there is none
+    #[returns(VestingScheduleResponse)]
+    VestingScheduleById { address: String, id: u64 },
+    /// Lists all vesting schedules of the given beneficiary. Supports pagination.
+    #[returns(VestingSchedulesResponse)]
+    VestingSchedules {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns how many tokens of all the beneficiary's schedules have vested at the given time,
+    /// or the current block time if not set, summed up per denom
+    #[returns(VestedTotalsResponse)]
+    Vested {
+        address: String,
+        at_time: Option<Timestamp>,
+    },
+    /// Returns how many tokens of the beneficiary's schedule `id` have vested at the given time,
+    /// or the current block time if not set
+    #[returns(VestedResponse)]
+    VestedById {
+        address: String,
+        id: u64,
+        at_time: Option<Timestamp>,
+    },
+    #[returns(cw_controllers::AdminResponse)]
+    Admin {},
+}
+
+#[cw_serde]
+pub struct VestingScheduleResponse {
+    pub id: u64,
+    pub denom: Denom,
+    /// Total amount locked up on creation
+    pub amount: Uint128,
+    /// Amount already sent to the beneficiary
+    pub claimed: Uint128,
+    pub schedule: Schedule,
+    /// Set when the admin revoked the unvested tokens
+    pub revoked_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct VestingSchedulesResponse {
+    pub schedules: Vec<VestingScheduleResponse>,
+}
+
+#[cw_serde]
+pub struct VestedResponse {
+    /// Total amount vested at the given time, including the claimed tokens
+    pub vested: Uint128,
+    /// Vested tokens that have not yet been claimed
+    pub claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct VestedTotal {
+    pub denom: Denom,
+    /// Total amount vested at the given time, including the claimed tokens
+    pub vested: Uint128,
+    /// Vested tokens that have not yet been claimed
+    pub claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct VestedTotalsResponse {
+    /// One entry per denom, in the order of the first schedule holding it
+    pub totals: Vec<VestedTotal>,
+}
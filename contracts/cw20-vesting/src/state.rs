@@ -0,0 +1,206 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw20::Denom;
+use cw_controllers::Admin;
+use cw_storage_plus::{Item, Map};
+
+use crate::error::ContractError;
+
+/// Describes how the tokens of a vesting schedule are released over block time.
+#[cw_serde]
+pub enum Schedule {
+    /// Everything vests at once at `time`
+    Cliff { time: Timestamp },
+    /// Tokens vest linearly from `start_time` to `end_time`.
+    /// If `cliff_time` is set, nothing vests before it, and everything accrued since
+    /// `start_time` is released at once when it is reached.
+    Linear {
+        start_time: Timestamp,
+        end_time: Timestamp,
+        cliff_time: Option<Timestamp>,
+    },
+    /// The amount is split into `steps` equal parts, with one part vesting every `interval`
+    /// seconds after `start_time`. Any rounding leftover vests with the last step.
+    Step {
+        start_time: Timestamp,
+        interval: u64,
+        steps: u64,
+    },
+}
+
+impl Schedule {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let invalid = |reason: &str| {
+            Err(ContractError::InvalidSchedule {
+                reason: reason.to_string(),
+            })
+        };
+        match self {
+            Schedule::Cliff { .. } => Ok(()),
+            Schedule::Linear {
+                start_time,
+                end_time,
+                cliff_time,
+            } => {
+                if end_time <= start_time {
+                    return invalid("end_time must be after start_time");
+                }
+                match cliff_time {
+                    Some(cliff) if cliff < start_time || cliff > end_time => {
+                        invalid("cliff_time must be between start_time and end_time")
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Schedule::Step {
+                interval, steps, ..
+            } => {
+                if *interval == 0 {
+                    return invalid("interval must be positive");
+                }
+                if *steps == 0 {
+                    return invalid("steps must be positive");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns how much of `amount` has vested at block time `at`
+    pub fn vested(&self, amount: Uint128, at: Timestamp) -> Uint128 {
+        match self {
+            Schedule::Cliff { time } => {
+                if at >= *time {
+                    amount
+                } else {
+                    Uint128::zero()
+                }
+            }
+            Schedule::Linear {
+                start_time,
+                end_time,
+                cliff_time,
+            } => {
+                let before_cliff = matches!(cliff_time, Some(cliff) if at < *cliff);
+                if at <= *start_time || before_cliff {
+                    Uint128::zero()
+                } else if at >= *end_time {
+                    amount
+                } else {
+                    let elapsed = at.seconds() - start_time.seconds();
+                    let duration = end_time.seconds() - start_time.seconds();
+                    amount.multiply_ratio(elapsed, duration)
+                }
+            }
+            Schedule::Step {
+                start_time,
+                interval,
+                steps,
+            } => {
+                let elapsed = at.seconds().saturating_sub(start_time.seconds());
+                let done = (elapsed / interval).min(*steps);
+                amount.multiply_ratio(done, *steps)
+            }
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Vesting {
+    /// The token that is vested
+    pub denom: Denom,
+    /// Total amount locked up on creation
+    pub amount: Uint128,
+    /// Amount already sent to the beneficiary
+    pub claimed: Uint128,
+    pub schedule: Schedule,
+    /// When set, nothing vests after this time anymore
+    pub revoked_at: Option<Timestamp>,
+}
+
+impl Vesting {
+    /// Returns the amount vested at block time `at`, taking a revocation into account
+    pub fn vested(&self, at: Timestamp) -> Uint128 {
+        let at = match self.revoked_at {
+            Some(revoked) if revoked < at => revoked,
+            _ => at,
+        };
+        self.schedule.vested(self.amount, at)
+    }
+
+    /// Returns the amount that can be claimed at block time `at`
+    pub fn claimable(&self, at: Timestamp) -> Uint128 {
+        self.vested(at).saturating_sub(self.claimed)
+    }
+}
+
+pub const ADMIN: Admin = Admin::new("admin");
+
+/// Last used schedule id
+pub const VESTING_SEQ: Item<u64> = Item::new("vesting_seq");
+/// Vesting schedules by beneficiary and id, so a beneficiary can have any number of them
+pub const VESTINGS: Map<(&Addr, u64), Vesting> = Map::new("vestings");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> Timestamp {
+        Timestamp::from_seconds(seconds)
+    }
+
+    #[test]
+    fn step_vesting() {
+        let schedule = Schedule::Step {
+            start_time: at(100),
+            interval: 10,
+            steps: 3,
+        };
+        schedule.validate().unwrap();
+        let amount = Uint128::new(1000);
+        assert_eq!(schedule.vested(amount, at(50)), Uint128::zero());
+        assert_eq!(schedule.vested(amount, at(109)), Uint128::zero());
+        assert_eq!(schedule.vested(amount, at(110)), Uint128::new(333));
+        assert_eq!(schedule.vested(amount, at(125)), Uint128::new(666));
+        // the rounding leftover comes with the last step
+        assert_eq!(schedule.vested(amount, at(130)), amount);
+        assert_eq!(schedule.vested(amount, at(1000)), amount);
+    }
+
+    #[test]
+    fn validate_schedules() {
+        let invalid = [
+            Schedule::Linear {
+                start_time: at(100),
+                end_time: at(100),
+                cliff_time: None,
+            },
+            Schedule::Linear {
+                start_time: at(100),
+                end_time: at(200),
+                cliff_time: Some(at(50)),
+            },
+            Schedule::Linear {
+                start_time: at(100),
+                end_time: at(200),
+                cliff_time: Some(at(201)),
+            },
+            Schedule::Step {
+                start_time: at(100),
+                interval: 0,
+                steps: 3,
+            },
+            Schedule::Step {
+                start_time: at(100),
+                interval: 10,
+                steps: 0,
+            },
+        ];
+        for schedule in invalid {
+            assert!(matches!(
+                schedule.validate().unwrap_err(),
+                ContractError::InvalidSchedule { .. }
+            ));
+        }
+    }
+}
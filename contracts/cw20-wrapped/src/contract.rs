@@ -0,0 +1,573 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, to_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Uint128,
+};
+
+use cw2::set_contract_version;
+use cw20::MinterResponse;
+use cw20_base::allowances::{
+    execute_decrease_allowance, execute_increase_allowance, execute_send_from,
+    execute_transfer_from, query_allowance,
+};
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_remove_metadata, execute_send, execute_set_metadata,
+    execute_transfer, execute_update_marketing, execute_upload_logo, query_balance,
+    query_download_logo, query_marketing_info, query_metadata, query_token_info,
+};
+use cw20_base::enumerable::{
+    query_all_accounts, query_all_metadata, query_owner_allowances, query_spender_allowances,
+};
+use cw_utils::{must_pay, nonpayable};
+
+use crate::error::ContractError;
+use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, InvariantResponse, QueryMsg};
+use crate::state::{Config, CONFIG};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-wrapped";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.denom.is_empty() {
+        return Err(ContractError::EmptyDenom {});
+    }
+
+    // the contract itself is the only minter, minting only happens on deposits
+    let base_msg = cw20_base::msg::InstantiateMsg {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: env.contract.address.to_string(),
+            cap: None,
+        }),
+        marketing: msg.marketing,
+    };
+    cw20_base::contract::instantiate(deps.branch(), env, info, base_msg)?;
+
+    // overwrite the version set by cw20-base
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(deps.storage, &Config { denom: msg.denom })?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
+        ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, env, info, amount),
+
+        // these all come from cw20-base to implement the cw20 standard
+        ExecuteMsg::Transfer { recipient, amount } => {
+            Ok(execute_transfer(deps, env, info, recipient, amount)?)
+        }
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send(deps, env, info, contract, amount, msg)?),
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => Ok(execute_transfer_from(
+            deps, env, info, owner, recipient, amount,
+        )?),
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send_from(
+            deps, env, info, owner, contract, amount, msg,
+        )?),
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => Ok(execute_update_marketing(
+            deps,
+            env,
+            info,
+            project,
+            description,
+            marketing,
+        )?),
+        ExecuteMsg::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
+        ExecuteMsg::SetMetadata { key, value } => {
+            Ok(execute_set_metadata(deps, env, info, key, value)?)
+        }
+        ExecuteMsg::RemoveMetadata { key } => Ok(execute_remove_metadata(deps, env, info, key)?),
+    }
+}
+
+pub fn execute_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let amount = must_pay(&info, &cfg.denom)?;
+
+    // the contract is the minter, so it mints on behalf of itself
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    execute_mint(deps, env, sub_info, info.sender.to_string(), amount)?;
+
+    let res = Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // this fails on zero amount or insufficient balance
+    execute_burn(deps, env, info.clone(), amount)?;
+
+    let msg = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: coins(amount.u128(), cfg.denom),
+    };
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute("action", "withdraw")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Invariant {} => to_binary(&query_invariant(deps, env)?),
+
+        // inherited from cw20-base
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::Metadata { key } => to_binary(&query_metadata(deps, key)?),
+        QueryMsg::AllMetadata { start_after, limit } => {
+            to_binary(&query_all_metadata(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse { denom: cfg.denom })
+}
+
+pub fn query_invariant(deps: Deps, env: Env) -> StdResult<InvariantResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let total_supply = query_token_info(deps)?.total_supply;
+    let reserve = deps
+        .querier
+        .query_balance(env.contract.address, cfg.denom)?
+        .amount;
+    Ok(InvariantResponse {
+        total_supply,
+        reserve,
+        surplus: reserve.saturating_sub(total_supply),
+        holds: reserve >= total_supply,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coin, from_binary, Addr, CosmosMsg, Empty, StdError, SubMsg};
+    use cw20::{BalanceResponse, TokenInfoResponse};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_utils::PaymentError;
+
+    use super::*;
+
+    const DENOM: &str = "ucosm";
+
+    fn do_instantiate(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            name: "Wrapped Cosm".to_string(),
+            symbol: "WCOSM".to_string(),
+            decimals: 6,
+            denom: DENOM.to_string(),
+            marketing: None,
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn get_balance(deps: Deps, address: &str) -> Uint128 {
+        query_balance(deps, address.to_string()).unwrap().balance
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap(),
+            TokenInfoResponse {
+                name: "Wrapped Cosm".to_string(),
+                symbol: "WCOSM".to_string(),
+                decimals: 6,
+                total_supply: Uint128::zero(),
+            }
+        );
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap(),
+            ConfigResponse {
+                denom: DENOM.to_string()
+            }
+        );
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+
+        // empty denom is rejected
+        let msg = InstantiateMsg {
+            name: "Wrapped Cosm".to_string(),
+            symbol: "WCOSM".to_string(),
+            decimals: 6,
+            denom: "".to_string(),
+            marketing: None,
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyDenom {});
+    }
+
+    #[test]
+    fn deposit_mints_tokens() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let info = mock_info("alice", &coins(1234, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(1234));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(1234)
+        );
+
+        // wrong denom
+        let info = mock_info("alice", &coins(100, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(PaymentError::MissingDenom(DENOM.to_string()))
+        );
+
+        // no funds
+        let info = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+        // multiple denoms
+        let info = mock_info("alice", &[coin(100, DENOM), coin(100, "uatom")]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::MultipleDenoms {}));
+
+        assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(1234));
+    }
+
+    #[test]
+    fn withdraw_burns_tokens() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let info = mock_info("alice", &coins(1000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(400),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(400, DENOM),
+            })]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(600));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(600)
+        );
+
+        // cannot withdraw more than owned
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(601),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Base(cw20_base::ContractError::Std(StdError::Overflow { .. }))
+        ));
+
+        // cannot withdraw zero
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::zero(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Base(cw20_base::ContractError::InvalidZeroAmount {})
+        );
+
+        // withdraw is not payable
+        let info = mock_info("alice", &coins(1, DENOM));
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NonPayable {}));
+    }
+
+    #[test]
+    fn nobody_can_mint() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        // even the creator cannot call mint directly through the base contract logic
+        let err = execute_mint(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            "creator".to_string(),
+            Uint128::new(100),
+        )
+        .unwrap_err();
+        assert_eq!(err, cw20_base::ContractError::Unauthorized {});
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn invariant_query() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let info = mock_info("alice", &coins(500, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(500, DENOM));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Invariant {}).unwrap();
+        let invariant: InvariantResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            invariant,
+            InvariantResponse {
+                total_supply: Uint128::new(500),
+                reserve: Uint128::new(500),
+                surplus: Uint128::zero(),
+                holds: true,
+            }
+        );
+
+        // someone sent tokens directly, which is a surplus and not a solvency problem
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(700, DENOM));
+        let invariant = query_invariant(deps.as_ref(), mock_env()).unwrap();
+        assert!(invariant.holds);
+        assert_eq!(invariant.reserve, Uint128::new(700));
+        assert_eq!(invariant.surplus, Uint128::new(200));
+
+        // the reserve falling below the supply breaks it
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(499, DENOM));
+        let invariant = query_invariant(deps.as_ref(), mock_env()).unwrap();
+        assert!(!invariant.holds);
+        assert_eq!(invariant.surplus, Uint128::zero());
+    }
+
+    fn contract_wrapped() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        Box::new(contract)
+    }
+
+    #[test]
+    fn wrap_and_unwrap_with_bank() {
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice, coins(1000, DENOM))
+                .unwrap();
+        });
+
+        let code_id = app.store_code(contract_wrapped());
+        let msg = InstantiateMsg {
+            name: "Wrapped Cosm".to_string(),
+            symbol: "WCOSM".to_string(),
+            decimals: 6,
+            denom: DENOM.to_string(),
+            marketing: None,
+        };
+        let wrapped = app
+            .instantiate_contract(code_id, alice.clone(), &msg, &[], "wrapped", None)
+            .unwrap();
+
+        let invariant = |app: &App| -> InvariantResponse {
+            app.wrap()
+                .query_wasm_smart(&wrapped, &QueryMsg::Invariant {})
+                .unwrap()
+        };
+
+        app.execute_contract(
+            alice.clone(),
+            wrapped.clone(),
+            &ExecuteMsg::Deposit {},
+            &coins(800, DENOM),
+        )
+        .unwrap();
+        assert!(invariant(&app).holds);
+        assert_eq!(invariant(&app).reserve, Uint128::new(800));
+
+        // move some wrapped tokens to bob, who unwraps them
+        app.execute_contract(
+            alice.clone(),
+            wrapped.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: bob.to_string(),
+                amount: Uint128::new(300),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            bob.clone(),
+            wrapped.clone(),
+            &ExecuteMsg::Withdraw {
+                amount: Uint128::new(300),
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(&bob, DENOM).unwrap(),
+            coin(300, DENOM)
+        );
+        assert_eq!(
+            app.wrap().query_balance(&alice, DENOM).unwrap(),
+            coin(200, DENOM)
+        );
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &wrapped,
+                &QueryMsg::Balance {
+                    address: alice.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(500));
+        assert_eq!(
+            invariant(&app),
+            InvariantResponse {
+                total_supply: Uint128::new(500),
+                reserve: Uint128::new(500),
+                surplus: Uint128::zero(),
+                holds: true,
+            }
+        );
+
+        // bob cannot withdraw more
+        app.execute_contract(
+            bob,
+            wrapped.clone(),
+            &ExecuteMsg::Withdraw {
+                amount: Uint128::new(1),
+            },
+            &[],
+        )
+        .unwrap_err();
+        assert!(invariant(&app).holds);
+
+        // direct mint messages are not part of the interface
+        let err = app
+            .execute(
+                alice,
+                CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+                    contract_addr: wrapped.to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Burn {
+                        amount: Uint128::new(1),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+            )
+            .unwrap_err();
+        assert!(err.root_cause().to_string().contains("unknown variant"));
+    }
+}
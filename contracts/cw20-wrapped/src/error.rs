@@ -0,0 +1,18 @@
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw20_base::ContractError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Native denom must not be empty")]
+    EmptyDenom {},
+}
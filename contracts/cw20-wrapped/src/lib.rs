@@ -0,0 +1,20 @@
+/*!
+This is a "wrapped native" token, much like WETH on Ethereum. It accepts deposits of a single
+native denom and mints the same amount of cw20 tokens to the depositor. Those can be withdrawn
+at any time, burning the cw20 tokens and releasing the native coins again.
+
+This allows contracts written against the
+[CW20 spec](https://github.com/CosmWasm/cw-plus/blob/main/packages/cw20/README.md) to handle the
+native denom of the chain as well. All the cw20 logic is imported from
+[cw20-base](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-base/README.md).
+
+For more information on this contract, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/contracts/cw20-wrapped/README.md).
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,161 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Uint128};
+use cw20::{Expiration, Logo};
+
+pub use cw20_base::msg::InstantiateMarketingInfo;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// name of the wrapped token
+    pub name: String,
+    /// symbol / ticker of the wrapped token
+    pub symbol: String,
+    /// decimal places of the wrapped token, should match the native denom
+    pub decimals: u8,
+    /// the native denom which can be wrapped
+    pub denom: String,
+    pub marketing: Option<InstantiateMarketingInfo>,
+}
+
+/// All the cw20 messages, besides the ones that would change the total supply without
+/// touching the reserve (`Burn`, `BurnFrom`, `Mint` and `UpdateMinter`)
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Deposit mints the same amount of cw20 tokens as native tokens sent along with the message
+    Deposit {},
+    /// Withdraw burns `amount` cw20 tokens of the sender and sends back the native tokens
+    Withdraw { amount: Uint128 },
+
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// If authorized, updates marketing metadata.
+    /// Setting None/null for any of these will leave it unchanged.
+    /// Setting Some("") will clear this field on the contract storage
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, PNG, WebP or JPEG
+    /// for the token
+    UploadLogo(Logo),
+    /// If authorized, sets an arbitrary metadata entry
+    SetMetadata { key: String, value: String },
+    /// If authorized, removes the metadata entry stored under this key
+    RemoveMetadata { key: String },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the native denom backing this token
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Compares the total supply of the cw20 token with the native balance of the contract
+    #[returns(InvariantResponse)]
+    Invariant {},
+
+    /// Returns the current balance of the given address, 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    /// Returns how much spender can use from owner account, 0 if unset.
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns more metadata on the contract to display in the client:
+    /// - description, logo, project url, etc.
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for
+    /// this contract.
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    #[returns(cw20::MetadataResponse)]
+    Metadata { key: String },
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    #[returns(cw20::AllMetadataResponse)]
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub denom: String,
+}
+
+#[cw_serde]
+pub struct InvariantResponse {
+    /// Total supply of the wrapped cw20 token
+    pub total_supply: Uint128,
+    /// Native balance of the contract in the wrapped denom
+    pub reserve: Uint128,
+    /// Native tokens in the reserve which do not back any wrapped token, eg. sent directly to
+    /// the contract instead of using `Deposit`
+    pub surplus: Uint128,
+    /// True if every wrapped token is backed by at least one native token
+    pub holds: bool,
+}
@@ -0,0 +1,58 @@
+use cosmwasm_std::Addr;
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw3::{Cw3QueryMsg, ProposalResponse, Status};
+use cw4::Cw4Contract;
+use cw_multi_test::App;
+
+/// Asserts the native balance of `addr` in `denom`
+#[track_caller]
+pub fn assert_native_balance(app: &App, addr: &str, denom: &str, expected: u128) {
+    let balance = app.wrap().query_balance(addr, denom).unwrap();
+    assert_eq!(
+        balance.amount.u128(),
+        expected,
+        "native balance of {} in {}",
+        addr,
+        denom
+    );
+}
+
+/// Asserts the balance of `addr` on the cw20 contract `token`
+#[track_caller]
+pub fn assert_cw20_balance(app: &App, token: &Addr, addr: &str, expected: u128) {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.balance.u128(),
+        expected,
+        "cw20 balance of {} on {}",
+        addr,
+        token
+    );
+}
+
+/// Asserts the status of a proposal on any cw3 contract
+#[track_caller]
+pub fn assert_proposal_status(app: &App, multisig: &Addr, proposal_id: u64, expected: Status) {
+    let res: ProposalResponse = app
+        .wrap()
+        .query_wasm_smart(multisig, &Cw3QueryMsg::Proposal { proposal_id })
+        .unwrap();
+    assert_eq!(res.status, expected, "status of proposal {}", proposal_id);
+}
+
+/// Asserts the current weight of `addr` in any cw4 group, `None` if it is not a member
+#[track_caller]
+pub fn assert_group_weight(app: &App, group: &Addr, addr: &str, expected: Option<u64>) {
+    let weight = Cw4Contract::new(group.clone())
+        .is_member(&app.wrap(), &Addr::unchecked(addr), None)
+        .unwrap();
+    assert_eq!(weight, expected, "weight of {} in {}", addr, group);
+}
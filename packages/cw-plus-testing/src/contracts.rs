@@ -0,0 +1,197 @@
+//! `ContractWrapper` constructors for all cw-plus contracts, named after their crates.
+//! Every contract is wrapped with all the entry points supported by multi-test.
+
+use cosmwasm_std::Empty;
+use cw_multi_test::{Contract, ContractWrapper};
+
+pub fn cw1_subkeys() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw1_subkeys::contract::execute,
+        cw1_subkeys::contract::instantiate,
+        cw1_subkeys::contract::query,
+    )
+    .with_migrate(cw1_subkeys::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn cw1_whitelist() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw1_whitelist::contract::execute,
+        cw1_whitelist::contract::instantiate,
+        cw1_whitelist::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw3_fixed_multisig() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw3_fixed_multisig::contract::execute,
+        cw3_fixed_multisig::contract::instantiate,
+        cw3_fixed_multisig::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw3_flex_multisig() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw3_flex_multisig::contract::execute,
+        cw3_flex_multisig::contract::instantiate,
+        cw3_flex_multisig::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw4_group() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw4_group::contract::execute,
+        cw4_group::contract::instantiate,
+        cw4_group::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw4_stake() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw4_stake::contract::execute,
+        cw4_stake::contract::instantiate,
+        cw4_stake::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_atomic_swap() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_atomic_swap::contract::execute,
+        cw20_atomic_swap::contract::instantiate,
+        cw20_atomic_swap::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_base() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    )
+    .with_migrate(cw20_base::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn cw20_bonding() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_bonding::contract::execute,
+        cw20_bonding::contract::instantiate,
+        cw20_bonding::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_escrow() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_escrow::contract::execute,
+        cw20_escrow::contract::instantiate,
+        cw20_escrow::contract::query,
+    );
+    Box::new(contract)
+}
+
+/// Multi-test does not support IBC, so this only covers the non-IBC entry points,
+/// e.g. to test the configuration and the allow list.
+pub fn cw20_ics20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_ics20::contract::execute,
+        cw20_ics20::contract::instantiate,
+        cw20_ics20::contract::query,
+    )
+    .with_reply(cw20_ics20::ibc::reply)
+    .with_migrate(cw20_ics20::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn cw20_merkle_airdrop() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_merkle_airdrop::contract::execute,
+        cw20_merkle_airdrop::contract::instantiate,
+        cw20_merkle_airdrop::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_rewards() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_rewards::contract::execute,
+        cw20_rewards::contract::instantiate,
+        cw20_rewards::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_staking() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_staking::contract::execute,
+        cw20_staking::contract::instantiate,
+        cw20_staking::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_streams() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_streams::contract::execute,
+        cw20_streams::contract::instantiate,
+        cw20_streams::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_vesting() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_vesting::contract::execute,
+        cw20_vesting::contract::instantiate,
+        cw20_vesting::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn cw20_wrapped() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_wrapped::contract::execute,
+        cw20_wrapped::contract::instantiate,
+        cw20_wrapped::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_multi_test::App;
+
+    #[test]
+    fn all_contracts_can_be_stored() {
+        let mut app = App::default();
+        let contracts = [
+            cw1_subkeys(),
+            cw1_whitelist(),
+            cw3_fixed_multisig(),
+            cw3_flex_multisig(),
+            cw4_group(),
+            cw4_stake(),
+            cw20_atomic_swap(),
+            cw20_base(),
+            cw20_bonding(),
+            cw20_escrow(),
+            cw20_ics20(),
+            cw20_merkle_airdrop(),
+            cw20_rewards(),
+            cw20_staking(),
+            cw20_streams(),
+            cw20_vesting(),
+            cw20_wrapped(),
+        ];
+        for (i, contract) in contracts.into_iter().enumerate() {
+            assert_eq!(app.store_code(contract), i as u64 + 1);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/*!
+Helpers to test against the real cw-plus contracts with [`cw_multi_test`].
+
+This provides:
+
+* `ContractWrapper` constructors for every contract in this repo, to store them on an `App`
+  (see [`contracts`])
+* Typed suites setting up common scenarios in a few lines, like a cw20 token with initial
+  balances and minter ([`Cw20SuiteBuilder`]), or a `cw3-flex-multisig` voting with
+  a `cw4-group` ([`MultisigSuiteBuilder`])
+* Assertion helpers for native and cw20 balances, proposal status and group weights
+
+For more information on this package, please check out the
+[README](https://github.com/CosmWasm/cw-plus/blob/main/packages/cw-plus-testing/README.md).
+*/
+
+mod assertions;
+pub mod contracts;
+mod multisig;
+mod token;
+
+pub use crate::assertions::{
+    assert_cw20_balance, assert_group_weight, assert_native_balance, assert_proposal_status,
+};
+pub use crate::multisig::{MultisigSuite, MultisigSuiteBuilder};
+pub use crate::token::{Cw20Suite, Cw20SuiteBuilder};
@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result as AnyResult};
+use cosmwasm_std::{Addr, Coin, CosmosMsg};
+use cw3::{ProposalResponse, Status, Vote};
+use cw3_flex_multisig::state::Executor;
+use cw4::Member;
+use cw_multi_test::{App, AppResponse, Executor as _};
+use cw_utils::{Duration, Threshold};
+
+use crate::contracts;
+
+/// Builds a [`MultisigSuite`], with a `cw3-flex-multisig` voting with the members of
+/// a `cw4-group`. The multisig is registered as a hook on the group, so it follows
+/// membership changes.
+#[derive(Clone, Debug)]
+pub struct MultisigSuiteBuilder {
+    members: Vec<Member>,
+    threshold: Threshold,
+    max_voting_period: Duration,
+    executor: Option<Executor>,
+    funds: Vec<(String, Vec<Coin>)>,
+}
+
+impl Default for MultisigSuiteBuilder {
+    fn default() -> Self {
+        Self {
+            members: vec![],
+            threshold: Threshold::AbsoluteCount { weight: 1 },
+            max_voting_period: Duration::Time(3600),
+            executor: None,
+            funds: vec![],
+        }
+    }
+}
+
+impl MultisigSuiteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a group member, can be called multiple times
+    pub fn with_member(mut self, addr: &str, weight: u64) -> Self {
+        self.members.push(Member {
+            addr: addr.to_string(),
+            weight,
+        });
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: Threshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_voting_period(mut self, max_voting_period: Duration) -> Self {
+        self.max_voting_period = max_voting_period;
+        self
+    }
+
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Gives native tokens to an account on genesis, can be called multiple times
+    pub fn with_funds(mut self, addr: &str, funds: Vec<Coin>) -> Self {
+        self.funds.push((addr.to_string(), funds));
+        self
+    }
+
+    pub fn build(self) -> MultisigSuite {
+        let funds = self.funds;
+        let mut app = App::new(|router, _, storage| {
+            for (addr, coins) in funds {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(addr), coins)
+                    .unwrap();
+            }
+        });
+        let owner = Addr::unchecked("owner");
+
+        let group_id = app.store_code(contracts::cw4_group());
+        let group = app
+            .instantiate_contract(
+                group_id,
+                owner.clone(),
+                &cw4_group::msg::InstantiateMsg {
+                    admin: Some(owner.to_string()),
+                    members: self.members,
+                },
+                &[],
+                "group",
+                None,
+            )
+            .unwrap();
+
+        let multisig_id = app.store_code(contracts::cw3_flex_multisig());
+        let multisig = app
+            .instantiate_contract(
+                multisig_id,
+                owner.clone(),
+                &cw3_flex_multisig::msg::InstantiateMsg {
+                    group_addr: group.to_string(),
+                    threshold: self.threshold,
+                    max_voting_period: self.max_voting_period,
+                    executor: self.executor,
+                    proposal_deposit: None,
+                },
+                &[],
+                "multisig",
+                None,
+            )
+            .unwrap();
+
+        let add_hook = cw4_group::msg::ExecuteMsg::AddHook {
+            addr: multisig.to_string(),
+        };
+        app.execute_contract(owner.clone(), group.clone(), &add_hook, &[])
+            .unwrap();
+
+        // votes are weighted at the height a proposal was opened, which only
+        // includes the initial members from the next block on
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(5);
+        });
+
+        MultisigSuite {
+            app,
+            owner,
+            group,
+            multisig,
+        }
+    }
+}
+
+/// A `cw3-flex-multisig` with its `cw4-group` on a multi-test `App`
+pub struct MultisigSuite {
+    pub app: App,
+    /// Admin of the group
+    pub owner: Addr,
+    pub group: Addr,
+    pub multisig: Addr,
+}
+
+impl MultisigSuite {
+    /// Creates a proposal and returns its id
+    pub fn propose(&mut self, sender: &str, title: &str, msgs: Vec<CosmosMsg>) -> AnyResult<u64> {
+        let msg = cw3_flex_multisig::msg::ExecuteMsg::Propose {
+            title: title.to_string(),
+            description: title.to_string(),
+            msgs,
+            latest: None,
+        };
+        let res = self.execute(sender, &msg)?;
+        let id = res
+            .custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == "proposal_id")
+            .ok_or_else(|| anyhow!("no proposal_id in response"))?;
+        Ok(id.value.parse()?)
+    }
+
+    pub fn vote(&mut self, sender: &str, proposal_id: u64, vote: Vote) -> AnyResult<AppResponse> {
+        let msg = cw3_flex_multisig::msg::ExecuteMsg::Vote { proposal_id, vote };
+        self.execute(sender, &msg)
+    }
+
+    pub fn execute_proposal(&mut self, sender: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        let msg = cw3_flex_multisig::msg::ExecuteMsg::Execute { proposal_id };
+        self.execute(sender, &msg)
+    }
+
+    pub fn close(&mut self, sender: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        let msg = cw3_flex_multisig::msg::ExecuteMsg::Close { proposal_id };
+        self.execute(sender, &msg)
+    }
+
+    /// Updates the group as its admin
+    pub fn update_members(
+        &mut self,
+        add: Vec<Member>,
+        remove: Vec<String>,
+    ) -> AnyResult<AppResponse> {
+        let msg = cw4_group::msg::ExecuteMsg::UpdateMembers { remove, add };
+        self.app
+            .execute_contract(self.owner.clone(), self.group.clone(), &msg, &[])
+    }
+
+    pub fn proposal(&self, proposal_id: u64) -> ProposalResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.multisig,
+                &cw3_flex_multisig::msg::QueryMsg::Proposal { proposal_id },
+            )
+            .unwrap()
+    }
+
+    pub fn proposal_status(&self, proposal_id: u64) -> Status {
+        self.proposal(proposal_id).status
+    }
+
+    /// Moves the chain forward by the given number of blocks, 5 seconds each
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.app.update_block(|block| {
+            block.height += blocks;
+            block.time = block.time.plus_seconds(5 * blocks);
+        });
+    }
+
+    fn execute(
+        &mut self,
+        sender: &str,
+        msg: &cw3_flex_multisig::msg::ExecuteMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.multisig.clone(), msg, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_group_weight, assert_native_balance, assert_proposal_status};
+    use cosmwasm_std::{coins, BankMsg};
+
+    #[test]
+    fn proposal_lifecycle() {
+        let mut suite = MultisigSuiteBuilder::new()
+            .with_member("alice", 2)
+            .with_member("bob", 1)
+            .with_threshold(Threshold::AbsoluteCount { weight: 3 })
+            .with_funds("owner", coins(1000, "ucosm"))
+            .build();
+
+        // fund the multisig
+        let owner = suite.owner.clone();
+        let multisig = suite.multisig.clone();
+        suite
+            .app
+            .send_tokens(owner, multisig.clone(), &coins(500, "ucosm"))
+            .unwrap();
+
+        let payout = BankMsg::Send {
+            to_address: "carl".to_string(),
+            amount: coins(300, "ucosm"),
+        };
+        let id = suite
+            .propose("alice", "Pay carl", vec![payout.into()])
+            .unwrap();
+        assert_proposal_status(&suite.app, &multisig, id, Status::Open);
+
+        suite.vote("bob", id, Vote::Yes).unwrap();
+        assert_eq!(suite.proposal_status(id), Status::Passed);
+        suite.execute_proposal("bob", id).unwrap();
+        assert_proposal_status(&suite.app, &multisig, id, Status::Executed);
+        assert_native_balance(&suite.app, "carl", "ucosm", 300);
+
+        // a second proposal does not pass without bob
+        suite
+            .update_members(
+                vec![Member {
+                    addr: "carl".to_string(),
+                    weight: 1,
+                }],
+                vec!["bob".to_string()],
+            )
+            .unwrap();
+        assert_group_weight(&suite.app, &suite.group, "bob", None);
+        assert_group_weight(&suite.app, &suite.group, "carl", Some(1));
+
+        suite.advance_blocks(1);
+        let id = suite.propose("alice", "Nothing", vec![]).unwrap();
+        suite.advance_blocks(1000);
+        assert_eq!(suite.proposal_status(id), Status::Rejected);
+        suite.close("carl", id).unwrap();
+    }
+}
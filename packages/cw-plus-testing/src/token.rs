@@ -0,0 +1,194 @@
+use anyhow::Result as AnyResult;
+use cosmwasm_std::{Addr, Binary, Uint128};
+use cw20::{
+    BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse, TokenInfoResponse,
+};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use crate::contracts;
+
+/// Builds a [`Cw20Suite`], with a `cw20-base` token instantiated with the given balances
+/// and minter
+#[derive(Clone, Debug)]
+pub struct Cw20SuiteBuilder {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    initial_balances: Vec<Cw20Coin>,
+    mint: Option<MinterResponse>,
+}
+
+impl Default for Cw20SuiteBuilder {
+    fn default() -> Self {
+        Self {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: None,
+        }
+    }
+}
+
+impl Cw20SuiteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(mut self, name: &str, symbol: &str) -> Self {
+        self.name = name.to_string();
+        self.symbol = symbol.to_string();
+        self
+    }
+
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Adds an initial balance, can be called multiple times
+    pub fn with_balance(mut self, addr: &str, amount: u128) -> Self {
+        self.initial_balances.push(Cw20Coin {
+            address: addr.to_string(),
+            amount: Uint128::new(amount),
+        });
+        self
+    }
+
+    pub fn with_minter(mut self, minter: &str, cap: Option<u128>) -> Self {
+        self.mint = Some(MinterResponse {
+            minter: minter.to_string(),
+            cap: cap.map(Uint128::new),
+        });
+        self
+    }
+
+    /// Instantiates the token on an existing app, e.g. to combine it with other contracts
+    pub fn instantiate(self, app: &mut App, sender: &Addr) -> AnyResult<Addr> {
+        let code_id = app.store_code(contracts::cw20_base());
+        let msg = cw20_base::msg::InstantiateMsg {
+            name: self.name,
+            symbol: self.symbol.clone(),
+            decimals: self.decimals,
+            initial_balances: self.initial_balances,
+            mint: self.mint,
+            marketing: None,
+        };
+        app.instantiate_contract(code_id, sender.clone(), &msg, &[], self.symbol, None)
+    }
+
+    pub fn build(self) -> Cw20Suite {
+        let mut app = App::default();
+        let owner = Addr::unchecked("owner");
+        let token = self.instantiate(&mut app, &owner).unwrap();
+        Cw20Suite { app, owner, token }
+    }
+}
+
+/// A `cw20-base` token on a multi-test `App`, with helpers to call and query it
+pub struct Cw20Suite {
+    pub app: App,
+    /// Account that instantiated the token
+    pub owner: Addr,
+    pub token: Addr,
+}
+
+impl Cw20Suite {
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> AnyResult<AppResponse> {
+        let msg = Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        };
+        self.execute(sender, &msg)
+    }
+
+    pub fn send(
+        &mut self,
+        sender: &str,
+        contract: &str,
+        amount: u128,
+        msg: Binary,
+    ) -> AnyResult<AppResponse> {
+        let msg = Cw20ExecuteMsg::Send {
+            contract: contract.to_string(),
+            amount: Uint128::new(amount),
+            msg,
+        };
+        self.execute(sender, &msg)
+    }
+
+    pub fn mint(&mut self, sender: &str, recipient: &str, amount: u128) -> AnyResult<AppResponse> {
+        let msg = Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        };
+        self.execute(sender, &msg)
+    }
+
+    pub fn burn(&mut self, sender: &str, amount: u128) -> AnyResult<AppResponse> {
+        let msg = Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(amount),
+        };
+        self.execute(sender, &msg)
+    }
+
+    /// Executes any cw20 message on the token
+    pub fn execute(&mut self, sender: &str, msg: &Cw20ExecuteMsg) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.token.clone(), msg, &[])
+    }
+
+    pub fn balance(&self, addr: &str) -> u128 {
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.token,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance.u128()
+    }
+
+    pub fn token_info(&self) -> TokenInfoResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.token, &Cw20QueryMsg::TokenInfo {})
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_cw20_balance;
+
+    #[test]
+    fn transfer_and_mint() {
+        let mut suite = Cw20SuiteBuilder::new()
+            .with_name("Cash", "CASH")
+            .with_balance("alice", 1000)
+            .with_minter("minter", Some(1500))
+            .build();
+        assert_eq!(suite.token_info().total_supply, Uint128::new(1000));
+
+        suite.transfer("alice", "bob", 400).unwrap();
+        assert_eq!(suite.balance("alice"), 600);
+        assert_cw20_balance(&suite.app, &suite.token, "bob", 400);
+
+        suite.mint("alice", "alice", 100).unwrap_err();
+        suite.mint("minter", "bob", 500).unwrap();
+        suite.mint("minter", "bob", 1).unwrap_err();
+        assert_eq!(suite.balance("bob"), 900);
+
+        suite.burn("bob", 900).unwrap();
+        assert_eq!(suite.token_info().total_supply, Uint128::new(600));
+    }
+}
@@ -15,6 +15,8 @@ pub enum UncheckedDenom {
     Cw20(String),
 }
 
+// not used by any contract yet, newer compilers flag its private fields as dead code
+#[allow(dead_code)]
 #[cw_serde]
 pub struct DepositInfo {
     amount: Uint128,
@@ -18,9 +18,10 @@ pub use crate::helpers::Cw20Contract;
 pub use crate::logo::{EmbeddedLogo, Logo, LogoInfo};
 pub use crate::msg::Cw20ExecuteMsg;
 pub use crate::query::{
-    AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse, AllowanceInfo,
-    AllowanceResponse, BalanceResponse, Cw20QueryMsg, DownloadLogoResponse, MarketingInfoResponse,
-    MinterResponse, SpenderAllowanceInfo, TokenInfoResponse,
+    AllAccountsResponse, AllAllowancesResponse, AllMetadataResponse, AllSpenderAllowancesResponse,
+    AllowanceInfo, AllowanceResponse, BalanceResponse, Cw20QueryMsg, DownloadLogoResponse,
+    MarketingInfoResponse, MetadataInfo, MetadataResponse, MinterResponse, SpenderAllowanceInfo,
+    TokenInfoResponse,
 };
 pub use crate::receiver::Cw20ReceiveMsg;
 
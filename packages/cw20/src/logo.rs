@@ -23,6 +23,11 @@ pub enum EmbeddedLogo {
     /// Store the Logo as a PNG file. This will likely only support up to 64x64 or so
     /// within the 5KB limit.
     Png(Binary),
+    /// Store the Logo as a WebP file. Usually gives better compression than PNG
+    /// at the same dimensions.
+    Webp(Binary),
+    /// Store the Logo as a JPEG file.
+    Jpeg(Binary),
 }
 
 /// This is used to display logo info, provide a link or inform there is one
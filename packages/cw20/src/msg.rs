@@ -68,6 +68,13 @@ pub enum Cw20ExecuteMsg {
         /// The address (if any) who can update this data structure
         marketing: Option<String>,
     },
-    /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, PNG, WebP or JPEG
+    /// for the token
     UploadLogo(Logo),
+    /// Only with the "marketing" extension. If authorized, sets an arbitrary metadata entry
+    /// (socials, audit links, ISIN codes, ...), overwriting any previous value under this key.
+    SetMetadata { key: String, value: String },
+    /// Only with the "marketing" extension. If authorized, removes the metadata entry stored
+    /// under this key.
+    RemoveMetadata { key: String },
 }
@@ -34,6 +34,17 @@ pub enum Cw20QueryMsg {
     /// this contract.
     /// Return type: DownloadLogoResponse.
     DownloadLogo {},
+    /// Only with "marketing" extension
+    /// Returns the metadata value stored under the given key. Errors if there is none.
+    /// Return type: MetadataResponse.
+    Metadata { key: String },
+    /// Only with "marketing" extension
+    /// Returns all metadata entries, ordered by key. Supports pagination.
+    /// Return type: AllMetadataResponse.
+    AllMetadata {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Only with "enumerable" extension (and "allowances")
     /// Returns all allowances this owner has approved. Supports pagination.
     /// Return type: AllAllowancesResponse.
@@ -89,6 +100,9 @@ pub struct MarketingInfoResponse {
     pub logo: Option<LogoInfo>,
     /// The address (if any) who can update this data structure
     pub marketing: Option<Addr>,
+    /// Hex-encoded sha256 hash of the embedded logo data (if stored on chain). It changes every
+    /// time a new logo is uploaded, so it can be used as a cache key for `DownloadLogo`.
+    pub logo_hash: Option<String>,
 }
 
 /// When we download an embedded logo, we get this response type.
@@ -99,6 +113,22 @@ pub struct DownloadLogoResponse {
     pub data: Binary,
 }
 
+#[cw_serde]
+pub struct MetadataResponse {
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct MetadataInfo {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct AllMetadataResponse {
+    pub metadata: Vec<MetadataInfo>,
+}
+
 #[cw_serde]
 pub struct AllowanceInfo {
     pub spender: String,
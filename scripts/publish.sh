@@ -22,7 +22,10 @@ ALL_PACKAGES="controllers cw1 cw3 cw4 cw20"
 CW20_BASE="cw20-base"
 # these are imported by other contracts
 BASE_CONTRACTS="cw1-whitelist cw4-group cw3-fixed-multisig "
-ALL_CONTRACTS="cw1-subkeys cw3-flex-multisig cw4-stake cw20-ics20"
+ALL_CONTRACTS="cw1-subkeys cw3-flex-multisig cw4-stake cw20-ics20 cw20-wrapped cw20-rewards cw20-merkle-airdrop cw20-escrow cw20-atomic-swap cw20-bonding cw20-vesting cw20-streams cw20-staking"
+
+# this imports all the contracts
+TEST_PACKAGES="cw-plus-testing"
 
 SLEEP_TIME=30
 
@@ -83,4 +86,16 @@ for cont in $ALL_CONTRACTS; do
   )
 done
 
+# wait for these to be processed on crates.io
+echo "Waiting for publishing all contracts"
+sleep $SLEEP_TIME
+
+for pack in $TEST_PACKAGES; do
+  (
+    cd "packages/$pack"
+    echo "Publishing $pack"
+    cargo publish
+  )
+done
+
 echo "Everything is published!"